@@ -1,8 +1,11 @@
 use facet::ShapeLayout;
 
 use crate::*;
+use std::any::TypeId;
+use std::collections::{HashSet, VecDeque};
 use std::error::Error;
 use std::fmt;
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub enum ConversionError {
@@ -10,6 +13,9 @@ pub enum ConversionError {
     NotAStruct(String),
     MissingTypeInfo,
     MultiplePrimaryKeys(String),
+    NameCollision(String),
+    InvalidAttribute(String),
+    InvalidReference(String),
 }
 
 impl fmt::Display for ConversionError {
@@ -21,6 +27,9 @@ impl fmt::Display for ConversionError {
             ConversionError::MultiplePrimaryKeys(msg) => {
                 write!(f, "Multiple primary keys defined: {}", msg)
             }
+            ConversionError::NameCollision(msg) => write!(f, "Name collision: {}", msg),
+            ConversionError::InvalidAttribute(msg) => write!(f, "Invalid attribute: {}", msg),
+            ConversionError::InvalidReference(msg) => write!(f, "Invalid reference: {}", msg),
         }
     }
 }
@@ -31,72 +40,1795 @@ impl TryFrom<&facet::Shape> for PartialSchema {
     type Error = ConversionError;
 
     fn try_from(shape: &facet::Shape) -> Result<Self, Self::Error> {
+        let mut converter = Converter::new(ConversionOptions::default());
+        converter.add_root(shape)?;
+        Ok(converter.schema)
+    }
+}
+
+/// Assembles one [`PartialSchema`] from any number of root shapes.
+///
+/// Tables reached from several roots are emitted once. Two different shapes
+/// that map to the same table name are reported as
+/// [`ConversionError::NameCollision`].
+///
+/// ```ignore
+/// let schema = SchemaBuilder::new()
+///     .register(User::SHAPE)
+///     .register(Post::SHAPE)
+///     .build()?;
+/// ```
+#[derive(Default)]
+pub struct SchemaBuilder {
+    roots: Vec<&'static facet::Shape>,
+    options: ConversionOptions,
+}
+
+/// Schema-wide switches for how Rust types are mapped.
+#[derive(Clone, Debug)]
+pub struct ConversionOptions {
+    /// Map nested struct fields to composite types instead of `jsonb`.
+    /// Individual fields can opt in with `psql::composite`.
+    pub composite_types: bool,
+    /// Store `Vec`s, slices and fixed-size arrays as `jsonb` instead of
+    /// native arrays. Individual fields can opt out with `psql::jsonb`.
+    pub arrays_as_jsonb: bool,
+    /// How table and column names are derived from Rust names. Defaults to
+    /// [`Lowercase`].
+    pub naming: Arc<dyn NamingStrategy>,
+    /// Mappings for types the converter does not know, such as `uuid::Uuid`.
+    /// Defaults to the built-in ones of the enabled cargo features.
+    pub types: TypeRegistry,
+}
+
+impl Default for ConversionOptions {
+    fn default() -> Self {
+        ConversionOptions {
+            composite_types: false,
+            arrays_as_jsonb: false,
+            naming: Arc::new(Lowercase),
+            types: TypeRegistry::default(),
+        }
+    }
+}
+
+impl SchemaBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a root shape. Conversion happens in [`SchemaBuilder::build`].
+    pub fn register(mut self, shape: &'static facet::Shape) -> Self {
+        self.roots.push(shape);
+        self
+    }
+
+    /// Set the options used for every registered shape.
+    pub fn options(mut self, options: ConversionOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Convert every registered shape into one merged schema.
+    pub fn build(self) -> Result<PartialSchema, ConversionError> {
+        let mut converter = Converter::new(self.options);
+        for shape in self.roots {
+            converter.add_root(shape)?;
+        }
+        Ok(converter.schema)
+    }
+}
+
+/// Which shape (and enum variant, for variant tables) a table was generated from.
+#[derive(PartialEq)]
+struct TableOrigin {
+    type_id: TypeId,
+    variant: Option<String>,
+}
+
+/// Accumulates the schema for one or more root shapes.
+struct Converter {
+    options: ConversionOptions,
+    schema: PartialSchema,
+    origins: HashMap<String, (TableOrigin, String)>,
+    /// Which shape each enum or composite type name was generated from.
+    type_origins: HashMap<String, (TypeId, String)>,
+    /// Shapes already turned into tables.
+    converted: HashSet<TypeId>,
+    /// Shapes reached through `psql::references` that still need converting.
+    pending: VecDeque<&'static facet::Shape>,
+}
+
+impl Converter {
+    fn new(options: ConversionOptions) -> Self {
+        Converter {
+            options,
+            schema: PartialSchema::default(),
+            origins: HashMap::new(),
+            type_origins: HashMap::new(),
+            converted: HashSet::new(),
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Convert `shape` and everything it references.
+    fn add_root(&mut self, shape: &facet::Shape) -> Result<(), ConversionError> {
+        self.add_shape(shape)?;
+        while let Some(shape) = self.pending.pop_front() {
+            self.add_shape(shape)?;
+        }
+        Ok(())
+    }
+
+    fn add_shape(&mut self, shape: &facet::Shape) -> Result<(), ConversionError> {
+        if !self.converted.insert(shape.id.get()) {
+            return Ok(());
+        }
         match shape.ty {
-            facet::Type::User(facet::UserType::Struct(_)) => {
-                let table = shape_to_table(shape)?;
-                Ok(PartialSchema {
-                    tables: vec![table],
-                    views: Default::default(),
-                    materialized_views: Default::default(),
-                    enums: Default::default(),
-                    domains: Default::default(),
-                    composite_types: Default::default(),
-                    sequences: Default::default(),
-                    collations: Default::default(),
-                    functions: Default::default(),
-                })
-            }
-            facet::Type::User(facet::UserType::Enum(ref e)) => enum_to_partial_schema(shape, e),
+            facet::Type::User(facet::UserType::Struct(_)) => self.add_struct(shape),
+            facet::Type::User(facet::UserType::Enum(ref e)) => self.add_enum(shape, e),
             _ => Err(ConversionError::NotAStruct(format!("{:?}", shape.ty))),
         }
     }
+
+    /// Add `table`, skipping it if the same shape already produced it.
+    fn add_table(
+        &mut self,
+        table: Table,
+        shape: &facet::Shape,
+        variant: Option<&str>,
+    ) -> Result<(), ConversionError> {
+        let origin = TableOrigin {
+            type_id: shape.id.get(),
+            variant: variant.map(str::to_string),
+        };
+        let description = match variant {
+            Some(v) => format!("{}::{}", shape, v),
+            None => shape.to_string(),
+        };
+
+        if let Some((existing, existing_description)) = self.origins.get(&table.name) {
+            if *existing == origin {
+                return Ok(());
+            }
+            return Err(ConversionError::NameCollision(format!(
+                "Table '{}' is generated by both {} and {}",
+                table.name, existing_description, description
+            )));
+        }
+
+        self.origins
+            .insert(table.name.clone(), (origin, description));
+        self.schema.tables.push(table);
+        Ok(())
+    }
+
+    /// Declare the types that mapping some columns produced.
+    fn add_types(&mut self, types: DerivedTypes) -> Result<(), ConversionError> {
+        for derived in types.enums {
+            if self.claim_type_name(&derived.ty.name, derived.type_id, derived.description)? {
+                self.schema.enums.push(derived.ty);
+            }
+        }
+        for derived in types.composites {
+            if self.claim_type_name(&derived.ty.name, derived.type_id, derived.description)? {
+                self.schema.composite_types.push(derived.ty);
+            }
+        }
+        for derived in types.domains {
+            if self.claim_type_name(&derived.ty.name, derived.type_id, derived.description)? {
+                self.schema.domains.push(derived.ty);
+            }
+        }
+        Ok(())
+    }
+
+    /// Record that `type_id` generates the type `name`. Returns `false` if it
+    /// already did, and an error if another shape did.
+    fn claim_type_name(
+        &mut self,
+        name: &str,
+        type_id: TypeId,
+        description: String,
+    ) -> Result<bool, ConversionError> {
+        if let Some((existing, existing_description)) = self.type_origins.get(name) {
+            if *existing == type_id {
+                return Ok(false);
+            }
+            return Err(ConversionError::NameCollision(format!(
+                "Type '{}' is generated by both {} and {}",
+                name, existing_description, description
+            )));
+        }
+        self.type_origins
+            .insert(name.to_string(), (type_id, description));
+        Ok(true)
+    }
 }
 
-fn shape_to_table(shape: &facet::Shape) -> Result<Table, ConversionError> {
+impl Converter {
+    fn add_struct(&mut self, shape: &facet::Shape) -> Result<(), ConversionError> {
+        // Newtypes are column types, not tables
+        if newtype(shape)?.is_some() {
+            let mut types = DerivedTypes::default();
+            shape_to_data_type(shape, TypeHints::new(&self.options), &mut types)?;
+            return self.add_types(types);
+        }
+        let (table, fields) = struct_table(shape, &self.options)?;
+        self.pending.extend(fields.referenced);
+        self.add_types(fields.types)?;
+        self.add_table(table, shape, None)
+    }
+}
+
+/// The table for a struct shape. Columns and constraints move into the
+/// table; the rest of what the fields produced is returned alongside it.
+fn struct_table(
+    shape: &facet::Shape,
+    options: &ConversionOptions,
+) -> Result<(Table, ProcessedFields), ConversionError> {
     // Get the struct type definition
     let struct_type = match &shape.ty {
         facet::Type::User(facet::UserType::Struct(s)) => s,
         _ => return Err(ConversionError::NotAStruct(format!("{:?}", shape.ty))),
     };
 
-    // Table name is the lowercase type identifier
-    let table_name = shape.type_identifier.to_lowercase();
+    let table_name = table_name(shape, options);
+    let naming = column_naming(shape, options);
 
     // Process fields
-    let (columns, primary_key) = process_fields(&struct_type.fields, &table_name)?;
+    let mut fields = process_fields(struct_type.fields, &table_name, "", naming, options)?;
+    apply_table_attributes(shape, struct_type.fields, &table_name, naming, &mut fields)?;
 
-    Ok(Table {
+    let table = Table {
         name: table_name,
-        columns,
-        primary_key,
-        uniques: vec![],
-        foreign_keys: vec![],
-        checks: vec![],
-        indexes: vec![],
-        options: TableOptions {
-            inherits: vec![],
-            temporary: false,
-            unlogged: false,
-            partitioned: None,
-            tablespace: None,
-            with_storage_params: Default::default(),
-        },
+        columns: std::mem::take(&mut fields.columns),
+        primary_key: fields.primary_key.take(),
+        uniques: std::mem::take(&mut fields.uniques),
+        foreign_keys: std::mem::take(&mut fields.foreign_keys),
+        checks: std::mem::take(&mut fields.checks),
+        indexes: std::mem::take(&mut fields.indexes),
+        options: empty_table_options(),
         comment: None,
         owned_sequences: vec![],
-    })
+        rows: vec![],
+    };
+    Ok((table, fields))
+}
+
+/// Adds the constraints declared on the struct itself: `psql::primary_key`,
+/// `psql::unique` and `psql::check`.
+fn apply_table_attributes(
+    shape: &facet::Shape,
+    struct_fields: &'static [facet::Field],
+    table_name: &str,
+    naming: &dyn NamingStrategy,
+    fields: &mut ProcessedFields,
+) -> Result<(), ConversionError> {
+    // Columns of flattened fields are only found by their column name
+    let column_of =
+        |fields: &ProcessedFields, list: &str| -> Result<Vec<String>, ConversionError> {
+            list.split(',')
+                .map(str::trim)
+                .map(|name| {
+                    if fields.columns.iter().any(|c| c.name == name) {
+                        return Ok(name.to_string());
+                    }
+                    let field = named_fields(name, struct_fields, table_name, naming)?[0];
+                    fields
+                        .sources
+                        .iter()
+                        .position(|path| path.len() == 1 && std::ptr::eq(path[0], field))
+                        .map(|index| fields.columns[index].name.clone())
+                        .ok_or_else(|| {
+                            ConversionError::InvalidAttribute(format!(
+                                "'{}' has no column '{}'",
+                                table_name, name
+                            ))
+                        })
+                })
+                .collect()
+        };
+
+    for attr in shape.attributes.iter().filter(|a| a.ns == Some("psql")) {
+        match attr.key {
+            "primary_key" => {
+                let list = attr_columns(attr).ok_or_else(|| missing_columns(table_name, attr))?;
+                if let Some(pk) = &fields.primary_key {
+                    return Err(ConversionError::MultiplePrimaryKeys(format!(
+                        "Table '{}' declares a primary key on the struct and on {:?}",
+                        table_name, pk.columns
+                    )));
+                }
+                fields.primary_key = Some(PrimaryKey {
+                    name: None,
+                    columns: column_of(fields, list)?,
+                    using: None,
+                    deferrable: None,
+                });
+            }
+            "unique" => {
+                let list = attr_columns(attr).ok_or_else(|| missing_columns(table_name, attr))?;
+                let columns = column_of(fields, list)?;
+                fields.uniques.push(UniqueConstraint {
+                    name: None,
+                    columns,
+                    deferrable: None,
+                });
+            }
+            // Unless named, left unnamed so they are numbered like PostgreSQL does
+            "check" => {
+                if let Some(spec) = attr.get_as::<&'static str>() {
+                    let (name, expression) = check_clause(spec, table_name)?;
+                    fields.checks.push(CheckConstraint {
+                        name: name.map(str::to_string),
+                        expression: expression.to_string(),
+                        no_inherit: false,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let indexes = declared_indexes(shape.attributes, None, table_name, |name| {
+        Ok(column_of(fields, name)?.remove(0))
+    })?;
+    fields.indexes.extend(indexes);
+    name_indexes(table_name, &mut fields.indexes);
+    Ok(())
+}
+
+/// Indexes declared by `psql::index` among `attrs`. `column` is the column of
+/// the field they are on, if any; `resolve` maps a field name to its column.
+fn declared_indexes(
+    attrs: &'static [facet::Attr],
+    column: Option<&str>,
+    table_name: &str,
+    resolve: impl Fn(&str) -> Result<String, ConversionError>,
+) -> Result<Vec<Index>, ConversionError> {
+    let mut indexes: Vec<Index> = Vec::new();
+    for attr in attrs
+        .iter()
+        .filter(|a| a.ns == Some("psql") && a.key == "index")
+    {
+        let definition = match attr_columns(attr) {
+            Some(spec) => index_definition(spec, table_name)?,
+            None => IndexDefinition::default(),
+        };
+        let columns = match (definition.columns, column) {
+            (Some(_), Some(column)) => {
+                return Err(ConversionError::InvalidAttribute(format!(
+                    "'{}.{}' lists columns, which only struct-level attributes take",
+                    table_name, column
+                )));
+            }
+            (Some(list), None) => list
+                .split(',')
+                .map(|item| index_column(item, table_name, &resolve))
+                .collect::<Result<_, _>>()?,
+            (None, Some(column)) => vec![IndexColumn {
+                expr: IndexExpr::Column(column.to_string()),
+                collate: None,
+                opclass: None,
+                order: None,
+                nulls_order: None,
+            }],
+            (None, None) => return Err(missing_columns(table_name, attr)),
+        };
+        let include = match definition.include {
+            Some(list) => list
+                .split(',')
+                .map(|name| resolve(name.trim()))
+                .collect::<Result<_, _>>()?,
+            None => vec![],
+        };
+        indexes.push(Index {
+            name: definition.name.unwrap_or_default().to_string(),
+            columns,
+            unique: false,
+            method: definition.method.map(str::to_string),
+            predicate: definition.predicate.map(str::to_string),
+            include,
+            tablespace: None,
+            concurrently: false,
+            is_primary: false,
+            is_valid: true,
+        });
+    }
+    Ok(indexes)
+}
+
+/// The parts of a `psql::index` definition.
+#[derive(Default)]
+struct IndexDefinition<'s> {
+    name: Option<&'s str>,
+    method: Option<&'s str>,
+    columns: Option<&'s str>,
+    include: Option<&'s str>,
+    predicate: Option<&'s str>,
+}
+
+/// Parses `[name] [USING method] [(columns)] [INCLUDE (columns)] [WHERE
+/// predicate]`. A definition with none of these keywords or parentheses is a
+/// bare column list.
+fn index_definition<'s>(
+    spec: &'s str,
+    table_name: &str,
+) -> Result<IndexDefinition<'s>, ConversionError> {
+    let invalid = || {
+        ConversionError::InvalidAttribute(format!(
+            "psql::index(\"{}\") on '{}' should read \"[name] [USING method] [(columns)] [INCLUDE (columns)] [WHERE predicate]\"",
+            spec.trim(),
+            table_name
+        ))
+    };
+    let mut definition = IndexDefinition::default();
+    let mut rest = spec.trim();
+    if let Some(at) = find_keyword(rest, "WHERE") {
+        let predicate = rest[at + "WHERE".len()..].trim();
+        if predicate.is_empty() {
+            return Err(invalid());
+        }
+        definition.predicate = Some(predicate);
+        rest = rest[..at].trim_end();
+    }
+    if let Some(at) = find_keyword(rest, "INCLUDE") {
+        definition.include =
+            Some(parenthesized(&rest[at + "INCLUDE".len()..]).ok_or_else(invalid)?);
+        rest = rest[..at].trim_end();
+    }
+    let head = match rest.find('(') {
+        Some(open) => {
+            definition.columns = Some(parenthesized(&rest[open..]).ok_or_else(invalid)?);
+            &rest[..open]
+        }
+        None => rest,
+    };
+    let words: Vec<_> = head.split_whitespace().collect();
+    let using = words.iter().position(|w| w.eq_ignore_ascii_case("USING"));
+    let names = match using {
+        Some(at) => match &words[at + 1..] {
+            [method] => {
+                definition.method = Some(method);
+                &words[..at]
+            }
+            _ => return Err(invalid()),
+        },
+        None if definition.columns.is_none()
+            && definition.include.is_none()
+            && definition.predicate.is_none() =>
+        {
+            definition.columns = Some(head).filter(|list| !list.trim().is_empty());
+            &[][..]
+        }
+        None => &words[..],
+    };
+    match names {
+        [] => {}
+        [name] => definition.name = Some(name),
+        _ => return Err(invalid()),
+    }
+    Ok(definition)
+}
+
+/// Byte offset of `keyword` (in any case) as a whole word outside parentheses.
+fn find_keyword(text: &str, keyword: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut word_start = true;
+    for (at, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            _ if depth == 0
+                && word_start
+                && text[at..]
+                    .get(..keyword.len())
+                    .is_some_and(|head| head.eq_ignore_ascii_case(keyword))
+                && text[at + keyword.len()..]
+                    .chars()
+                    .next()
+                    .is_none_or(|next| next.is_whitespace() || next == '(') =>
+            {
+                return Some(at);
+            }
+            _ => {}
+        }
+        word_start = c.is_whitespace() || c == ')';
+    }
+    None
+}
+
+/// The inside of `text` when it is exactly one parenthesized group.
+fn parenthesized(text: &str) -> Option<&str> {
+    let inner = text.trim().strip_prefix('(')?.strip_suffix(')')?;
+    (!inner.contains(['(', ')']) && !inner.trim().is_empty()).then_some(inner)
+}
+
+/// One entry of a `psql::index` column list: a field name, optionally followed
+/// by an operator class, `ASC`/`DESC` and `NULLS FIRST`/`NULLS LAST`.
+fn index_column(
+    item: &str,
+    table_name: &str,
+    resolve: impl Fn(&str) -> Result<String, ConversionError>,
+) -> Result<IndexColumn, ConversionError> {
+    let invalid = || {
+        ConversionError::InvalidAttribute(format!(
+            "invalid psql::index column '{}' on '{}'",
+            item.trim(),
+            table_name
+        ))
+    };
+    let mut words = item.split_whitespace();
+    let mut column = IndexColumn {
+        expr: IndexExpr::Column(resolve(words.next().ok_or_else(invalid)?)?),
+        collate: None,
+        opclass: None,
+        order: None,
+        nulls_order: None,
+    };
+    while let Some(word) = words.next() {
+        match word.to_ascii_uppercase().as_str() {
+            "ASC" => column.order = Some(SortOrder::Asc),
+            "DESC" => column.order = Some(SortOrder::Desc),
+            "NULLS" => {
+                let nulls = words.next().map(str::to_ascii_uppercase);
+                column.nulls_order = match nulls.as_deref() {
+                    Some("FIRST") => Some(NullsOrder::First),
+                    Some("LAST") => Some(NullsOrder::Last),
+                    _ => return Err(invalid()),
+                }
+            }
+            _ if column.opclass.is_none() && column.order.is_none() => {
+                column.opclass = Some(word.to_string())
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    Ok(column)
+}
+
+/// Names the unnamed indexes `<table>_idx_<columns>`, numbering any that
+/// would clash with an earlier one.
+fn name_indexes(table_name: &str, indexes: &mut [Index]) {
+    let mut taken: Vec<String> = indexes
+        .iter()
+        .filter(|idx| !idx.name.is_empty())
+        .map(|idx| idx.name.clone())
+        .collect();
+    for idx in indexes.iter_mut().filter(|idx| idx.name.is_empty()) {
+        let base = idx.effective_name(table_name);
+        let mut name = base.clone();
+        let mut n = 0;
+        while taken.contains(&name) {
+            n += 1;
+            name = format!("{}{}", base, n);
+        }
+        taken.push(name.clone());
+        idx.name = name;
+    }
+}
+
+/// The column list of a struct-level `psql::primary_key(...)` or
+/// `psql::unique(...)`, or the definition of a `psql::index(...)`.
+fn attr_columns(attr: &facet::Attr) -> Option<&'static str> {
+    match attr.get_as::<crate::Attr>()? {
+        crate::Attr::PrimaryKey(columns)
+        | crate::Attr::Unique(columns)
+        | crate::Attr::Index(columns) => *columns,
+        _ => None,
+    }
+}
+
+fn missing_columns(table_name: &str, attr: &facet::Attr) -> ConversionError {
+    ConversionError::InvalidAttribute(format!(
+        "psql::{} on '{}' needs a column list, e.g. psql::{}(\"a, b\")",
+        attr.key, table_name, attr.key
+    ))
+}
+
+/// The fields named in a comma-separated list, by field or column name.
+fn named_fields(
+    list: &str,
+    fields: &'static [facet::Field],
+    table_name: &str,
+    naming: &dyn NamingStrategy,
+) -> Result<Vec<&'static facet::Field>, ConversionError> {
+    list.split(',')
+        .map(str::trim)
+        .map(|name| {
+            fields
+                .iter()
+                .find(|f| f.name == name || column_name(f, fields, naming) == name)
+                .ok_or_else(|| {
+                    ConversionError::InvalidAttribute(format!(
+                        "'{}' has no column '{}'",
+                        table_name, name
+                    ))
+                })
+        })
+        .collect()
+}
+
+/// The fields leading to a column's value: the field of the struct or
+/// variant, then the fields within it for flattened structs.
+pub(crate) type FieldPath = Vec<&'static facet::Field>;
+
+/// How the fields of a struct line up with the columns of its table.
+pub(crate) struct RowLayout {
+    pub(crate) table: Table,
+    /// The fields each column is read from, in column order.
+    pub(crate) fields: Vec<FieldPath>,
+    /// Enum types used by the columns, by name.
+    pub(crate) enums: HashMap<String, EnumType>,
+    /// Composite types used by the columns, by name.
+    pub(crate) composites: HashMap<String, CompositeType>,
+    /// Domains used by the columns, by name.
+    pub(crate) domains: HashMap<String, DomainType>,
+    /// Mappings for the types the converter does not know.
+    pub(crate) registry: TypeRegistry,
+}
+
+impl RowLayout {
+    pub(crate) fn of(
+        shape: &facet::Shape,
+        options: &ConversionOptions,
+    ) -> Result<Self, ConversionError> {
+        let (table, fields) = struct_table(shape, options)?;
+        Ok(RowLayout::new(table, fields, options))
+    }
+
+    fn new(table: Table, fields: ProcessedFields, options: &ConversionOptions) -> Self {
+        RowLayout {
+            table,
+            fields: fields.sources,
+            enums: fields
+                .types
+                .enums
+                .into_iter()
+                .map(|derived| (derived.ty.name.clone(), derived.ty))
+                .collect(),
+            composites: fields
+                .types
+                .composites
+                .into_iter()
+                .map(|derived| (derived.ty.name.clone(), derived.ty))
+                .collect(),
+            domains: fields
+                .types
+                .domains
+                .into_iter()
+                .map(|derived| (derived.ty.name.clone(), derived.ty))
+                .collect(),
+            registry: options.types.clone(),
+        }
+    }
+
+    /// Each column with the fields it is read from. Identity columns are
+    /// generated by the database and have no field.
+    pub(crate) fn columns(&self) -> impl Iterator<Item = (&Column, &[&'static facet::Field])> {
+        self.table
+            .columns
+            .iter()
+            .filter(|c| !c.is_identity)
+            .zip(self.fields.iter().map(Vec::as_slice))
+    }
+}
+
+/// How an enum lines up with its main and variant tables.
+pub(crate) struct EnumLayout {
+    /// The main table, with the discriminant and variant columns.
+    pub(crate) table: Table,
+    pub(crate) discriminant: DiscriminantColumn,
+    pub(crate) variants: Vec<VariantLayout>,
+}
+
+pub(crate) struct VariantLayout {
+    /// Position of the variant among the enum's variants.
+    pub(crate) index: usize,
+    /// Column of the main table referencing the variant's row.
+    pub(crate) column: String,
+    pub(crate) row: RowLayout,
+}
+
+impl EnumLayout {
+    pub(crate) fn of(
+        shape: &facet::Shape,
+        options: &ConversionOptions,
+    ) -> Result<Self, ConversionError> {
+        let facet::Type::User(facet::UserType::Enum(enum_type)) = &shape.ty else {
+            return Err(ConversionError::NotAStruct(format!("{:?}", shape.ty)));
+        };
+        if enum_storage(shape, enum_type)? != EnumStorage::TablePerVariant
+            || is_unit_only(enum_type)
+        {
+            return Err(ConversionError::UnsupportedType(format!(
+                "{} is not stored as a table per variant",
+                shape
+            )));
+        }
+        let discriminant = DiscriminantColumn::of(shape, enum_type, options)?;
+        let tables = enum_tables(shape, enum_type, &discriminant, options)?;
+        Ok(EnumLayout {
+            table: tables.main,
+            discriminant,
+            variants: tables
+                .variants
+                .into_iter()
+                .map(|v| VariantLayout {
+                    index: v.index,
+                    column: v.column,
+                    row: RowLayout::new(v.table, v.fields, options),
+                })
+                .collect(),
+        })
+    }
+
+    pub(crate) fn variant(&self, index: usize) -> Option<&VariantLayout> {
+        self.variants.iter().find(|v| v.index == index)
+    }
+}
+
+/// PostgreSQL enum label for a unit variant of `shape`: facet's `rename` or
+/// `rename_all` as given, so labels match what facet serializes, else the
+/// naming strategy's name for the variant.
+fn enum_label(
+    shape: &facet::Shape,
+    variant: &facet::Variant,
+    naming: &dyn NamingStrategy,
+) -> String {
+    let renamed = variant.has_attr(None, "rename")
+        || shape
+            .attributes
+            .iter()
+            .any(|attr| attr.ns.is_none() && attr.key == "rename_all");
+    if renamed {
+        variant.name.to_string()
+    } else {
+        naming.variant_name(variant.name)
+    }
+}
+
+/// Table name for a struct or enum shape: `psql::table` or facet's `rename`
+/// if given, else the naming strategy's name for the type identifier.
+fn table_name(shape: &facet::Shape, options: &ConversionOptions) -> String {
+    let explicit = shape_attr(shape, "table").or_else(|| {
+        shape
+            .attributes
+            .iter()
+            .find(|attr| attr.ns.is_none() && attr.key == "rename")
+    });
+    match explicit.and_then(|attr| attr.get_as::<&'static str>()) {
+        Some(name) => name.to_string(),
+        None => options.naming.table_name(shape.type_identifier),
+    }
+}
+
+/// Name of the enum, composite or domain type for a shape: facet's `rename`
+/// if given, else the naming strategy's name for the type identifier.
+fn type_name(shape: &facet::Shape, naming: &dyn NamingStrategy) -> String {
+    let renamed = shape
+        .attributes
+        .iter()
+        .find(|attr| attr.ns.is_none() && attr.key == "rename")
+        .and_then(|attr| attr.get_as::<&'static str>());
+    match renamed {
+        Some(name) => name.to_string(),
+        None => naming.type_name(shape.type_identifier),
+    }
+}
+
+/// The strategy naming the columns of a struct's fields. facet's
+/// `rename_all` has already renamed them, so they're kept as they are.
+fn column_naming<'a>(
+    shape: &facet::Shape,
+    options: &'a ConversionOptions,
+) -> &'a dyn NamingStrategy {
+    let renamed = shape
+        .attributes
+        .iter()
+        .any(|attr| attr.ns.is_none() && attr.key == "rename_all");
+    if renamed {
+        &Lowercase
+    } else {
+        &*options.naming
+    }
+}
+
+/// Per-field switches that change how a field's type is mapped.
+#[derive(Clone, Copy)]
+struct TypeHints<'o> {
+    /// Store unit-only enums as an integer instead of a PostgreSQL enum.
+    enum_as_integer: bool,
+    /// Store nested structs as composite types instead of `jsonb`.
+    struct_as_composite: bool,
+    /// Store sequences as `jsonb` instead of native arrays.
+    sequence_as_jsonb: bool,
+    /// Store byte sequences as native arrays instead of `bytea`.
+    bytes_as_array: bool,
+    /// Mappings for the types the converter does not know.
+    registry: &'o TypeRegistry,
+    /// Names the enum, composite and domain types and enum labels.
+    naming: &'o dyn NamingStrategy,
+}
+
+impl<'o> TypeHints<'o> {
+    /// Hints for a shape on its own, outside of any field.
+    fn new(options: &'o ConversionOptions) -> Self {
+        TypeHints {
+            enum_as_integer: false,
+            struct_as_composite: false,
+            sequence_as_jsonb: false,
+            bytes_as_array: false,
+            registry: &options.types,
+            naming: &*options.naming,
+        }
+    }
+
+    fn for_field(field: &facet::Field, options: &'o ConversionOptions) -> Self {
+        Self::from_attrs(
+            field,
+            options.composite_types,
+            options.arrays_as_jsonb,
+            &options.types,
+            &*options.naming,
+        )
+    }
+
+    /// Hints for a field of a composite type, inheriting from the outer field.
+    fn for_nested(field: &facet::Field, outer: TypeHints<'o>) -> Self {
+        Self::from_attrs(
+            field,
+            true,
+            outer.sequence_as_jsonb,
+            outer.registry,
+            outer.naming,
+        )
+    }
+
+    fn from_attrs(
+        field: &facet::Field,
+        composite: bool,
+        sequence_as_jsonb: bool,
+        registry: &'o TypeRegistry,
+        naming: &'o dyn NamingStrategy,
+    ) -> Self {
+        // `psql::jsonb` wins over any schema-wide setting
+        let jsonb = field.has_attr(Some("psql"), "jsonb");
+        TypeHints {
+            enum_as_integer: field.has_attr(Some("psql"), "as_integer"),
+            struct_as_composite: !jsonb && (composite || field.has_attr(Some("psql"), "composite")),
+            sequence_as_jsonb: jsonb || sequence_as_jsonb,
+            bytes_as_array: field.has_attr(Some("psql"), "array"),
+            registry,
+            naming,
+        }
+    }
 }
 
-fn field_to_column(field: &facet::Field) -> Result<Column, ConversionError> {
+/// Types created while mapping columns, which the schema must declare.
+/// Each list is in dependency order.
+#[derive(Default)]
+struct DerivedTypes {
+    enums: Vec<Derived<EnumType>>,
+    composites: Vec<Derived<CompositeType>>,
+    domains: Vec<Derived<DomainType>>,
+}
+
+struct Derived<T> {
+    ty: T,
+    type_id: TypeId,
+    description: String,
+}
+
+impl<T> Derived<T> {
+    fn new(ty: T, shape: &facet::Shape) -> Self {
+        Derived {
+            ty,
+            type_id: shape.id.get(),
+            description: shape.to_string(),
+        }
+    }
+}
+
+/// `siblings` are the fields of the struct or variant `field` belongs to.
+fn field_to_column(
+    field: &facet::Field,
+    siblings: &[facet::Field],
+    naming: &dyn NamingStrategy,
+    hints: TypeHints,
+    types: &mut DerivedTypes,
+) -> Result<Column, ConversionError> {
     // Call the shape function to get the field type
     let field_shape = field.shape();
 
-    let (data_type, nullable) = shape_to_data_type(field_shape)?;
+    let (mut data_type, nullable) = shape_to_data_type(field_shape, hints, types)?;
+    if let Some(declared) = declared_type(field)? {
+        let lossy = field.has_attr(Some("psql"), "lossy");
+        if !storable_as(&data_type, &declared, lossy) {
+            let hint = if storable_as(&data_type, &declared, true) {
+                " without psql::lossy, as it doesn't hold every value"
+            } else {
+                ""
+            };
+            return Err(ConversionError::InvalidAttribute(format!(
+                "field '{}' of type {} is stored as {} and cannot be declared {}{}",
+                field.name,
+                field_shape,
+                render_data_type(&data_type),
+                render_data_type(&declared),
+                hint
+            )));
+        }
+        data_type = declared;
+    }
 
     Ok(Column {
-        name: field.name.to_string(),
+        name: column_name(field, siblings, naming),
         data_type,
         default: None,
-        nullable,
+        nullable,
+        collation: None,
+        is_generated: false,
+        generation_expression: None,
+        is_identity: false,
+        identity_generation: None,
+        comment: None,
+        privileges: None,
+    })
+}
+
+/// The type given with `psql::type`, `psql::varchar`, `psql::numeric` or
+/// `psql::citext`.
+fn declared_type(field: &facet::Field) -> Result<Option<DataType>, ConversionError> {
+    let Some(attr) = field.attributes.iter().find(|attr| {
+        attr.ns == Some("psql") && matches!(attr.key, "type" | "varchar" | "numeric" | "citext")
+    }) else {
+        return Ok(None);
+    };
+    let data_type = match (attr.key, attr.get_as::<crate::Attr>()) {
+        (_, Some(crate::Attr::Varchar(length))) => DataType::Varchar(*length),
+        (_, Some(crate::Attr::Numeric(None))) => DataType::Numeric {
+            precision: None,
+            scale: None,
+        },
+        (_, Some(crate::Attr::Numeric(Some([precision, scale])))) => {
+            if !(1..=1000).contains(precision) || scale > precision {
+                return Err(ConversionError::InvalidAttribute(format!(
+                    "field '{}' declares numeric({}, {}), but the precision must be 1 to 1000 and the scale at most the precision",
+                    field.name, precision, scale
+                )));
+            }
+            DataType::Numeric {
+                precision: Some(*precision),
+                scale: Some(*scale),
+            }
+        }
+        ("citext", _) => DataType::Custom {
+            schema: None,
+            name: "citext".to_string(),
+        },
+        _ => {
+            let text = attr.get_as::<&'static str>().copied().unwrap_or_default();
+            parse_data_type(text).ok_or_else(|| {
+                ConversionError::InvalidAttribute(format!(
+                    "field '{}' declares unknown SQL type '{}'",
+                    field.name, text
+                ))
+            })?
+        }
+    };
+    Ok(Some(data_type))
+}
+
+/// Whether values of a field mapped to `derived` can be stored in a column of
+/// type `declared`, and read back. Narrower integers and floats, and
+/// blank-padded `char(n)` for strings, are only accepted when `lossy`.
+fn storable_as(derived: &DataType, declared: &DataType, lossy: bool) -> bool {
+    use DataType::*;
+    let width = |ty: &DataType| match ty {
+        SmallInt => 2,
+        Integer | Real => 4,
+        _ => 8,
+    };
+    match (derived, declared) {
+        (SmallInt | Integer | BigInt, SmallInt | Integer | BigInt)
+        | (Real | DoublePrecision, Real | DoublePrecision) => {
+            lossy || width(declared) >= width(derived)
+        }
+        (SmallInt | Integer | BigInt | Real | DoublePrecision, Numeric { .. }) => true,
+        (Text, Varchar(_) | Uuid | Inet | MacAddr | Custom { .. }) => true,
+        (Text, Char(_)) => lossy,
+        (Char(_), Text | Varchar(_)) => true,
+        (Jsonb, Json) => true,
+        (Array(derived), Array(declared)) => storable_as(derived, declared, lossy),
+        _ => crate::diff::same(derived, declared),
+    }
+}
+
+/// The column name for `field`: `psql::column` or facet's `rename` if
+/// given, else the naming strategy's name for the field. Positional fields,
+/// named `0`, `1`, ... by facet, become `value` when alone and `value_<n>`
+/// otherwise.
+fn column_name(
+    field: &facet::Field,
+    siblings: &[facet::Field],
+    naming: &dyn NamingStrategy,
+) -> String {
+    if let Some(name) = field
+        .get_attr(Some("psql"), "column")
+        .and_then(|attr| attr.get_as::<&'static str>())
+    {
+        return name.to_string();
+    }
+    if let Some(name) = field.rename {
+        return name.to_string();
+    }
+    let positional = field.name.bytes().all(|b| b.is_ascii_digit());
+    match (positional, siblings.len()) {
+        (true, 1) => "value".to_string(),
+        (true, _) => format!("value_{}", field.name),
+        (false, _) => naming.column_name(field.name),
+    }
+}
+
+/// Columns and constraints derived from a list of fields.
+struct ProcessedFields {
+    columns: Vec<Column>,
+    primary_key: Option<PrimaryKey>,
+    foreign_keys: Vec<ForeignKey>,
+    checks: Vec<CheckConstraint>,
+    uniques: Vec<UniqueConstraint>,
+    indexes: Vec<Index>,
+    /// The fields each column was read from, in column order.
+    sources: Vec<FieldPath>,
+    /// Shapes named in `psql::references`, which need tables of their own.
+    referenced: Vec<&'static facet::Shape>,
+    types: DerivedTypes,
+}
+
+/// `column_prefix` is prepended to every column name, for fields sharing a
+/// table with others.
+fn process_fields(
+    fields: &'static [facet::Field],
+    table_name: &str,
+    column_prefix: &str,
+    naming: &dyn NamingStrategy,
+    options: &ConversionOptions,
+) -> Result<ProcessedFields, ConversionError> {
+    let mut columns = Vec::new();
+    let mut pk_columns = Vec::new();
+    let mut foreign_keys = Vec::new();
+    let mut referenced = Vec::new();
+    let mut types = DerivedTypes::default();
+    let mut checks = Vec::new();
+    let mut uniques = Vec::new();
+    let mut indexes = Vec::new();
+    let mut sources = Vec::new();
+
+    for field in fields.iter() {
+        if is_skipped(field) {
+            continue;
+        }
+        if field.is_flattened() {
+            let prefix = format!("{}{}_", column_prefix, column_name(field, fields, naming));
+            let (shape, optional) = match field.shape().def {
+                facet::Def::Option(option) => (option.t(), true),
+                _ => (field.shape(), false),
+            };
+            let facet::Type::User(facet::UserType::Struct(inner)) = &shape.ty else {
+                return Err(ConversionError::InvalidAttribute(format!(
+                    "'{}.{}' is flattened but is not a struct",
+                    table_name, field.name
+                )));
+            };
+            let mut inner = process_fields(inner.fields, table_name, &prefix, naming, options)?;
+            // Nothing is stored for a `None`
+            for column in &mut inner.columns {
+                column.nullable |= optional;
+            }
+            columns.append(&mut inner.columns);
+            pk_columns.extend(inner.primary_key.map(|pk| pk.columns).unwrap_or_default());
+            foreign_keys.append(&mut inner.foreign_keys);
+            checks.append(&mut inner.checks);
+            uniques.append(&mut inner.uniques);
+            indexes.append(&mut inner.indexes);
+            sources.extend(inner.sources.into_iter().map(|mut path| {
+                path.insert(0, field);
+                path
+            }));
+            referenced.append(&mut inner.referenced);
+            types.enums.append(&mut inner.types.enums);
+            types.composites.append(&mut inner.types.composites);
+            types.domains.append(&mut inner.types.domains);
+            continue;
+        }
+
+        let mut column = field_to_column(
+            field,
+            fields,
+            naming,
+            TypeHints::for_field(field, options),
+            &mut types,
+        )?;
+        column.name.insert_str(0, column_prefix);
+
+        // Several primary key fields make a composite key, in field order
+        if is_primary_key(field) {
+            pk_columns.push(column.name.clone());
+        }
+        if field.attributes.iter().any(|attr| {
+            attr.ns == Some("psql") && attr.key != "index" && attr_columns(attr).is_some()
+        }) {
+            return Err(ConversionError::InvalidAttribute(format!(
+                "'{}.{}' lists columns, which only struct-level attributes take",
+                table_name, field.name
+            )));
+        }
+
+        if let Some(target) = field_reference(field, table_name, &column, options)? {
+            foreign_keys.push(target.foreign_key);
+            referenced.push(target.shape);
+        }
+
+        // Numbered like PostgreSQL names several checks of one column
+        let mut column_checks = 0;
+        if field.has_attr(Some("psql"), "check_length") {
+            checks.push(length_check(field, table_name, &column)?);
+            column_checks += 1;
+        }
+        for spec in field_attrs(field, "check") {
+            let (name, expression) = check_clause(spec, table_name)?;
+            let name = match name {
+                Some(name) => name.to_string(),
+                None => {
+                    column_checks += 1;
+                    match column_checks - 1 {
+                        0 => format!("{}_{}_check", table_name, column.name),
+                        n => format!("{}_{}_check{}", table_name, column.name, n),
+                    }
+                }
+            };
+            checks.push(CheckConstraint {
+                name: Some(name),
+                expression: expression.to_string(),
+                no_inherit: false,
+            });
+        }
+        if field.has_attr(Some("psql"), "unique") {
+            uniques.push(UniqueConstraint {
+                name: None,
+                columns: vec![column.name.clone()],
+                deferrable: None,
+            });
+        }
+        indexes.extend(declared_indexes(
+            field.attributes,
+            Some(&column.name),
+            table_name,
+            |name| {
+                let sibling = named_fields(name, fields, table_name, naming)?[0];
+                Ok(format!(
+                    "{}{}",
+                    column_prefix,
+                    column_name(sibling, fields, naming)
+                ))
+            },
+        )?);
+        if let Some(default) = field_attrs(field, "default").next() {
+            column.default = Some(default.to_string());
+        }
+        if field.has_attr(Some("psql"), "not_null") {
+            column.nullable = false;
+        }
+
+        columns.push(column);
+        sources.push(vec![field]);
+    }
+
+    let primary_key = if !pk_columns.is_empty() {
+        Some(PrimaryKey {
+            name: None,
+            columns: pk_columns,
+            using: None,
+            deferrable: None,
+        })
+    } else {
+        None
+    };
+
+    name_indexes(table_name, &mut indexes);
+    Ok(ProcessedFields {
+        columns,
+        primary_key,
+        foreign_keys,
+        checks,
+        uniques,
+        indexes,
+        sources,
+        referenced,
+        types,
+    })
+}
+
+/// The values of every `psql::<key> = "..."` on `field`, in order.
+fn field_attrs<'f>(
+    field: &'f facet::Field,
+    key: &'f str,
+) -> impl Iterator<Item = &'static str> + 'f {
+    field
+        .attributes
+        .iter()
+        .filter(move |attr| attr.ns == Some("psql") && attr.key == key)
+        .filter_map(|attr| attr.get_as::<&'static str>().copied())
+}
+
+/// Splits a `psql::check` into its optional name and expression: either a
+/// bare expression, or PostgreSQL's `CONSTRAINT name CHECK (expr)`.
+fn check_clause<'s>(
+    spec: &'s str,
+    table_name: &str,
+) -> Result<(Option<&'s str>, &'s str), ConversionError> {
+    let spec = spec.trim();
+    let Some(rest) = strip_keyword(spec, "CONSTRAINT") else {
+        return Ok((None, spec));
+    };
+    let invalid = || {
+        ConversionError::InvalidAttribute(format!(
+            "psql::check(\"{}\") on '{}' should read \"CONSTRAINT name CHECK (expr)\"",
+            spec, table_name
+        ))
+    };
+    let (name, rest) = rest.split_once(char::is_whitespace).ok_or_else(invalid)?;
+    let expression = strip_keyword(rest.trim_start(), "CHECK")
+        .map(str::trim)
+        .and_then(|rest| rest.strip_prefix('('))
+        .and_then(|rest| rest.strip_suffix(')'))
+        .ok_or_else(invalid)?;
+    Ok((Some(name), expression.trim()))
+}
+
+/// `text` without its leading `keyword` (in any case) and the whitespace after it.
+fn strip_keyword<'s>(text: &'s str, keyword: &str) -> Option<&'s str> {
+    let head = text.get(..keyword.len())?;
+    let rest = &text[keyword.len()..];
+    (head.eq_ignore_ascii_case(keyword) && rest.starts_with(char::is_whitespace))
+        .then(|| rest.trim_start())
+}
+
+/// `CHECK` that a fixed-size array column holds exactly as many elements as
+/// the Rust array.
+fn length_check(
+    field: &facet::Field,
+    table_name: &str,
+    column: &Column,
+) -> Result<CheckConstraint, ConversionError> {
+    let Some(len) = fixed_array_len(field.shape()) else {
+        return Err(ConversionError::InvalidAttribute(format!(
+            "'{}.{}' uses psql::check_length but is not a fixed-size array",
+            table_name, field.name
+        )));
+    };
+    if !matches!(column.data_type, DataType::Array(_)) {
+        return Err(ConversionError::InvalidAttribute(format!(
+            "'{}.{}' uses psql::check_length but is stored as {}",
+            table_name,
+            field.name,
+            render_data_type(&column.data_type)
+        )));
+    }
+
+    Ok(CheckConstraint {
+        name: Some(format!("{}_{}_check", table_name, column.name)),
+        expression: format!("array_length({}, 1) = {}", quote_ident(&column.name), len),
+        no_inherit: false,
+    })
+}
+
+/// The indexes of the fields in `fields` that are stored, as columns or as
+/// composite attributes.
+pub(crate) fn stored_fields(fields: &[facet::Field]) -> Vec<usize> {
+    (0..fields.len())
+        .filter(|&i| !is_skipped(&fields[i]))
+        .collect()
+}
+
+/// Fields facet skips when serializing, or marked `psql::skip`, get no column.
+fn is_skipped(field: &facet::Field) -> bool {
+    !field
+        .flags
+        .intersection(facet::FieldFlags::SKIP.union(facet::FieldFlags::SKIP_SERIALIZING))
+        .is_empty()
+        || field.has_attr(Some("psql"), "skip")
+}
+
+fn is_primary_key(field: &facet::Field) -> bool {
+    field.has_attr(Some("psql"), "primary_key")
+}
+
+/// A foreign key declared with `psql::references`, and the shape it points at.
+struct FieldReference {
+    foreign_key: ForeignKey,
+    shape: &'static facet::Shape,
+}
+
+fn field_reference(
+    field: &'static facet::Field,
+    table_name: &str,
+    column: &Column,
+    options: &ConversionOptions,
+) -> Result<Option<FieldReference>, ConversionError> {
+    let Some(reference) = field
+        .get_attr(Some("psql"), "references")
+        .and_then(|attr| attr.get_as::<facet::Shape>())
+    else {
+        return Ok(None);
+    };
+    // `psql::Ref<Target, A, B>` carries the target and its actions
+    let (target, actions) = match (reference.type_tag, reference.type_params) {
+        (Some("psql::references"), [target, actions @ ..]) => (target.shape, actions),
+        _ => (reference, &[][..]),
+    };
+    let target_table = self::table_name(target, options);
+
+    let target_pk = primary_key_columns(target, options)?;
+    let [(pk_column, pk_type)] = target_pk.as_slice() else {
+        return Err(ConversionError::InvalidReference(format!(
+            "'{}.{}' references '{}', which has {} primary key columns; expected exactly one",
+            table_name,
+            field.name,
+            target_table,
+            target_pk.len()
+        )));
+    };
+
+    if !same_column_type(&column.data_type, pk_type) {
+        return Err(ConversionError::InvalidReference(format!(
+            "'{}.{}' is {} but references '{}.{}' of type {}",
+            table_name,
+            field.name,
+            render_data_type(&column.data_type),
+            target_table,
+            pk_column,
+            render_data_type(pk_type)
+        )));
+    }
+
+    let mut on_delete = None;
+    let mut on_update = None;
+    for action in actions.iter().filter(|a| a.shape != <() as Facet>::SHAPE) {
+        let shape = action.shape;
+        let Some((delete, action)) = referential_action(shape) else {
+            return Err(ConversionError::InvalidAttribute(format!(
+                "'{}.{}' references '{}' with {}, which is not a psql::on_delete or psql::on_update action",
+                table_name, field.name, target_table, shape
+            )));
+        };
+        let (slot, clause) = if delete {
+            (&mut on_delete, "ON DELETE")
+        } else {
+            (&mut on_update, "ON UPDATE")
+        };
+        if slot.replace(action).is_some() {
+            return Err(ConversionError::InvalidAttribute(format!(
+                "'{}.{}' gives several {} actions",
+                table_name, field.name, clause
+            )));
+        }
+    }
+
+    Ok(Some(FieldReference {
+        foreign_key: ForeignKey {
+            name: None,
+            columns: vec![column.name.clone()],
+            referenced_table: QualifiedName {
+                schema: None,
+                name: target_table,
+            },
+            referenced_columns: Some(vec![pk_column.clone()]),
+            on_delete,
+            on_update,
+            match_type: None,
+            deferrable: None,
+            initially: None,
+        },
+        shape: target,
+    }))
+}
+
+/// The action a `psql::on_delete` or `psql::on_update` marker stands for,
+/// and whether it is an `ON DELETE` one.
+fn referential_action(shape: &facet::Shape) -> Option<(bool, ReferentialAction)> {
+    use crate::{on_delete, on_update};
+
+    let actions: [(&facet::Shape, &facet::Shape, ReferentialAction); 5] = [
+        (
+            on_delete::NoAction::SHAPE,
+            on_update::NoAction::SHAPE,
+            ReferentialAction::NoAction,
+        ),
+        (
+            on_delete::Restrict::SHAPE,
+            on_update::Restrict::SHAPE,
+            ReferentialAction::Restrict,
+        ),
+        (
+            on_delete::Cascade::SHAPE,
+            on_update::Cascade::SHAPE,
+            ReferentialAction::Cascade,
+        ),
+        (
+            on_delete::SetNull::SHAPE,
+            on_update::SetNull::SHAPE,
+            ReferentialAction::SetNull,
+        ),
+        (
+            on_delete::SetDefault::SHAPE,
+            on_update::SetDefault::SHAPE,
+            ReferentialAction::SetDefault,
+        ),
+    ];
+    actions.into_iter().find_map(|(delete, update, action)| {
+        if shape == delete {
+            Some((true, action))
+        } else if shape == update {
+            Some((false, action))
+        } else {
+            None
+        }
+    })
+}
+
+/// Primary key columns of the table `shape` maps to, with their types.
+fn primary_key_columns(
+    shape: &facet::Shape,
+    options: &ConversionOptions,
+) -> Result<Vec<(String, DataType)>, ConversionError> {
+    match &shape.ty {
+        facet::Type::User(facet::UserType::Struct(s)) => {
+            let naming = column_naming(shape, options);
+            let keys = match shape_attr(shape, "primary_key").and_then(attr_columns) {
+                Some(list) => named_fields(list, s.fields, &table_name(shape, options), naming)?,
+                None => s.fields.iter().filter(|f| is_primary_key(f)).collect(),
+            };
+            keys.into_iter()
+                .map(|f| {
+                    let (data_type, _) = shape_to_data_type(
+                        f.shape(),
+                        TypeHints::for_field(f, options),
+                        &mut DerivedTypes::default(),
+                    )?;
+                    Ok((column_name(f, s.fields, naming), data_type))
+                })
+                .collect()
+        }
+        // Enum tables are keyed by a generated bigint id, except the table
+        // of variants of a unit-only enum
+        facet::Type::User(facet::UserType::Enum(e)) => {
+            let no_table = || {
+                ConversionError::InvalidReference(format!(
+                    "{} is stored as a PostgreSQL enum and has no table",
+                    shape
+                ))
+            };
+            match enum_storage(shape, e)? {
+                EnumStorage::TablePerVariant if is_unit_only(e) => {
+                    let table = unit_table(shape, e, options)?.ok_or_else(no_table)?;
+                    let key = &table.columns[0];
+                    Ok(vec![(key.name.clone(), key.data_type.clone())])
+                }
+                EnumStorage::Native => Err(no_table()),
+                _ => Ok(vec![("id".into(), DataType::BigInt)]),
+            }
+        }
+        _ => Err(ConversionError::NotAStruct(format!("{:?}", shape.ty))),
+    }
+}
+
+/// Whether a column of type `a` can reference a column of type `b`.
+fn same_column_type(a: &DataType, b: &DataType) -> bool {
+    fn normalize(dt: &DataType) -> &DataType {
+        match dt {
+            DataType::Serial => &DataType::Integer,
+            DataType::BigSerial => &DataType::BigInt,
+            other => other,
+        }
+    }
+    crate::diff::same(normalize(a), normalize(b))
+}
+
+impl Converter {
+    fn add_enum(
+        &mut self,
+        shape: &facet::Shape,
+        enum_type: &facet::EnumType,
+    ) -> Result<(), ConversionError> {
+        match enum_storage(shape, enum_type)? {
+            EnumStorage::TablePerVariant if is_unit_only(enum_type) => {
+                match unit_table(shape, enum_type, &self.options)? {
+                    Some(table) => self.add_table(table, shape, None),
+                    None => self.add_types(DerivedTypes {
+                        enums: vec![Derived::new(
+                            native_enum(shape, enum_type, &*self.options.naming),
+                            shape,
+                        )],
+                        ..Default::default()
+                    }),
+                }
+            }
+            EnumStorage::TablePerVariant => {
+                let discriminant = self.add_discriminant(shape, enum_type)?;
+                let tables = enum_tables(shape, enum_type, &discriminant, &self.options)?;
+                for variant in tables.variants {
+                    self.pending.extend(variant.fields.referenced);
+                    self.add_types(variant.fields.types)?;
+                    self.add_table(variant.table, shape, Some(variant.name))?;
+                }
+                self.add_table(tables.main, shape, None)
+            }
+            EnumStorage::SingleTable => {
+                let discriminant = self.add_discriminant(shape, enum_type)?;
+                let (table, fields) = single_table(shape, enum_type, &discriminant, &self.options)?;
+                self.pending.extend(fields.referenced);
+                self.add_types(fields.types)?;
+                self.add_table(table, shape, None)
+            }
+            EnumStorage::Jsonb => {
+                let table = jsonb_table(shape, enum_type, &self.options);
+                self.add_table(table, shape, None)
+            }
+            EnumStorage::Native => self.add_types(DerivedTypes {
+                enums: vec![Derived::new(
+                    native_enum(shape, enum_type, &*self.options.naming),
+                    shape,
+                )],
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// The discriminant column of an enum's table, declaring the lookup
+    /// table or enum type it uses.
+    fn add_discriminant(
+        &mut self,
+        shape: &facet::Shape,
+        enum_type: &facet::EnumType,
+    ) -> Result<DiscriminantColumn, ConversionError> {
+        let discriminant = DiscriminantColumn::of(shape, enum_type, &self.options)?;
+        if let Some(lookup) = &discriminant.lookup {
+            self.add_table(lookup.clone(), shape, Some("discriminant"))?;
+        }
+        if let Some(ty) = &discriminant.enum_type {
+            self.add_types(DerivedTypes {
+                enums: vec![Derived::new(ty.clone(), shape)],
+                ..Default::default()
+            })?;
+        }
+        Ok(discriminant)
+    }
+}
+
+/// How a top-level enum is stored, chosen with `psql::storage`.
+#[derive(Clone, Copy, PartialEq)]
+enum EnumStorage {
+    /// A main table referencing one table per variant with fields.
+    TablePerVariant,
+    /// One table with every variant's fields as nullable columns.
+    SingleTable,
+    /// One table with the value in a `jsonb` column.
+    Jsonb,
+    /// A PostgreSQL enum type and no table; unit-only enums only.
+    Native,
+}
+
+fn enum_storage(
+    shape: &facet::Shape,
+    enum_type: &facet::EnumType,
+) -> Result<EnumStorage, ConversionError> {
+    let Some(value) = shape_attr(shape, "storage").and_then(|attr| attr.get_as::<&'static str>())
+    else {
+        return Ok(EnumStorage::TablePerVariant);
+    };
+    let storage = match value.to_lowercase().replace('-', "_").as_str() {
+        "table_per_variant" => EnumStorage::TablePerVariant,
+        "single_table" => EnumStorage::SingleTable,
+        "jsonb" => EnumStorage::Jsonb,
+        "enum" => EnumStorage::Native,
+        _ => {
+            return Err(ConversionError::InvalidAttribute(format!(
+                "unknown storage '{}' on {}",
+                value, shape
+            )));
+        }
+    };
+    if storage == EnumStorage::Native && !is_unit_only(enum_type) {
+        return Err(ConversionError::InvalidAttribute(format!(
+            "{} uses storage = \"enum\" but has variants with fields",
+            shape
+        )));
+    }
+    Ok(storage)
+}
+
+pub(crate) fn is_unit_only(enum_type: &facet::EnumType) -> bool {
+    enum_type
+        .variants
+        .iter()
+        .all(|v| matches!(v.data.kind, facet::StructKind::Unit))
+}
+
+/// What a variant stores in the `discriminant` column of its enum's table.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Discriminant {
+    /// The variant's discriminant, declared or implicit.
+    Integer(i64),
+    /// The variant's label, for text and enum discriminators.
+    Label(String),
+}
+
+impl Discriminant {
+    /// The value as an SQL literal, as used in `CHECK`s and lookup rows.
+    fn literal(&self) -> String {
+        match self {
+            Discriminant::Integer(n) => n.to_string(),
+            Discriminant::Label(label) => format!("'{}'", label.replace('\'', "''")),
+        }
+    }
+}
+
+/// The `discriminant` column of an enum's table, chosen with
+/// `psql::discriminator`, and what each variant stores in it.
+pub(crate) struct DiscriminantColumn {
+    pub(crate) column: Column,
+    /// Value of each variant, in declaration order.
+    pub(crate) values: Vec<Discriminant>,
+    /// Table of the labels the column references, for `"text"`.
+    lookup: Option<Table>,
+    /// Type of the column, for `"enum"`.
+    enum_type: Option<EnumType>,
+}
+
+impl DiscriminantColumn {
+    fn of(
+        shape: &facet::Shape,
+        enum_type: &facet::EnumType,
+        options: &ConversionOptions,
+    ) -> Result<Self, ConversionError> {
+        let mut discriminant = Self::of_kind(shape, enum_type, options)?;
+        // Unit variants have no table or column of their own to describe them
+        let units: Vec<_> = enum_type
+            .variants
+            .iter()
+            .zip(&discriminant.values)
+            .filter(|(v, _)| v.data.fields.is_empty())
+            .map(|(v, value)| format!("{} = {}", v.name, value.literal()))
+            .collect();
+        if !units.is_empty() {
+            discriminant.column.comment = Some(format!(
+                "Discriminant for enum variant. Unit variants: {}",
+                units.join(", ")
+            ));
+        }
+        Ok(discriminant)
+    }
+
+    fn of_kind(
+        shape: &facet::Shape,
+        enum_type: &facet::EnumType,
+        options: &ConversionOptions,
+    ) -> Result<Self, ConversionError> {
+        let value = shape_attr(shape, "discriminator")
+            .and_then(|attr| attr.get_as::<&'static str>())
+            .map_or("integer", |v| *v);
+        let labels = || -> Vec<Discriminant> {
+            enum_type
+                .variants
+                .iter()
+                .map(|v| Discriminant::Label(enum_label(shape, v, &*options.naming)))
+                .collect()
+        };
+        let name = format!("{}_variant", table_name(shape, options));
+        let mut column = discriminant_column();
+
+        match value.to_lowercase().as_str() {
+            "integer" => {
+                column.data_type = repr_data_type(enum_type.enum_repr);
+                let values = enum_type
+                    .variants
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| Discriminant::Integer(v.discriminant.unwrap_or(i as i64)))
+                    .collect();
+                Ok(DiscriminantColumn {
+                    column,
+                    values,
+                    lookup: None,
+                    enum_type: None,
+                })
+            }
+            "text" => {
+                let values = labels();
+                column.data_type = DataType::Text;
+                let lookup = variant_table(name, shape, None, &values);
+                Ok(DiscriminantColumn {
+                    column,
+                    values,
+                    lookup: Some(lookup),
+                    enum_type: None,
+                })
+            }
+            "enum" => {
+                column.data_type = DataType::Enum {
+                    schema: None,
+                    name: name.clone(),
+                };
+                Ok(DiscriminantColumn {
+                    column,
+                    values: labels(),
+                    lookup: None,
+                    enum_type: Some(EnumType {
+                        schema: None,
+                        name,
+                        variants: enum_type
+                            .variants
+                            .iter()
+                            .map(|v| enum_label(shape, v, &*options.naming))
+                            .collect(),
+                        comment: None,
+                    }),
+                })
+            }
+            _ => Err(ConversionError::InvalidAttribute(format!(
+                "unknown discriminator '{}' on {}",
+                value, shape
+            ))),
+        }
+    }
+
+    /// The foreign key from the enum's table to the lookup table, if any.
+    fn foreign_key(&self) -> Option<ForeignKey> {
+        let lookup = self.lookup.as_ref()?;
+        Some(ForeignKey {
+            name: None,
+            columns: vec![self.column.name.clone()],
+            referenced_table: QualifiedName {
+                schema: None,
+                name: lookup.name.clone(),
+            },
+            referenced_columns: Some(vec!["name".to_string()]),
+            on_delete: Some(ReferentialAction::Restrict),
+            on_update: Some(ReferentialAction::Cascade),
+            match_type: None,
+            deferrable: None,
+            initially: None,
+        })
+    }
+
+    /// `discriminant IN (...)`, listing the value of every variant.
+    fn is_valid(&self) -> String {
+        let values: Vec<_> = self.values.iter().map(Discriminant::literal).collect();
+        format!("{} IN ({})", self.column.name, values.join(", "))
+    }
+
+    /// `discriminant = <value of the variant at index>`.
+    fn matches(&self, index: usize) -> String {
+        format!("{} = {}", self.column.name, self.values[index].literal())
+    }
+}
+
+/// A table with one row per variant: its label, and its discriminant when
+/// `key` is given. The first column is the primary key.
+fn variant_table(
+    name: String,
+    shape: &facet::Shape,
+    key: Option<&DiscriminantColumn>,
+    labels: &[Discriminant],
+) -> Table {
+    let label = Column {
+        name: "name".to_string(),
+        data_type: DataType::Text,
+        default: None,
+        nullable: false,
         collation: None,
         is_generated: false,
         generation_expression: None,
@@ -104,229 +1836,405 @@ fn field_to_column(field: &facet::Field) -> Result<Column, ConversionError> {
         identity_generation: None,
         comment: None,
         privileges: None,
-    })
+    };
+    let (columns, rows) = match key {
+        Some(key) => (
+            vec![key.column.clone(), label],
+            key.values
+                .iter()
+                .zip(labels)
+                .map(|(k, l)| vec![k.literal(), l.literal()])
+                .collect(),
+        ),
+        None => (
+            vec![label],
+            labels.iter().map(|l| vec![l.literal()]).collect(),
+        ),
+    };
+    let uniques = match key {
+        Some(_) => vec![UniqueConstraint {
+            name: None,
+            columns: vec!["name".to_string()],
+            deferrable: None,
+        }],
+        None => vec![],
+    };
+    Table {
+        name,
+        primary_key: Some(PrimaryKey {
+            name: None,
+            columns: vec![columns[0].name.clone()],
+            using: None,
+            deferrable: None,
+        }),
+        columns,
+        uniques,
+        foreign_keys: vec![],
+        checks: vec![],
+        indexes: vec![],
+        options: empty_table_options(),
+        comment: Some(format!("Variants of {}", shape)),
+        owned_sequences: vec![],
+        rows,
+    }
 }
 
-fn process_fields(
-    fields: &[facet::Field],
-    table_name: &str,
-) -> Result<(Vec<Column>, Option<PrimaryKey>), ConversionError> {
-    let mut columns = Vec::new();
-    let mut pk_columns = Vec::new();
-
-    for field in fields.iter() {
-        let column = field_to_column(field)?;
-        columns.push(column);
+/// What an enum with only unit variants is stored as instead of a table
+/// per variant, which would be an identity and a discriminant: a table of
+/// its variants keyed by the discriminant, or `None` for the enum type of
+/// `psql::discriminator = "enum"`.
+fn unit_table(
+    shape: &facet::Shape,
+    enum_type: &facet::EnumType,
+    options: &ConversionOptions,
+) -> Result<Option<Table>, ConversionError> {
+    let discriminant = DiscriminantColumn::of(shape, enum_type, options)?;
+    if discriminant.enum_type.is_some() {
+        return Ok(None);
+    }
+    let labels: Vec<_> = enum_type
+        .variants
+        .iter()
+        .map(|v| Discriminant::Label(enum_label(shape, v, &*options.naming)))
+        .collect();
+    // Text discriminators are the labels themselves
+    let key = discriminant.lookup.is_none().then_some(&discriminant);
+    Ok(Some(variant_table(
+        table_name(shape, options),
+        shape,
+        key,
+        &labels,
+    )))
+}
 
-        // Check for primary key attribute
-        for attr in field.attributes {
-            if attr.key == "primary_key" && attr.ns == Some("psql") {
-                pk_columns.push(field.name.to_string());
-            }
+/// The narrowest integer type holding every discriminant of `repr`.
+fn repr_data_type(repr: facet::EnumRepr) -> DataType {
+    use facet::EnumRepr;
+    match repr {
+        EnumRepr::U8 | EnumRepr::I8 | EnumRepr::I16 => DataType::SmallInt,
+        EnumRepr::U16 | EnumRepr::I32 | EnumRepr::RustNPO => DataType::Integer,
+        EnumRepr::U32 | EnumRepr::U64 | EnumRepr::USize | EnumRepr::I64 | EnumRepr::ISize => {
+            DataType::BigInt
         }
     }
+}
 
-    if pk_columns.len() > 1 {
-        return Err(ConversionError::MultiplePrimaryKeys(format!(
-            "Table '{}' has {} primary keys: {:?}",
-            table_name,
-            pk_columns.len(),
-            pk_columns
-        )));
+/// The PostgreSQL enum for a unit-only enum, one label per variant.
+fn native_enum(
+    shape: &facet::Shape,
+    enum_type: &facet::EnumType,
+    naming: &dyn NamingStrategy,
+) -> EnumType {
+    EnumType {
+        schema: None,
+        name: type_name(shape, naming),
+        variants: enum_type
+            .variants
+            .iter()
+            .map(|v| enum_label(shape, v, naming))
+            .collect(),
+        comment: None,
     }
+}
 
-    let primary_key = if !pk_columns.is_empty() {
-        Some(PrimaryKey {
+/// Single-table inheritance: the discriminant plus every variant's fields,
+/// prefixed with the variant name. Each variant gets a `CHECK` that its
+/// required columns are set when it is active and all of its columns are
+/// `NULL` otherwise.
+fn single_table(
+    shape: &facet::Shape,
+    enum_type: &facet::EnumType,
+    discriminant: &DiscriminantColumn,
+    options: &ConversionOptions,
+) -> Result<(Table, ProcessedFields), ConversionError> {
+    let name = table_name(shape, options);
+    let mut table = Table {
+        name: name.clone(),
+        columns: vec![identity_column(), discriminant.column.clone()],
+        primary_key: Some(PrimaryKey {
             name: None,
-            columns: pk_columns,
+            columns: vec!["id".to_string()],
             using: None,
             deferrable: None,
-        })
-    } else {
-        None
+        }),
+        uniques: vec![],
+        foreign_keys: discriminant.foreign_key().into_iter().collect(),
+        checks: vec![CheckConstraint {
+            name: Some(format!("{}_discriminant_check", name)),
+            expression: discriminant.is_valid(),
+            no_inherit: false,
+        }],
+        indexes: vec![],
+        options: empty_table_options(),
+        comment: None,
+        owned_sequences: vec![],
+        rows: vec![],
+    };
+    let mut all = ProcessedFields {
+        columns: vec![],
+        primary_key: None,
+        foreign_keys: vec![],
+        checks: vec![],
+        uniques: vec![],
+        indexes: vec![],
+        sources: vec![],
+        referenced: vec![],
+        types: DerivedTypes::default(),
     };
 
-    Ok((columns, primary_key))
+    for (index, variant) in enum_type.variants.iter().enumerate() {
+        if variant.data.fields.is_empty() {
+            continue;
+        }
+        let variant_name = options.naming.variant_name(variant.name);
+        let prefix = format!("{}_", variant_name);
+        let mut fields = process_fields(
+            variant.data.fields,
+            &name,
+            &prefix,
+            &*options.naming,
+            options,
+        )?;
+
+        let required: Vec<_> = fields
+            .columns
+            .iter()
+            .filter(|c| !c.nullable)
+            .map(|c| format!("{} IS NOT NULL", quote_ident(&c.name)))
+            .collect();
+        let unset: Vec<_> = fields
+            .columns
+            .iter()
+            .map(|c| format!("{} IS NULL", quote_ident(&c.name)))
+            .collect();
+        table.checks.push(CheckConstraint {
+            name: Some(format!("{}_{}_check", name, variant_name)),
+            expression: format!(
+                "CASE WHEN {} THEN {} ELSE {} END",
+                discriminant.matches(index),
+                if required.is_empty() {
+                    "true".to_string()
+                } else {
+                    required.join(" AND ")
+                },
+                unset.join(" AND ")
+            ),
+            no_inherit: false,
+        });
+
+        for mut column in fields.columns.drain(..) {
+            column.nullable = true;
+            table.columns.push(column);
+        }
+        table.foreign_keys.append(&mut fields.foreign_keys);
+        table.checks.append(&mut fields.checks);
+        table.uniques.append(&mut fields.uniques);
+        table.indexes.append(&mut fields.indexes);
+        all.sources.append(&mut fields.sources);
+        all.referenced.append(&mut fields.referenced);
+        all.types.enums.append(&mut fields.types.enums);
+        all.types.composites.append(&mut fields.types.composites);
+        all.types.domains.append(&mut fields.types.domains);
+    }
+    Ok((table, all))
 }
 
-fn enum_to_partial_schema(
+/// The value in a single `jsonb` column, as `facet_json` writes it: unit
+/// variants as their name, others as an object keyed by the variant name.
+fn jsonb_table(
     shape: &facet::Shape,
     enum_type: &facet::EnumType,
-) -> Result<PartialSchema, ConversionError> {
-    let base_name = shape.type_identifier.to_lowercase();
-    let mut tables = Vec::new();
-    let mut foreign_keys = Vec::new();
-    let mut main_columns = Vec::new();
+    options: &ConversionOptions,
+) -> Table {
+    let name = table_name(shape, options);
+    let quote = |s: &str| s.replace('\'', "''");
 
-    // 1. Create columns for the main table
-    // Add primary key 'id'
-    main_columns.push(Column {
-        name: "id".to_string(),
-        data_type: DataType::BigSerial, // Or BigInt if managed externally
-        default: None,
-        nullable: false,
-        collation: None,
-        is_generated: true,
-        generation_expression: None,
-        is_identity: true,
-        identity_generation: Some(IdentityGeneration::Always),
-        comment: None,
-        privileges: None,
-    });
+    let units: Vec<_> = enum_type
+        .variants
+        .iter()
+        .filter(|v| v.data.fields.is_empty())
+        .map(|v| format!("'{}'", quote(v.name)))
+        .collect();
+    let tagged: Vec<_> = enum_type
+        .variants
+        .iter()
+        .filter(|v| !v.data.fields.is_empty())
+        .map(|v| format!("value - '{}' = '{{}}'", quote(v.name)))
+        .collect();
+    let mut branches = Vec::new();
+    if !units.is_empty() {
+        branches.push(format!(
+            "WHEN 'string' THEN value #>> '{{}}' IN ({})",
+            units.join(", ")
+        ));
+    }
+    if !tagged.is_empty() {
+        branches.push(format!(
+            "WHEN 'object' THEN value <> '{{}}' AND ({})",
+            tagged.join(" OR ")
+        ));
+    }
 
-    // Add dictionary/discriminant column
-    main_columns.push(Column {
-        name: "discriminant".to_string(),
-        data_type: DataType::Integer,
-        default: None,
-        nullable: false,
-        collation: None,
-        is_generated: false,
-        generation_expression: None,
-        is_identity: false,
-        identity_generation: None,
-        comment: Some("Discriminant for enum variant".to_string()),
-        privileges: None,
-    });
+    Table {
+        name: name.clone(),
+        columns: vec![
+            identity_column(),
+            Column {
+                name: "value".to_string(),
+                data_type: DataType::Jsonb,
+                default: None,
+                nullable: false,
+                collation: None,
+                is_generated: false,
+                generation_expression: None,
+                is_identity: false,
+                identity_generation: None,
+                comment: None,
+                privileges: None,
+            },
+        ],
+        primary_key: Some(PrimaryKey {
+            name: None,
+            columns: vec!["id".to_string()],
+            using: None,
+            deferrable: None,
+        }),
+        uniques: vec![],
+        foreign_keys: vec![],
+        checks: vec![CheckConstraint {
+            name: Some(format!("{}_value_check", name)),
+            expression: format!(
+                "CASE jsonb_typeof(value) {} ELSE false END",
+                branches.join(" ")
+            ),
+            no_inherit: false,
+        }],
+        indexes: vec![],
+        options: empty_table_options(),
+        comment: None,
+        owned_sequences: vec![],
+        rows: vec![],
+    }
+}
 
-    // 2. Process variants
-    for (_, variant) in enum_type.variants.iter().enumerate() {
-        let variant_name = variant.name.to_lowercase();
-        let variant_table_name = format!("{}_{}", base_name, variant_name);
+/// The tables for an enum: one per variant with fields, and a main table
+/// holding the discriminant and a reference to the active variant's row.
+struct EnumTables {
+    main: Table,
+    variants: Vec<VariantTable>,
+}
 
-        // --- Variant Table ---
-        match &variant.data.kind {
-            facet::StructKind::Struct
-            | facet::StructKind::Tuple
-            | facet::StructKind::TupleStruct => {
-                // Create a table for this variant
-                // It needs an ID to be referenced
-                let mut variant_columns = Vec::new();
-                variant_columns.push(Column {
-                    name: "id".to_string(),
-                    data_type: DataType::BigSerial,
-                    default: None,
-                    nullable: false,
-                    collation: None,
-                    is_generated: true,
-                    generation_expression: None,
-                    is_identity: true,
-                    identity_generation: Some(IdentityGeneration::Always),
-                    comment: None,
-                    privileges: None,
-                });
+struct VariantTable {
+    /// Position of the variant among the enum's variants.
+    index: usize,
+    name: &'static str,
+    table: Table,
+    /// Column of the main table referencing this table.
+    column: String,
+    fields: ProcessedFields,
+}
 
-                let (fields_cols, _) = process_fields(&variant.data.fields, &variant_table_name)?;
-                variant_columns.extend(fields_cols);
-
-                let variant_table = Table {
-                    name: variant_table_name.clone(),
-                    columns: variant_columns,
-                    primary_key: Some(PrimaryKey {
-                        name: None, // explicit name?
-                        columns: vec!["id".to_string()],
-                        using: None,
-                        deferrable: None,
-                    }),
-                    uniques: vec![],
-                    foreign_keys: vec![],
-                    checks: vec![],
-                    indexes: vec![],
-                    options: empty_table_options(),
-                    comment: None,
-                    owned_sequences: vec![],
-                };
-                tables.push(variant_table);
-
-                // --- Main Table Reference ---
-                // Add FK column to main table
-                let fk_col_name = format!("{}_id", variant_name);
-                main_columns.push(Column {
-                    name: fk_col_name.clone(),
-                    data_type: DataType::BigInt,
-                    default: None,
-                    nullable: true, // Nullable because only one variant is active
-                    collation: None,
-                    is_generated: false,
-                    generation_expression: None,
-                    is_identity: false,
-                    identity_generation: None,
-                    comment: None,
-                    privileges: None,
-                });
+fn enum_tables(
+    shape: &facet::Shape,
+    enum_type: &facet::EnumType,
+    discriminant: &DiscriminantColumn,
+    options: &ConversionOptions,
+) -> Result<EnumTables, ConversionError> {
+    let base_name = table_name(shape, options);
+    let mut main_columns = vec![identity_column(), discriminant.column.clone()];
+    let mut foreign_keys: Vec<_> = discriminant.foreign_key().into_iter().collect();
+    let mut variants = Vec::new();
 
-                // Add Foreign Key constraint to main table
-                foreign_keys.push(ForeignKey {
-                    name: None,
-                    columns: vec![fk_col_name.clone()],
-                    referenced_table: QualifiedName {
-                        schema: None,
-                        name: variant_table_name,
-                    },
-                    referenced_columns: Some(vec!["id".to_string()]),
-                    on_delete: Some(ReferentialAction::Cascade), // Deleting main row deletes variant row? Or vice versa? Usually cascade delete from parent to child.
-                    on_update: Some(ReferentialAction::NoAction),
-                    match_type: None,
-                    deferrable: None,
-                    initially: None,
-                });
-            }
-            facet::StructKind::Unit => {
-                // Unit variant - no extra data table needed?
-                // Or just a marker?
-                // User said "foreign keys to other new tables which represent either of those variants"
-                // If it's unit, maybe no table needed, but we still need to track it.
-                // For simplified logic matching request: "become 1 table ... with 3 fields (1 desc, 2 FKs)"
-                // But if A is Unit, it has no fields.
-                // Let's assume for now we still make a table for consistency, or strict optimization?
-                // The request example had fields in A and B.
-            }
+    // Unit variants get no table: their discriminant alone identifies them
+    for (index, variant) in enum_type.variants.iter().enumerate() {
+        if let facet::StructKind::Unit = variant.data.kind {
+            continue;
         }
-    }
+        let variant_name = options.naming.variant_name(variant.name);
+        let variant_table_name = format!("{}_{}", base_name, variant_name);
 
-    // Generate CHECK constraint
-    // CHECK (
-    //   (CASE WHEN discriminant = 0 THEN variant_0_id IS NOT NULL ELSE variant_0_id IS NULL END) AND
-    //   (CASE WHEN discriminant = 1 THEN variant_1_id IS NOT NULL ELSE variant_1_id IS NULL END)
-    // )
-    // This ensures that IF discriminant is X, THEN id_X is set, AND (implicitly by logic) others should be null logic?
-    // Actually, "ELSE variant_X_id IS NULL" ensures that if discriminant != X, then id_X MUST be null.
-    // This is exactly what we want: rigid lockstep.
+        let mut fields = process_fields(
+            variant.data.fields,
+            &variant_table_name,
+            "",
+            &*options.naming,
+            options,
+        )?;
+        let mut columns = vec![identity_column()];
+        columns.append(&mut fields.columns);
+        let table = Table {
+            name: variant_table_name.clone(),
+            columns,
+            primary_key: Some(PrimaryKey {
+                name: None,
+                columns: vec!["id".to_string()],
+                using: None,
+                deferrable: None,
+            }),
+            uniques: std::mem::take(&mut fields.uniques),
+            foreign_keys: std::mem::take(&mut fields.foreign_keys),
+            checks: std::mem::take(&mut fields.checks),
+            indexes: std::mem::take(&mut fields.indexes),
+            options: empty_table_options(),
+            comment: None,
+            owned_sequences: vec![],
+            rows: vec![],
+        };
 
-    let mut check_parts: Vec<String> = Vec::new();
-    for (index, variant) in enum_type.variants.iter().enumerate() {
-        let variant_name = variant.name.to_lowercase();
-        match &variant.data.kind {
-            facet::StructKind::Struct
-            | facet::StructKind::Tuple
-            | facet::StructKind::TupleStruct => {
-                let col_name = format!("{}_id", variant_name);
-                check_parts.push(format!(
-                    "(CASE WHEN discriminant = {} THEN {} IS NOT NULL ELSE {} IS NULL END)",
-                    index, col_name, col_name
-                ));
-            }
-            // For Unit variants, we don't have an ID column, so we just ensure no other IDs are set?
-            // But wait, if unit variant is active, then ALL ID columns must be null.
-            // My loop above skips Unit variants for table creation, so there is no `unit_id` column.
-            // But we need to verify that if discriminant points to Unit, then all existing ID columns are NULL.
-            // AND the above loop only generates checks for existing columns.
-            // So if discriminant = unit_index, then the above checks:
-            // "CASE WHEN discriminant = struct_index ... ELSE struct_id IS NULL"
-            // Since discriminant != struct_index, it enforces struct_id IS NULL.
-            // So identifying unit variants implicitly works by enforcing all others to be null!
-            // WE JUST NEED TO ENSURE `CASE WHEN` covers the "ELSE" branch correctly for all columns.
-            _ => {}
-        }
+        // Nullable, since only the active variant's column is set
+        let column = options.naming.column_name(&format!("{}_id", variant_name));
+        main_columns.push(Column {
+            name: column.clone(),
+            data_type: DataType::BigInt,
+            default: None,
+            nullable: true,
+            collation: None,
+            is_generated: false,
+            generation_expression: None,
+            is_identity: false,
+            identity_generation: None,
+            comment: None,
+            privileges: None,
+        });
+        foreign_keys.push(ForeignKey {
+            name: None,
+            columns: vec![column.clone()],
+            referenced_table: QualifiedName {
+                schema: None,
+                name: variant_table_name,
+            },
+            referenced_columns: Some(vec!["id".to_string()]),
+            on_delete: Some(ReferentialAction::Cascade),
+            on_update: Some(ReferentialAction::NoAction),
+            match_type: None,
+            deferrable: None,
+            initially: None,
+        });
+
+        variants.push(VariantTable {
+            index,
+            name: variant.name,
+            table,
+            column,
+            fields,
+        });
     }
 
-    let check_expression = if check_parts.is_empty() {
-        "1=1".to_string()
-    } else {
-        check_parts.join(" AND ")
-    };
+    // The discriminant names a variant, and each variant column is set
+    // exactly when its variant is active. A unit variant's discriminant
+    // matches none of them, so all must be NULL.
+    let check_parts: Vec<String> = std::iter::once(format!("({})", discriminant.is_valid()))
+        .chain(variants.iter().map(|v| {
+            format!(
+                "(CASE WHEN {} THEN {} IS NOT NULL ELSE {} IS NULL END)",
+                discriminant.matches(v.index),
+                v.column,
+                v.column
+            )
+        }))
+        .collect();
 
-    let main_table = Table {
+    let main = Table {
         name: base_name,
         columns: main_columns,
         primary_key: Some(PrimaryKey {
@@ -339,35 +2247,49 @@ fn enum_to_partial_schema(
         foreign_keys,
         checks: vec![CheckConstraint {
             name: Some("variant_integrity".to_string()),
-            expression: check_expression,
+            expression: check_parts.join(" AND "),
             no_inherit: false,
         }],
         indexes: vec![],
-        options: TableOptions {
-            inherits: vec![],
-            temporary: false,
-            unlogged: false,
-            partitioned: None,
-            tablespace: None,
-            with_storage_params: Default::default(),
-        },
+        options: empty_table_options(),
         comment: None,
         owned_sequences: vec![],
+        rows: vec![],
     };
+    Ok(EnumTables { main, variants })
+}
 
-    tables.push(main_table);
-
-    Ok(PartialSchema {
-        tables,
-        views: vec![],
-        materialized_views: vec![],
-        enums: vec![],
-        domains: vec![],
-        composite_types: vec![],
-        sequences: vec![],
-        collations: vec![],
-        functions: vec![],
-    })
+fn discriminant_column() -> Column {
+    Column {
+        name: "discriminant".to_string(),
+        data_type: DataType::Integer,
+        default: None,
+        nullable: false,
+        collation: None,
+        is_generated: false,
+        generation_expression: None,
+        is_identity: false,
+        identity_generation: None,
+        comment: Some("Discriminant for enum variant".to_string()),
+        privileges: None,
+    }
+}
+
+/// A `bigint` primary key generated by the database.
+fn identity_column() -> Column {
+    Column {
+        name: "id".to_string(),
+        data_type: DataType::BigInt,
+        default: None,
+        nullable: false,
+        collation: None,
+        is_generated: false,
+        generation_expression: None,
+        is_identity: true,
+        identity_generation: Some(IdentityGeneration::Always),
+        comment: None,
+        privileges: None,
+    }
 }
 
 fn empty_table_options() -> TableOptions {
@@ -381,50 +2303,151 @@ fn empty_table_options() -> TableOptions {
     }
 }
 
-fn shape_to_data_type(shape: &facet::Shape) -> Result<(DataType, bool), ConversionError> {
-    // Check if this is an Option type (makes it nullable)
-    if is_option_type(shape) {
-        // Extract the inner type from Option
-        if let Some(inner_shape) = get_option_inner_type(shape) {
-            let (inner_type, _) = shape_to_data_type(inner_shape)?;
-            return Ok((inner_type, true));
+fn shape_to_data_type(
+    shape: &facet::Shape,
+    hints: TypeHints,
+    types: &mut DerivedTypes,
+) -> Result<(DataType, bool), ConversionError> {
+    // `Option` makes the column nullable
+    if let facet::Def::Option(option) = shape.def {
+        let (inner_type, _) = shape_to_data_type(option.t(), hints, types)?;
+        return Ok((inner_type, true));
+    }
+
+    if let Some(mapping) = hints.registry.get(shape) {
+        return Ok((mapping.data_type.clone(), false));
+    }
+
+    if let Some((element, len)) = sequence_element(shape) {
+        // Byte blobs are `bytea`, unless stored as JSONB or asked to be arrays
+        if len.is_none()
+            && element.is_type::<u8>()
+            && !hints.sequence_as_jsonb
+            && !hints.bytes_as_array
+        {
+            return Ok((DataType::Bytea, false));
         }
+        return Ok((sequence_to_data_type(element, hints, types)?, false));
     }
 
-    // Map primitive types
-    let data_type = match &shape.ty {
-        facet::Type::Primitive(prim) => primitive_to_data_type(prim, shape)?,
-        facet::Type::User(user_type) => user_type_to_data_type(user_type, shape)?,
-        facet::Type::Pointer(_) => {
-            // References like &str
-            // Check if this is a string reference by looking at inner type if available
-            if let Some(inner) = &shape.inner {
-                // For references, use the inner type's type_identifier
-                if inner.type_identifier.contains("str") {
-                    return Ok((DataType::Text, false));
-                }
+    if let Some((storage, field)) = newtype(shape)? {
+        let (base_type, nullable) = shape_to_data_type(field.shape(), hints, types)?;
+        return Ok(match storage {
+            NewtypeStorage::Inner => (base_type, nullable),
+            NewtypeStorage::Domain => {
+                let name = type_name(shape, hints.naming);
+                let ty = DomainType {
+                    schema: None,
+                    name: name.clone(),
+                    base_type,
+                    default: None,
+                    not_null: false,
+                    constraints: domain_checks(shape),
+                    comment: None,
+                };
+                types.domains.push(Derived::new(ty, shape));
+                (DataType::Domain { schema: None, name }, nullable)
             }
-            // Fallback check on main type_identifier
-            if shape.type_identifier.contains("str") {
-                DataType::Text
-            } else {
+        });
+    }
+
+    let data_type = match shape.def {
+        facet::Def::Scalar => scalar_to_data_type(shape, hints, types)?,
+        facet::Def::Set(set) => sequence_to_data_type(set.t(), hints, types)?,
+        facet::Def::NdArray(array) => sequence_to_data_type(array.t(), hints, types)?,
+        facet::Def::Map(_) | facet::Def::DynamicValue(_) => DataType::Jsonb,
+        // Box, Rc, Arc, references: store what they point at
+        facet::Def::Pointer(pointer) => match pointer.pointee() {
+            Some(pointee) => return shape_to_data_type(pointee, hints, types),
+            None => {
                 return Err(ConversionError::UnsupportedType(format!(
-                    "Pointer/reference type: {}",
-                    shape.type_identifier
+                    "Pointer without a known pointee: {}",
+                    shape
                 )));
             }
+        },
+        facet::Def::Result(_) => {
+            return Err(ConversionError::UnsupportedType(format!("{}", shape)));
         }
-        _ => {
-            return Err(ConversionError::UnsupportedType(format!(
-                "{:?} (type_identifier: {})",
-                shape.ty, shape.type_identifier
-            )));
-        }
+        _ => match &shape.ty {
+            facet::Type::Primitive(prim) => primitive_to_data_type(prim, shape)?,
+            facet::Type::User(user_type) => user_type_to_data_type(user_type, shape, hints, types)?,
+            _ => {
+                return Err(ConversionError::UnsupportedType(format!(
+                    "{:?} (type_identifier: {})",
+                    shape.ty, shape.type_identifier
+                )));
+            }
+        },
     };
 
     Ok((data_type, false))
 }
 
+fn scalar_to_data_type(
+    shape: &facet::Shape,
+    hints: TypeHints,
+    types: &mut DerivedTypes,
+) -> Result<DataType, ConversionError> {
+    match shape.scalar_type() {
+        Some(facet::ScalarType::Str | facet::ScalarType::String | facet::ScalarType::CowStr) => {
+            Ok(DataType::Text)
+        }
+        Some(facet::ScalarType::Unit) => {
+            Err(ConversionError::UnsupportedType("unit type".to_string()))
+        }
+        _ => match &shape.ty {
+            facet::Type::Primitive(prim) => primitive_to_data_type(prim, shape),
+            facet::Type::User(user_type) => user_type_to_data_type(user_type, shape, hints, types),
+            _ => Err(ConversionError::UnsupportedType(format!(
+                "{:?} (type_identifier: {})",
+                shape.ty, shape.type_identifier
+            ))),
+        },
+    }
+}
+
+/// Element shape of a `Vec`, slice, boxed or borrowed slice, or fixed-size
+/// array, along with the length for the latter.
+fn sequence_element(shape: &facet::Shape) -> Option<(&'static facet::Shape, Option<usize>)> {
+    match shape.def {
+        facet::Def::List(list) => Some((list.t(), None)),
+        facet::Def::Array(array) => Some((array.t(), Some(array.n))),
+        facet::Def::Slice(slice) => Some((slice.t(), None)),
+        facet::Def::Pointer(pointer) => match pointer.pointee()?.def {
+            facet::Def::Slice(slice) => Some((slice.t(), None)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn sequence_to_data_type(
+    element: &facet::Shape,
+    hints: TypeHints,
+    types: &mut DerivedTypes,
+) -> Result<DataType, ConversionError> {
+    if hints.sequence_as_jsonb {
+        return Ok(DataType::Jsonb);
+    }
+    // Arrays may hold NULLs, so `Option` elements need no special handling
+    let (element_type, _) = shape_to_data_type(element, hints, types)?;
+    Ok(match element_type {
+        // Elements without a native representation keep the whole value in JSONB
+        DataType::Jsonb => DataType::Jsonb,
+        element_type => DataType::Array(Box::new(element_type)),
+    })
+}
+
+/// Length of a fixed-size array field, looking through `Option`.
+fn fixed_array_len(shape: &facet::Shape) -> Option<usize> {
+    match shape.def {
+        facet::Def::Option(option) => fixed_array_len(option.t()),
+        facet::Def::Array(array) => Some(array.n),
+        _ => None,
+    }
+}
+
 fn primitive_to_data_type(
     prim: &facet::PrimitiveType,
     shape: &facet::Shape,
@@ -491,77 +2514,141 @@ fn primitive_to_data_type(
 fn user_type_to_data_type(
     user_type: &facet::UserType,
     shape: &facet::Shape,
+    hints: TypeHints,
+    types: &mut DerivedTypes,
 ) -> Result<DataType, ConversionError> {
-    // First check: Is this String type based on type_identifier?
-    // String can be represented in different ways (Struct, Opaque, etc)
-    if shape.type_identifier == "String"
-        || shape.type_identifier.ends_with("::String")
-        || shape.type_identifier.contains("alloc::string::String")
-    {
-        return Ok(DataType::Text);
-    }
-
-    // Check for Vec - represented as Opaque
-    if shape.type_identifier.contains("Vec") || shape.type_identifier.contains("::vec::Vec") {
-        // For now, treat Vec as JSONB (could be Array in future)
-        return Ok(DataType::Jsonb);
-    }
-
-    // Check for HashMap - represented as Opaque
-    if shape.type_identifier.contains("HashMap") {
-        return Ok(DataType::Jsonb);
-    }
-
     match user_type {
+        facet::UserType::Struct(struct_type) if hints.struct_as_composite => {
+            // Fields map like columns; anything nested inside is a composite too
+            let mut fields = Vec::new();
+            for &index in &stored_fields(struct_type.fields) {
+                let field = &struct_type.fields[index];
+                let field_hints = TypeHints::for_nested(field, hints);
+                // Composite attributes keep the field names
+                fields.push(field_to_column(
+                    field,
+                    struct_type.fields,
+                    &Lowercase,
+                    field_hints,
+                    types,
+                )?);
+            }
+
+            let name = type_name(shape, hints.naming);
+            let ty = CompositeType {
+                schema: None,
+                name: name.clone(),
+                fields,
+                comment: None,
+            };
+            types.composites.push(Derived::new(ty, shape));
+            Ok(DataType::Composite { schema: None, name })
+        }
         facet::UserType::Struct(_) => {
-            // For now, treat nested structs as JSONB
-            // In the future, we could create composite types
+            // Nested structs are stored as JSONB unless composites are enabled
             Ok(DataType::Jsonb)
         }
-        facet::UserType::Enum(_) => {
-            // For now, treat enums as integers
-            // In the future, we could create PostgreSQL ENUM types
-            Ok(DataType::Integer)
-        }
-        _ => {
-            // Final check: if this is still String-related, return Text
-            Err(ConversionError::UnsupportedType(format!(
-                "{:?} (type_identifier: {})",
-                user_type, shape.type_identifier
-            )))
+        facet::UserType::Enum(enum_type) => {
+            if enum_storage(shape, enum_type)? == EnumStorage::Jsonb {
+                return Ok(DataType::Jsonb);
+            }
+            if !is_unit_only(enum_type)
+                || hints.enum_as_integer
+                || shape_has_attr(shape, "as_integer")
+            {
+                // Enums carrying data (or opted out) are stored as integers
+                return Ok(DataType::Integer);
+            }
+
+            let ty = native_enum(shape, enum_type, hints.naming);
+            let name = ty.name.clone();
+            types.enums.push(Derived::new(ty, shape));
+            Ok(DataType::Enum { schema: None, name })
         }
+        _ => Err(ConversionError::UnsupportedType(format!(
+            "{:?} (type_identifier: {})",
+            user_type, shape.type_identifier
+        ))),
     }
 }
 
-fn is_option_type(shape: &facet::Shape) -> bool {
-    // Check if the type identifier contains "Option"
-    shape.type_identifier.contains("Option")
+/// How a newtype is stored, chosen with `psql::newtype`.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum NewtypeStorage {
+    /// As the type of its field.
+    Inner,
+    /// As a domain over the type of its field.
+    Domain,
 }
 
-fn get_option_inner_type(shape: &facet::Shape) -> Option<&facet::Shape> {
-    // For Option<T>, check if we have type parameters
-    // Option should have 1 type parameter
-    if !shape.type_params.is_empty() {
-        // type_params[0] should be the T in Option<T>
-        if let Some(first_param) = shape.type_params.first() {
-            // The TypeParam contains a shape
-            return Some(first_param.shape);
+/// How `shape` is stored and its only field, if it is a single-field tuple
+/// struct stored as a column type rather than as JSONB.
+pub(crate) fn newtype(
+    shape: &facet::Shape,
+) -> Result<Option<(NewtypeStorage, &'static facet::Field)>, ConversionError> {
+    let attr = shape_attr(shape, "newtype").and_then(|attr| attr.get_as::<&'static str>());
+    let domain = shape_has_attr(shape, "domain");
+    let field = match &shape.ty {
+        facet::Type::User(facet::UserType::Struct(s))
+            if s.kind == facet::StructKind::TupleStruct && s.fields.len() == 1 =>
+        {
+            &s.fields[0]
         }
-    }
-
-    // Fallback: try to extract from enum variant (old approach)
-    if let facet::Type::User(facet::UserType::Enum(enum_type)) = &shape.ty {
-        // Option is an enum with Some(T) and None variants
-        // Get the first variant (Some) and extract its shape
-        if let Some(variant) = enum_type.variants.first() {
-            if let facet::StructKind::Tuple = variant.data.kind {
-                if !variant.data.fields.is_empty() {
-                    let field_shape = variant.data.fields[0].shape();
-                    return Some(field_shape);
-                }
-            }
+        _ if attr.is_some() || domain => {
+            return Err(ConversionError::InvalidAttribute(format!(
+                "{} uses psql::{} but is not a single-field tuple struct",
+                shape,
+                if domain { "domain" } else { "newtype" }
+            )));
         }
+        _ => return Ok(None),
+    };
+    let storage = match attr.map(|value| value.to_lowercase()) {
+        None if domain => NewtypeStorage::Domain,
+        None if shape.is_transparent() => NewtypeStorage::Inner,
+        None => return Ok(None),
+        Some(value) => match value.as_str() {
+            "inner" => NewtypeStorage::Inner,
+            "domain" => NewtypeStorage::Domain,
+            _ => {
+                return Err(ConversionError::InvalidAttribute(format!(
+                    "unknown newtype storage '{}' on {}",
+                    value, shape
+                )));
+            }
+        },
+    };
+    if domain && storage != NewtypeStorage::Domain {
+        return Err(ConversionError::InvalidAttribute(format!(
+            "{} uses psql::domain but is stored as its field's type",
+            shape
+        )));
     }
+    Ok(Some((storage, field)))
+}
 
-    None
+/// The `CHECK`s given with `psql::domain("...")`.
+fn domain_checks(shape: &facet::Shape) -> Vec<CheckConstraint> {
+    shape
+        .attributes
+        .iter()
+        .filter(|attr| attr.ns == Some("psql") && attr.key == "domain")
+        .filter_map(|attr| attr.get_as::<&'static str>())
+        .map(|expression| CheckConstraint {
+            name: None,
+            expression: expression.to_string(),
+            no_inherit: false,
+        })
+        .collect()
+}
+
+fn shape_has_attr(shape: &facet::Shape, key: &str) -> bool {
+    shape_attr(shape, key).is_some()
+}
+
+fn shape_attr(shape: &facet::Shape, key: &str) -> Option<&'static facet::Attr> {
+    shape
+        .attributes
+        .iter()
+        .find(|attr| attr.ns == Some("psql") && attr.key == key)
 }
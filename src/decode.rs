@@ -0,0 +1,933 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::mem;
+
+use facet::{Def, Facet, ScalarType, Shape, StructKind, StructType, Type, UserType};
+use facet_reflect::{Partial, ReflectError};
+use sea_query::{Alias, Expr, Query, SelectStatement, Value};
+use serde_json::Value as Json;
+
+use crate::conversion::{
+    Discriminant, EnumLayout, RowLayout, VariantLayout, newtype, stored_fields,
+};
+use crate::row::{array_type, null_of, path_name, unsupported};
+use crate::*;
+
+/// A result row, as returned by a database driver.
+///
+/// Implemented for column maps and for the pairs [`RowEncoder::values`]
+/// produces; a driver adapter implements it to decode its own rows.
+pub trait Row {
+    /// The value of `column`, or `None` if the row has no such column.
+    fn get(&self, column: &str) -> Option<Value>;
+}
+
+impl Row for HashMap<String, Value> {
+    fn get(&self, column: &str) -> Option<Value> {
+        HashMap::get(self, column).cloned()
+    }
+}
+
+impl Row for BTreeMap<String, Value> {
+    fn get(&self, column: &str) -> Option<Value> {
+        BTreeMap::get(self, column).cloned()
+    }
+}
+
+impl Row for [(String, Value)] {
+    fn get(&self, column: &str) -> Option<Value> {
+        self.iter()
+            .find(|(name, _)| name == column)
+            .map(|(_, value)| value.clone())
+    }
+}
+
+impl Row for Vec<(String, Value)> {
+    fn get(&self, column: &str) -> Option<Value> {
+        Row::get(self.as_slice(), column)
+    }
+}
+
+type Wip = Partial<'static, false>;
+
+/// Reads values back from rows of the table derived from their type.
+///
+/// The reverse of [`RowEncoder`]: `NULL` becomes `None`, JSONB is read into
+/// nested structs, sequences and maps, and enums are found by label or
+/// discriminant. Use the [`ConversionOptions`] the schema was generated with.
+///
+/// ```ignore
+/// let user: User = RowDecoder::new().decode(&row)?;
+/// ```
+#[derive(Default)]
+pub struct RowDecoder {
+    options: ConversionOptions,
+}
+
+impl RowDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use the options the schema was generated with.
+    pub fn options(mut self, options: ConversionOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Builds a `T` from the columns of `row`.
+    ///
+    /// Enums stored as a main table and variant tables are read from the
+    /// columns [`RowDecoder::enum_select`] returns.
+    pub fn decode<T, R>(&self, row: &R) -> Result<T, RowError>
+    where
+        T: Facet<'static>,
+        R: Row + ?Sized,
+    {
+        let type_name = T::SHAPE.type_identifier;
+        let wip = Partial::alloc_owned::<T>().map_err(|e| unsupported(type_name, e))?;
+        let wip = match T::SHAPE.ty {
+            Type::User(UserType::Enum(_)) => {
+                let layout = EnumLayout::of(T::SHAPE, &self.options)?;
+                decode_variant(wip, &layout, row)?
+            }
+            _ => {
+                let layout = RowLayout::of(T::SHAPE, &self.options)?;
+                decode_fields(wip, &layout, row, str::to_string)?
+            }
+        };
+
+        wip.build()
+            .and_then(|value| value.materialize::<T>())
+            .map_err(|e| unsupported(type_name, e))
+    }
+
+    /// A `SELECT` from the main table of the enum `T` in `schema_name`, with
+    /// a `LEFT JOIN` to each variant table, returning the columns
+    /// [`RowDecoder::decode`] reads. Add a `WHERE` to pick rows.
+    pub fn enum_select<'f, T: Facet<'f>>(
+        &self,
+        schema_name: &str,
+    ) -> Result<SelectStatement, RowError> {
+        let layout = EnumLayout::of(T::SHAPE, &self.options)?;
+        let main = Alias::new(&layout.table.name);
+
+        let mut select = Query::select();
+        select
+            .column((main.clone(), Alias::new("id")))
+            .column((main.clone(), Alias::new("discriminant")))
+            .from((Alias::new(schema_name), main.clone()));
+        for variant in &layout.variants {
+            let table = Alias::new(&variant.row.table.name);
+            select.left_join(
+                (Alias::new(schema_name), table.clone()),
+                Expr::col((table.clone(), Alias::new("id")))
+                    .equals((main.clone(), Alias::new(&variant.column))),
+            );
+            for (column, _) in variant.row.columns() {
+                select.expr_as(
+                    Expr::col((table.clone(), Alias::new(&column.name))),
+                    Alias::new(variant_column(variant, &column.name)),
+                );
+            }
+        }
+        Ok(select)
+    }
+}
+
+/// Name under which [`RowDecoder::enum_select`] returns a variant column.
+fn variant_column(variant: &VariantLayout, column: &str) -> String {
+    format!("{}__{}", variant.row.table.name, column)
+}
+
+/// Fills the fields of a struct, or of the selected variant, from the
+/// columns of `layout`, looked up in `row` under `column_name`.
+fn decode_fields<R: Row + ?Sized>(
+    wip: Wip,
+    layout: &RowLayout,
+    row: &R,
+    column_name: impl Fn(&str) -> String,
+) -> Result<Wip, RowError> {
+    let columns: Vec<_> = layout.columns().collect();
+    decode_columns(wip, layout, row, &column_name, &columns, 0)
+}
+
+/// Fills the fields at `depth` in the paths of `columns`. The columns of a
+/// flattened struct are adjacent and filled within it, one level deeper.
+fn decode_columns<R: Row + ?Sized>(
+    mut wip: Wip,
+    layout: &RowLayout,
+    row: &R,
+    column_name: &impl Fn(&str) -> String,
+    columns: &[(&Column, &[&'static facet::Field])],
+    depth: usize,
+) -> Result<Wip, RowError> {
+    let mut rest = columns;
+    while let Some(&(column, path)) = rest.first() {
+        let field = path[depth];
+        let len = rest
+            .iter()
+            .take_while(|(_, other)| std::ptr::eq(other[depth], field))
+            .count();
+        let (group, tail) = rest.split_at(len);
+        rest = tail;
+
+        let decoder = ValueDecoder {
+            field: path_name(&path[..=depth]),
+            layout,
+        };
+        wip = wip.begin_field(field.name).map_err(|e| decoder.error(e))?;
+        if path.len() == depth + 1 {
+            let value = row
+                .get(&column_name(&column.name))
+                .ok_or_else(|| RowError::Missing {
+                    field: decoder.field.clone(),
+                })?;
+            wip = decoder.decode(wip, value, &column.data_type)?;
+        } else if let Def::Option(_) = wip.shape().def {
+            // A flattened `Option` is `None` when all of its columns are NULL
+            let all_null = group.iter().all(|(column, _)| {
+                matches!(row.get(&column_name(&column.name)), Some(value) if is_null(&value))
+            });
+            if all_null {
+                wip = wip.set_default().map_err(|e| decoder.error(e))?;
+            } else {
+                wip = wip.begin_some().map_err(|e| decoder.error(e))?;
+                wip = decode_columns(wip, layout, row, column_name, group, depth + 1)?;
+                wip = wip.end().map_err(|e| decoder.error(e))?;
+            }
+        } else {
+            wip = decode_columns(wip, layout, row, column_name, group, depth + 1)?;
+        }
+        wip = wip.end().map_err(|e| decoder.error(e))?;
+    }
+    default_skipped(wip)
+}
+
+/// Sets the fields that have no column, being skipped, to their default.
+fn default_skipped(mut wip: Wip) -> Result<Wip, RowError> {
+    let fields = match wip.shape().ty {
+        Type::User(UserType::Struct(struct_type)) => struct_type.fields,
+        Type::User(UserType::Enum(_)) => match wip.selected_variant() {
+            Some(variant) => variant.data.fields,
+            None => return Ok(wip),
+        },
+        _ => return Ok(wip),
+    };
+    for (index, field) in fields.iter().enumerate() {
+        let set = wip
+            .is_field_set(index)
+            .map_err(|e| unsupported(field.name, e))?;
+        if !set {
+            wip = wip
+                .set_nth_field_to_default(index)
+                .map_err(|e| unsupported(field.name, e))?;
+        }
+    }
+    Ok(wip)
+}
+
+/// Selects the variant named by the `discriminant` column and fills its
+/// fields from the joined variant table.
+fn decode_variant<R: Row + ?Sized>(
+    wip: Wip,
+    layout: &EnumLayout,
+    row: &R,
+) -> Result<Wip, RowError> {
+    let Type::User(UserType::Enum(enum_type)) = wip.shape().ty else {
+        return Err(unsupported(&layout.table.name, "not an enum"));
+    };
+    let value = row.get("discriminant").ok_or_else(|| RowError::Missing {
+        field: "discriminant".to_string(),
+    })?;
+    let index = (0..enum_type.variants.len())
+        .find(|&i| match &layout.discriminant.values[i] {
+            Discriminant::Integer(n) => integer_of(&value) == Some(*n as i128),
+            Discriminant::Label(label) => {
+                matches!(&value, Value::String(Some(text)) if **text == *label)
+            }
+        })
+        .ok_or_else(|| RowError::Mismatch {
+            field: "discriminant".to_string(),
+            expected: format!("a variant of {}", wip.shape()),
+            found: format!("{:?}", value),
+        })?;
+
+    let wip = wip
+        .select_nth_variant(index)
+        .map_err(|e| unsupported("discriminant", e))?;
+    match layout.variant(index) {
+        Some(variant) => decode_fields(wip, &variant.row, row, |column| {
+            variant_column(variant, column)
+        }),
+        None => Ok(wip),
+    }
+}
+
+/// Fills the value of one field from its column; `field` is the path used in
+/// errors, e.g. `address.zip`.
+struct ValueDecoder<'l> {
+    field: String,
+    layout: &'l RowLayout,
+}
+
+impl ValueDecoder<'_> {
+    fn decode(&self, wip: Wip, value: Value, data_type: &DataType) -> Result<Wip, RowError> {
+        let shape = wip.shape();
+        match shape.def {
+            Def::Option(_) if is_null(&value) => {
+                return wip.set_default().map_err(|e| self.error(e));
+            }
+            Def::Option(_) => {
+                let wip = wip.begin_some().map_err(|e| self.error(e))?;
+                let wip = self.decode(wip, value, data_type)?;
+                return wip.end().map_err(|e| self.error(e));
+            }
+            // Slices cannot be built in place, so `bytes` sets `Box<[u8]>` whole
+            Def::Pointer(_) if !shape.is_type::<Box<[u8]>>() => {
+                let wip = wip.begin_smart_ptr().map_err(|e| self.error(e))?;
+                let wip = self.decode(wip, value, data_type)?;
+                return wip.end().map_err(|e| self.error(e));
+            }
+            _ => {}
+        }
+        if let Some(mapping) = self.layout.registry.get(shape) {
+            if is_null(&value) {
+                return Err(self.mismatch(shape, "NULL"));
+            }
+            return (mapping.decode)(wip, value).map_err(|e| unsupported(&self.field, e));
+        }
+        if let DataType::Domain { name, .. } = data_type {
+            let Some(domain) = self.layout.domains.get(name) else {
+                return Err(unsupported(
+                    &self.field,
+                    format!("unknown domain '{}'", name),
+                ));
+            };
+            return self.decode(wip, value, &domain.base_type);
+        }
+        // Newtypes hold the value of their field
+        if newtype(shape)?.is_some() {
+            let wip = wip.begin_nth_field(0).map_err(|e| self.error(e))?;
+            let wip = self.decode(wip, value, data_type)?;
+            return wip.end().map_err(|e| self.error(e));
+        }
+        if is_null(&value) {
+            return Err(self.mismatch(shape, "NULL"));
+        }
+
+        match data_type {
+            DataType::Boolean => match value {
+                Value::Bool(Some(b)) => self.set(wip, b),
+                other => Err(self.mismatch(shape, describe(&other))),
+            },
+            DataType::SmallInt | DataType::Integer | DataType::BigInt => match integer_of(&value) {
+                Some(n) => self.set_integer(wip, n),
+                None => Err(self.mismatch(shape, describe(&value))),
+            },
+            DataType::Real | DataType::DoublePrecision => match value {
+                Value::Float(Some(f)) => self.set_float(wip, f as f64),
+                Value::Double(Some(f)) => self.set_float(wip, f),
+                other => Err(self.mismatch(shape, describe(&other))),
+            },
+            DataType::Numeric { .. } => match value {
+                Value::Float(Some(f)) => self.set_float(wip, f as f64),
+                Value::Double(Some(f)) => self.set_float(wip, f),
+                Value::String(Some(text)) => self.set_text(wip, *text),
+                other => match integer_of(&other) {
+                    Some(n) => self.set_integer(wip, n),
+                    None => Err(self.mismatch(shape, describe(&other))),
+                },
+            },
+            DataType::Char(_)
+            | DataType::Text
+            | DataType::Varchar(_)
+            | DataType::Uuid
+            | DataType::Inet
+            | DataType::MacAddr
+            | DataType::Custom { .. } => match value {
+                Value::String(Some(s)) => self.set_text(wip, *s),
+                Value::Char(Some(c)) => self.set_text(wip, c.to_string()),
+                other => Err(self.mismatch(shape, describe(&other))),
+            },
+            DataType::Bytea => match value {
+                Value::Bytes(Some(bytes)) => self.bytes(wip, *bytes),
+                // Drivers without a binary type hand the value over in hex
+                Value::String(Some(text)) => match bytea_from_text(&text) {
+                    Some(bytes) => self.bytes(wip, bytes),
+                    None => Err(self.mismatch(shape, format!("'{}'", text))),
+                },
+                other => Err(self.mismatch(shape, describe(&other))),
+            },
+            DataType::Json | DataType::Jsonb => match value {
+                Value::Json(Some(json)) => self.json(wip, &json),
+                // Drivers without a JSON type hand the document over as text
+                Value::String(Some(text)) => match serde_json::from_str(&text) {
+                    Ok(json) => self.json(wip, &json),
+                    Err(e) => Err(unsupported(&self.field, e)),
+                },
+                other => Err(self.mismatch(shape, describe(&other))),
+            },
+            DataType::Array(element) => match value {
+                Value::Array(_, Some(items)) => self.sequence(wip, *items, |decoder, wip, item| {
+                    decoder.decode(wip, item, element)
+                }),
+                other => Err(self.mismatch(shape, describe(&other))),
+            },
+            DataType::Enum { name, .. } => match value {
+                Value::String(Some(label)) => self.label(wip, &label, name),
+                other => Err(self.mismatch(shape, describe(&other))),
+            },
+            DataType::Composite { name, .. } => match value {
+                Value::String(Some(text)) => self.record(wip, &text, name),
+                other => Err(self.mismatch(shape, describe(&other))),
+            },
+            other => Err(unsupported(
+                &self.field,
+                format!("no decoding for {}", render_data_type(other)),
+            )),
+        }
+    }
+
+    /// A decoder for a value nested under this one.
+    fn at(&self, name: impl fmt::Display) -> ValueDecoder<'_> {
+        ValueDecoder {
+            field: format!("{}.{}", self.field, name),
+            layout: self.layout,
+        }
+    }
+
+    fn error(&self, e: ReflectError) -> RowError {
+        unsupported(&self.field, e)
+    }
+
+    fn mismatch(&self, expected: &Shape, found: impl fmt::Display) -> RowError {
+        RowError::Mismatch {
+            field: self.field.clone(),
+            expected: expected.to_string(),
+            found: found.to_string(),
+        }
+    }
+
+    fn set<U: Facet<'static>>(&self, wip: Wip, value: U) -> Result<Wip, RowError> {
+        if wip.shape() != U::SHAPE {
+            return Err(self.mismatch(wip.shape(), U::SHAPE));
+        }
+        wip.set(value).map_err(|e| self.error(e))
+    }
+
+    /// Sets an integer primitive, or selects the enum variant with that
+    /// discriminant.
+    fn set_integer(&self, wip: Wip, n: i128) -> Result<Wip, RowError> {
+        let shape = wip.shape();
+        if let Type::User(UserType::Enum(enum_type)) = shape.ty {
+            let Some(variant) = enum_type
+                .variants
+                .iter()
+                .enumerate()
+                .find(|(i, v)| v.discriminant.unwrap_or(*i as i64) as i128 == n)
+                .map(|(_, v)| v)
+            else {
+                return Err(self.mismatch(shape, format!("discriminant {}", n)));
+            };
+            if !variant.data.fields.is_empty() {
+                return Err(unsupported(
+                    &self.field,
+                    format!(
+                        "variant {} has fields an integer column does not store",
+                        variant.name
+                    ),
+                ));
+            }
+            return wip
+                .select_variant_named(variant.name)
+                .map_err(|e| self.error(e));
+        }
+
+        match shape.scalar_type() {
+            Some(ScalarType::I8) => self.set(wip, self.fit::<i8>(n, shape)?),
+            Some(ScalarType::I16) => self.set(wip, self.fit::<i16>(n, shape)?),
+            Some(ScalarType::I32) => self.set(wip, self.fit::<i32>(n, shape)?),
+            Some(ScalarType::I64) => self.set(wip, self.fit::<i64>(n, shape)?),
+            Some(ScalarType::I128) => self.set(wip, n),
+            Some(ScalarType::ISize) => self.set(wip, self.fit::<isize>(n, shape)?),
+            Some(ScalarType::U8) => self.set(wip, self.fit::<u8>(n, shape)?),
+            Some(ScalarType::U16) => self.set(wip, self.fit::<u16>(n, shape)?),
+            Some(ScalarType::U32) => self.set(wip, self.fit::<u32>(n, shape)?),
+            Some(ScalarType::U64) => self.set(wip, self.fit::<u64>(n, shape)?),
+            Some(ScalarType::U128) => self.set(wip, self.fit::<u128>(n, shape)?),
+            Some(ScalarType::USize) => self.set(wip, self.fit::<usize>(n, shape)?),
+            _ => Err(self.mismatch(shape, "integer")),
+        }
+    }
+
+    fn fit<T: TryFrom<i128>>(&self, n: i128, shape: &Shape) -> Result<T, RowError> {
+        T::try_from(n).map_err(|_| RowError::OutOfRange {
+            field: self.field.clone(),
+            value: n.to_string(),
+            data_type: shape.to_string(),
+        })
+    }
+
+    fn set_float(&self, wip: Wip, f: f64) -> Result<Wip, RowError> {
+        match wip.shape().scalar_type() {
+            Some(ScalarType::F32) => self.set(wip, f as f32),
+            Some(ScalarType::F64) => self.set(wip, f),
+            _ => Err(self.mismatch(wip.shape(), "float")),
+        }
+    }
+
+    /// Fills a `Vec<u8>`, `Box<[u8]>` or other sequence of bytes.
+    fn bytes(&self, wip: Wip, bytes: Vec<u8>) -> Result<Wip, RowError> {
+        if wip.shape().is_type::<Vec<u8>>() {
+            return self.set(wip, bytes);
+        }
+        if wip.shape().is_type::<Box<[u8]>>() {
+            return self.set(wip, bytes.into_boxed_slice());
+        }
+        self.sequence(wip, bytes, |decoder, wip, byte| decoder.set(wip, byte))
+    }
+
+    /// Sets a string, a `char`, or any other type that parses from text.
+    fn set_text(&self, wip: Wip, text: String) -> Result<Wip, RowError> {
+        let shape = wip.shape();
+        match shape.scalar_type() {
+            Some(ScalarType::String) => self.set(wip, text),
+            Some(ScalarType::CowStr) => self.set(wip, Cow::<'static, str>::Owned(text)),
+            Some(ScalarType::Char) => {
+                let mut chars = text.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => self.set(wip, c),
+                    _ => Err(self.mismatch(shape, format!("'{}'", text))),
+                }
+            }
+            Some(ScalarType::Str) => Err(unsupported(
+                &self.field,
+                "borrowed strings cannot be decoded, use String or Cow<str>",
+            )),
+            _ => wip
+                .parse_from_str(&text)
+                .map_err(|_| self.mismatch(shape, format!("'{}'", text))),
+        }
+    }
+
+    /// Selects the variant of a unit-only enum stored as a PostgreSQL enum.
+    fn label(&self, wip: Wip, label: &str, name: &str) -> Result<Wip, RowError> {
+        let shape = wip.shape();
+        let Some(enum_type) = self.layout.enums.get(name) else {
+            return Err(unsupported(
+                &self.field,
+                format!("unknown enum type '{}'", name),
+            ));
+        };
+        match enum_type.variants.iter().position(|v| v == label) {
+            Some(index) => wip.select_nth_variant(index).map_err(|e| self.error(e)),
+            None => Err(self.mismatch(shape, format!("label '{}'", label))),
+        }
+    }
+
+    /// Fills a struct from the text form of a composite value.
+    fn record(&self, mut wip: Wip, text: &str, name: &str) -> Result<Wip, RowError> {
+        let Some(composite) = self.layout.composites.get(name) else {
+            return Err(unsupported(
+                &self.field,
+                format!("unknown composite '{}'", name),
+            ));
+        };
+        let fields = match parse_record(text) {
+            Some(fields) if fields.len() == composite.fields.len() => fields,
+            _ => return Err(self.mismatch(wip.shape(), format!("record '{}'", text))),
+        };
+
+        let stored = match wip.shape().ty {
+            Type::User(UserType::Struct(struct_type)) => stored_fields(struct_type.fields),
+            _ => return Err(self.mismatch(wip.shape(), format!("record '{}'", text))),
+        };
+        for ((&i, column), text) in stored.iter().zip(&composite.fields).zip(fields) {
+            let decoder = self.at(&column.name);
+            let value = decoder.text_value(text, &column.data_type)?;
+            wip = wip.begin_nth_field(i).map_err(|e| decoder.error(e))?;
+            wip = decoder.decode(wip, value, &column.data_type)?;
+            wip = wip.end().map_err(|e| decoder.error(e))?;
+        }
+        default_skipped(wip)
+    }
+
+    /// The value of a composite field from its text form.
+    fn text_value(&self, text: Option<String>, data_type: &DataType) -> Result<Value, RowError> {
+        let Some(text) = text else {
+            return Ok(null_of(data_type));
+        };
+        let invalid = || RowError::Mismatch {
+            field: self.field.clone(),
+            expected: render_data_type(data_type),
+            found: format!("'{}'", text),
+        };
+        Ok(match data_type {
+            DataType::Boolean => match text.as_str() {
+                "t" | "true" => Value::Bool(Some(true)),
+                "f" | "false" => Value::Bool(Some(false)),
+                _ => return Err(invalid()),
+            },
+            DataType::SmallInt | DataType::Integer | DataType::BigInt => {
+                Value::BigInt(Some(text.parse().map_err(|_| invalid())?))
+            }
+            DataType::Real | DataType::DoublePrecision => {
+                Value::Double(Some(text.parse().map_err(|_| invalid())?))
+            }
+            DataType::Jsonb => Value::Json(Some(Box::new(
+                serde_json::from_str(&text).map_err(|_| invalid())?,
+            ))),
+            DataType::Bytea => {
+                Value::Bytes(Some(Box::new(bytea_from_text(&text).ok_or_else(invalid)?)))
+            }
+            DataType::Array(element) => {
+                let items = parse_array(&text).ok_or_else(invalid)?;
+                let values = items
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, item)| self.at(i).text_value(item, element))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Value::Array(array_type(element), Some(Box::new(values)))
+            }
+            _ => Value::String(Some(Box::new(text))),
+        })
+    }
+
+    /// Fills a list, set or fixed-size array from `items`.
+    fn sequence<I>(
+        &self,
+        mut wip: Wip,
+        items: impl IntoIterator<Item = I, IntoIter: ExactSizeIterator>,
+        fill: impl Fn(&ValueDecoder<'_>, Wip, I) -> Result<Wip, RowError>,
+    ) -> Result<Wip, RowError> {
+        let shape = wip.shape();
+        let items = items.into_iter();
+        match shape.def {
+            Def::Set(_) => {
+                wip = wip.begin_set().map_err(|e| self.error(e))?;
+                for (i, item) in items.enumerate() {
+                    let decoder = self.at(i);
+                    wip = wip.begin_set_item().map_err(|e| decoder.error(e))?;
+                    wip = fill(&decoder, wip, item)?;
+                    wip = wip.end().map_err(|e| decoder.error(e))?;
+                }
+            }
+            Def::Array(array) => {
+                if items.len() != array.n {
+                    return Err(self.mismatch(shape, format!("{} elements", items.len())));
+                }
+                for (i, item) in items.enumerate() {
+                    let decoder = self.at(i);
+                    wip = wip.begin_nth_field(i).map_err(|e| decoder.error(e))?;
+                    wip = fill(&decoder, wip, item)?;
+                    wip = wip.end().map_err(|e| decoder.error(e))?;
+                }
+            }
+            Def::List(_) | Def::Slice(_) => {
+                wip = wip.begin_list().map_err(|e| self.error(e))?;
+                for (i, item) in items.enumerate() {
+                    let decoder = self.at(i);
+                    wip = wip.begin_list_item().map_err(|e| decoder.error(e))?;
+                    wip = fill(&decoder, wip, item)?;
+                    wip = wip.end().map_err(|e| decoder.error(e))?;
+                }
+            }
+            _ => return Err(self.mismatch(shape, "array")),
+        }
+        Ok(wip)
+    }
+
+    /// Fills the current value from a JSON document, in the layout
+    /// `facet_json` writes.
+    fn json(&self, wip: Wip, json: &Json) -> Result<Wip, RowError> {
+        let shape = wip.shape();
+        match (shape.def, json) {
+            (Def::Option(_), Json::Null) => return wip.set_default().map_err(|e| self.error(e)),
+            (Def::Option(_), _) => {
+                let wip = wip.begin_some().map_err(|e| self.error(e))?;
+                let wip = self.json(wip, json)?;
+                return wip.end().map_err(|e| self.error(e));
+            }
+            (Def::Pointer(_), _) => {
+                let wip = wip.begin_smart_ptr().map_err(|e| self.error(e))?;
+                let wip = self.json(wip, json)?;
+                return wip.end().map_err(|e| self.error(e));
+            }
+            (Def::List(_) | Def::Array(_) | Def::Slice(_) | Def::Set(_), Json::Array(items)) => {
+                return self.sequence(wip, items, |decoder, wip, item| decoder.json(wip, item));
+            }
+            (Def::Map(_), Json::Object(entries)) => {
+                let mut wip = wip.begin_map().map_err(|e| self.error(e))?;
+                for (key, value) in entries {
+                    let decoder = self.at(key);
+                    wip = wip.begin_key().map_err(|e| decoder.error(e))?;
+                    wip = decoder.set_text(wip, key.clone())?;
+                    wip = wip.end().map_err(|e| decoder.error(e))?;
+                    wip = wip.begin_value().map_err(|e| decoder.error(e))?;
+                    wip = decoder.json(wip, value)?;
+                    wip = wip.end().map_err(|e| decoder.error(e))?;
+                }
+                return Ok(wip);
+            }
+            _ => {}
+        }
+
+        if shape.scalar_type().is_some() {
+            return match json {
+                Json::Bool(b) => self.set(wip, *b),
+                Json::Number(n) => match n.as_i64().map(i128::from) {
+                    Some(n) => self.set_integer(wip, n),
+                    None => match n.as_u64() {
+                        Some(n) => self.set_integer(wip, n as i128),
+                        None => self.set_float(wip, n.as_f64().unwrap_or(f64::NAN)),
+                    },
+                },
+                Json::String(s) => self.set_text(wip, s.clone()),
+                other => Err(self.mismatch(shape, json_kind(other))),
+            };
+        }
+
+        match shape.ty {
+            Type::User(UserType::Struct(struct_type)) => self.json_fields(wip, &struct_type, json),
+            Type::User(UserType::Enum(enum_type)) => {
+                let (name, payload) = match json {
+                    Json::String(name) => (name, None),
+                    Json::Object(entries) if entries.len() == 1 => {
+                        let (name, payload) = entries.iter().next().unwrap();
+                        (name, Some(payload))
+                    }
+                    other => return Err(self.mismatch(shape, json_kind(other))),
+                };
+                let Some(variant) = enum_type.variants.iter().find(|v| v.name == name) else {
+                    return Err(self.mismatch(shape, format!("variant '{}'", name)));
+                };
+                let wip = wip
+                    .select_variant_named(variant.name)
+                    .map_err(|e| self.error(e))?;
+                match payload {
+                    Some(payload) => self.json_fields(wip, &variant.data, payload),
+                    None if variant.data.fields.is_empty() => Ok(wip),
+                    None => Err(self.mismatch(shape, format!("unit variant '{}'", name))),
+                }
+            }
+            _ => Err(unsupported(
+                &self.field,
+                format!("{} cannot be read from JSON", shape),
+            )),
+        }
+    }
+
+    /// Fills the fields of a struct or enum variant from a JSON object, or
+    /// from an array for tuple structs.
+    fn json_fields(&self, mut wip: Wip, data: &StructType, json: &Json) -> Result<Wip, RowError> {
+        let shape = wip.shape();
+        match (data.kind, json) {
+            (StructKind::Tuple | StructKind::TupleStruct, _) if data.fields.len() == 1 => {
+                let decoder = self.at(0);
+                wip = wip.begin_nth_field(0).map_err(|e| decoder.error(e))?;
+                wip = decoder.json(wip, json)?;
+                wip.end().map_err(|e| decoder.error(e))
+            }
+            (StructKind::Tuple | StructKind::TupleStruct, Json::Array(items))
+                if items.len() == data.fields.len() =>
+            {
+                for (i, item) in items.iter().enumerate() {
+                    let decoder = self.at(i);
+                    wip = wip.begin_nth_field(i).map_err(|e| decoder.error(e))?;
+                    wip = decoder.json(wip, item)?;
+                    wip = wip.end().map_err(|e| decoder.error(e))?;
+                }
+                Ok(wip)
+            }
+            (StructKind::Struct, Json::Object(entries)) => {
+                for (i, field) in data.fields.iter().enumerate() {
+                    let decoder = self.at(field.name);
+                    match entries.get(field.rename.unwrap_or(field.name)) {
+                        Some(value) => {
+                            wip = wip.begin_nth_field(i).map_err(|e| decoder.error(e))?;
+                            wip = decoder.json(wip, value)?;
+                            wip = wip.end().map_err(|e| decoder.error(e))?;
+                        }
+                        None if field.has_default()
+                            || matches!(field.shape().def, Def::Option(_)) =>
+                        {
+                            wip = wip
+                                .set_nth_field_to_default(i)
+                                .map_err(|e| decoder.error(e))?;
+                        }
+                        None => {
+                            return Err(RowError::Missing {
+                                field: decoder.field,
+                            });
+                        }
+                    }
+                }
+                Ok(wip)
+            }
+            (StructKind::Unit, Json::Null) => Ok(wip),
+            (_, other) => Err(self.mismatch(shape, json_kind(other))),
+        }
+    }
+}
+
+fn is_null(value: &Value) -> bool {
+    matches!(
+        value,
+        Value::Bool(None)
+            | Value::TinyInt(None)
+            | Value::SmallInt(None)
+            | Value::Int(None)
+            | Value::BigInt(None)
+            | Value::TinyUnsigned(None)
+            | Value::SmallUnsigned(None)
+            | Value::Unsigned(None)
+            | Value::BigUnsigned(None)
+            | Value::Float(None)
+            | Value::Double(None)
+            | Value::String(None)
+            | Value::Char(None)
+            | Value::Bytes(None)
+            | Value::Json(None)
+            | Value::Array(_, None)
+    )
+}
+
+fn integer_of(value: &Value) -> Option<i128> {
+    match value {
+        Value::TinyInt(Some(n)) => Some(*n as i128),
+        Value::SmallInt(Some(n)) => Some(*n as i128),
+        Value::Int(Some(n)) => Some(*n as i128),
+        Value::BigInt(Some(n)) => Some(*n as i128),
+        Value::TinyUnsigned(Some(n)) => Some(*n as i128),
+        Value::SmallUnsigned(Some(n)) => Some(*n as i128),
+        Value::Unsigned(Some(n)) => Some(*n as i128),
+        Value::BigUnsigned(Some(n)) => Some(*n as i128),
+        _ => None,
+    }
+}
+
+/// What kind of value a column held, for error messages.
+fn describe(value: &Value) -> &'static str {
+    match value {
+        Value::Bool(_) => "boolean",
+        Value::Float(_) | Value::Double(_) => "float",
+        Value::String(_) | Value::Char(_) => "text",
+        Value::Bytes(_) => "bytes",
+        Value::Json(_) => "json",
+        Value::Array(..) => "array",
+        other if integer_of(other).is_some() => "integer",
+        _ => "unsupported value",
+    }
+}
+
+fn json_kind(json: &Json) -> &'static str {
+    match json {
+        Json::Null => "JSON null",
+        Json::Bool(_) => "JSON boolean",
+        Json::Number(_) => "JSON number",
+        Json::String(_) => "JSON string",
+        Json::Array(_) => "JSON array",
+        Json::Object(_) => "JSON object",
+    }
+}
+
+/// Splits the text form of a composite value, e.g. `("a b","1",)`, into its
+/// fields; unquoted empty fields are `NULL`.
+fn parse_record(text: &str) -> Option<Vec<Option<String>>> {
+    let inner = text.strip_prefix('(')?.strip_suffix(')')?;
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let (mut quoted, mut in_quotes) = (false, false);
+
+    let mut chars = inner.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            ',' if !in_quotes => {
+                fields.push((quoted || !field.is_empty()).then(|| mem::take(&mut field)));
+                quoted = false;
+            }
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => {
+                in_quotes = !in_quotes;
+                quoted = true;
+            }
+            '\\' => field.push(chars.next()?),
+            c => field.push(c),
+        }
+    }
+    if in_quotes {
+        return None;
+    }
+    fields.push((quoted || !field.is_empty()).then_some(field));
+    Some(fields)
+}
+
+/// Splits the text form of an array, e.g. `{a,"b c",NULL}`, into its
+/// elements; unquoted `NULL` is `NULL`, and nested arrays are kept whole for
+/// their own element type to split.
+fn parse_array(text: &str) -> Option<Vec<Option<String>>> {
+    let inner = text.strip_prefix('{')?.strip_suffix('}')?;
+    let mut items = Vec::new();
+    if inner.is_empty() {
+        return Some(items);
+    }
+    let mut item = String::new();
+    let (mut quoted, mut in_quotes, mut depth) = (false, false, 0usize);
+
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            ',' if !in_quotes && depth == 0 => {
+                items.push(array_item(mem::take(&mut item), quoted));
+                quoted = false;
+            }
+            '"' => {
+                in_quotes = !in_quotes;
+                quoted = true;
+                if depth > 0 {
+                    item.push(c);
+                }
+            }
+            '\\' => {
+                if depth > 0 {
+                    item.push(c);
+                }
+                item.push(chars.next()?);
+            }
+            '{' if !in_quotes => {
+                depth += 1;
+                item.push(c);
+            }
+            '}' if !in_quotes => {
+                depth = depth.checked_sub(1)?;
+                item.push(c);
+            }
+            c => item.push(c),
+        }
+    }
+    if in_quotes || depth != 0 {
+        return None;
+    }
+    items.push(array_item(item, quoted));
+    Some(items)
+}
+
+fn array_item(item: String, quoted: bool) -> Option<String> {
+    (quoted || !item.eq_ignore_ascii_case("NULL")).then_some(item)
+}
+
+/// The bytes of PostgreSQL's hex form of a `bytea`, e.g. `\x0aff`.
+pub(crate) fn bytea_from_text(text: &str) -> Option<Vec<u8>> {
+    let hex = text.strip_prefix("\\x")?;
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
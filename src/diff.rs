@@ -0,0 +1,740 @@
+use facet::Facet;
+use facet_diff::FacetDiff;
+
+use crate::*;
+
+/// One difference between two schemas, as produced by [`PartialSchema::diff`].
+///
+/// Tables and columns are matched by name, constraints and indexes by their
+/// effective name. A constraint or index that changed in place is reported as
+/// a drop followed by an add.
+#[derive(Facet, Clone)]
+#[repr(C)]
+pub enum SchemaChange {
+    CreateEnum(EnumType),
+    DropEnum(EnumType),
+    /// A value added to an existing enum, positioned after its predecessor or,
+    /// when it goes ahead of every existing value, before the first of those.
+    AddEnumValue {
+        schema: Option<String>,
+        name: String,
+        value: String,
+        before: Option<String>,
+        after: Option<String>,
+    },
+    /// Values removed from, or reordered within, an existing enum. PostgreSQL
+    /// cannot drop enum values, so this renders as a rebuild of the type, with
+    /// every attribute in `composites` and every column in `columns` cast over
+    /// to it.
+    DropEnumValues {
+        enum_type: EnumType,
+        dropped: Vec<String>,
+        /// Composite types with an attribute of the enum or an array of it.
+        composites: Vec<CompositeType>,
+        /// Table columns whose type includes the enum, directly, through an
+        /// array or through a composite, with the table they belong to.
+        columns: Vec<(String, Column)>,
+    },
+    CreateTable(Table),
+    DropTable(Table),
+    AlterTableComment {
+        table: String,
+        comment: Option<String>,
+    },
+    AddColumn {
+        table: String,
+        column: Column,
+    },
+    DropColumn {
+        table: String,
+        column: String,
+    },
+    AlterColumnType {
+        table: String,
+        column: String,
+        from: DataType,
+        to: DataType,
+    },
+    AlterColumnNullability {
+        table: String,
+        column: String,
+        nullable: bool,
+    },
+    AlterColumnDefault {
+        table: String,
+        column: String,
+        default: Option<String>,
+    },
+    AlterColumnComment {
+        table: String,
+        column: String,
+        comment: Option<String>,
+    },
+    AddPrimaryKey {
+        table: String,
+        primary_key: PrimaryKey,
+    },
+    DropPrimaryKey {
+        table: String,
+        primary_key: PrimaryKey,
+    },
+    AddUnique {
+        table: String,
+        unique: UniqueConstraint,
+    },
+    DropUnique {
+        table: String,
+        unique: UniqueConstraint,
+    },
+    AddCheck {
+        table: String,
+        name: String,
+        check: CheckConstraint,
+    },
+    DropCheck {
+        table: String,
+        name: String,
+    },
+    AddForeignKey {
+        table: String,
+        foreign_key: ForeignKey,
+    },
+    DropForeignKey {
+        table: String,
+        foreign_key: ForeignKey,
+    },
+    CreateIndex {
+        table: String,
+        index: Index,
+    },
+    DropIndex {
+        table: String,
+        index: Index,
+    },
+}
+
+impl PartialSchema {
+    /// Compute the changes that turn `old` into `new`.
+    ///
+    /// Only enums and tables (with their columns, constraints and indexes) are
+    /// compared. Unnamed checks are matched by position, see the naming used by
+    /// [`PartialSchema::to_ddl`].
+    pub fn diff(old: &PartialSchema, new: &PartialSchema) -> Vec<SchemaChange> {
+        let mut changes = Vec::new();
+
+        diff_enums(old, new, &mut changes);
+
+        for t in &new.tables {
+            match old.tables.iter().find(|o| o.name == t.name) {
+                Some(o) => diff_table(o, t, &mut changes),
+                None => changes.push(SchemaChange::CreateTable(t.clone())),
+            }
+        }
+        for o in &old.tables {
+            if !new.tables.iter().any(|t| t.name == o.name) {
+                changes.push(SchemaChange::DropTable(o.clone()));
+            }
+        }
+
+        changes
+    }
+
+    /// Render `changes` as a migration script against `schema_name`.
+    ///
+    /// Statements are ordered so that each one only depends on what came
+    /// before it: constraints and indexes are dropped first, then types and
+    /// tables are created and altered, and foreign keys are added last.
+    pub fn migration_ddl(changes: &[SchemaChange], schema_name: &str) -> String {
+        use SchemaChange::*;
+
+        let mut stmts: Vec<String> = Vec::new();
+        let alter =
+            |table: &str| format!("ALTER TABLE {}", quote_qualified(Some(schema_name), table));
+        let drop_constraint = |table: &str, name: &str| {
+            format!("{} DROP CONSTRAINT {};", alter(table), quote_ident(name))
+        };
+
+        // -- Pass 1: Drop dependent objects --
+        for change in changes {
+            match change {
+                DropForeignKey { table, foreign_key } => {
+                    stmts.push(drop_constraint(table, &foreign_key.effective_name(table)))
+                }
+                DropTable(t) => {
+                    for fk in &t.foreign_keys {
+                        stmts.push(drop_constraint(&t.name, &fk.effective_name(&t.name)));
+                    }
+                }
+                DropIndex { table, index } => stmts.push(format!(
+                    "DROP INDEX {};",
+                    quote_qualified(Some(schema_name), &index.effective_name(table))
+                )),
+                DropCheck { table, name } => stmts.push(drop_constraint(table, name)),
+                DropUnique { table, unique } => {
+                    stmts.push(drop_constraint(table, &unique.effective_name(table)))
+                }
+                DropPrimaryKey { table, primary_key } => {
+                    stmts.push(drop_constraint(table, &primary_key.effective_name(table)))
+                }
+                _ => {}
+            }
+        }
+
+        // -- Pass 2: Types --
+        for change in changes {
+            match change {
+                CreateEnum(e) => stmts.extend(render_enum(e, schema_name)),
+                AddEnumValue {
+                    schema,
+                    name,
+                    value,
+                    before,
+                    after,
+                } => {
+                    let mut stmt = format!(
+                        "ALTER TYPE {} ADD VALUE '{}'",
+                        quote_type(schema.as_deref(), name, schema_name),
+                        esc(value)
+                    );
+                    if let Some(a) = after {
+                        stmt.push_str(&format!(" AFTER '{}'", esc(a)));
+                    } else if let Some(b) = before {
+                        stmt.push_str(&format!(" BEFORE '{}'", esc(b)));
+                    }
+                    stmt.push(';');
+                    stmts.push(stmt);
+                }
+                DropEnumValues {
+                    enum_type,
+                    composites,
+                    columns,
+                    ..
+                } => {
+                    let q = quote_type(enum_type.schema.as_deref(), &enum_type.name, schema_name);
+                    let old_name = format!("{}__old", enum_type.name);
+                    stmts.push(format!(
+                        "ALTER TYPE {} RENAME TO {};",
+                        q,
+                        quote_ident(&old_name)
+                    ));
+                    stmts.extend(render_enum(enum_type, schema_name));
+
+                    // Composite attributes can only change type while no table
+                    // stores the composite, so those columns are parked as text.
+                    let via_composite = |c: &Column| !is_enum_or_array(&c.data_type, enum_type);
+                    for (table, c) in columns.iter().filter(|(_, c)| via_composite(c)) {
+                        let col = quote_ident(&c.name);
+                        stmts.push(format!(
+                            "{} ALTER COLUMN {} TYPE text USING {}::text;",
+                            alter(table),
+                            col,
+                            col
+                        ));
+                    }
+                    for ct in composites {
+                        let name = quote_type(ct.schema.as_deref(), &ct.name, schema_name);
+                        for f in &ct.fields {
+                            if is_enum_or_array(&f.data_type, enum_type) {
+                                stmts.push(format!(
+                                    "ALTER TYPE {} ALTER ATTRIBUTE {} TYPE {};",
+                                    name,
+                                    quote_ident(&f.name),
+                                    render_data_type(&qualify_type(&f.data_type, schema_name))
+                                ));
+                            }
+                        }
+                    }
+                    for (table, c) in columns {
+                        let col = quote_ident(&c.name);
+                        let ty = render_data_type(&qualify_type(&c.data_type, schema_name));
+                        let via = if via_composite(c) {
+                            String::new()
+                        } else {
+                            format!("::{}", text_form(&c.data_type))
+                        };
+                        stmts.push(format!(
+                            "{} ALTER COLUMN {} TYPE {} USING {}{}::{};",
+                            alter(table),
+                            col,
+                            ty,
+                            col,
+                            via,
+                            ty
+                        ));
+                    }
+                    stmts.push(format!(
+                        "DROP TYPE {};",
+                        quote_type(enum_type.schema.as_deref(), &old_name, schema_name)
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        // -- Pass 3: Tables and columns --
+        for change in changes {
+            match change {
+                CreateTable(t) => stmts.extend(render_table(t, schema_name)),
+                AddColumn { table, column } => {
+                    stmts.push(format!(
+                        "{} ADD COLUMN {};",
+                        alter(table),
+                        render_column(column, schema_name)
+                    ));
+                    if let Some(comment) = &column.comment {
+                        stmts.push(render_column_comment(
+                            table,
+                            &column.name,
+                            Some(comment),
+                            schema_name,
+                        ));
+                    }
+                }
+                AlterTableComment { table, comment } => {
+                    stmts.push(render_table_comment(table, comment.as_deref(), schema_name))
+                }
+                AlterColumnType {
+                    table, column, to, ..
+                } => {
+                    let ty = render_data_type(&qualify_type(to, schema_name));
+                    stmts.push(format!(
+                        "{} ALTER COLUMN {} TYPE {} USING {}::{};",
+                        alter(table),
+                        quote_ident(column),
+                        ty,
+                        quote_ident(column),
+                        ty
+                    ));
+                }
+                AlterColumnNullability {
+                    table,
+                    column,
+                    nullable,
+                } => stmts.push(format!(
+                    "{} ALTER COLUMN {} {} NOT NULL;",
+                    alter(table),
+                    quote_ident(column),
+                    if *nullable { "DROP" } else { "SET" }
+                )),
+                AlterColumnDefault {
+                    table,
+                    column,
+                    default,
+                } => stmts.push(match default {
+                    Some(d) => format!(
+                        "{} ALTER COLUMN {} SET DEFAULT {};",
+                        alter(table),
+                        quote_ident(column),
+                        d
+                    ),
+                    None => format!(
+                        "{} ALTER COLUMN {} DROP DEFAULT;",
+                        alter(table),
+                        quote_ident(column)
+                    ),
+                }),
+                AlterColumnComment {
+                    table,
+                    column,
+                    comment,
+                } => stmts.push(render_column_comment(
+                    table,
+                    column,
+                    comment.as_deref(),
+                    schema_name,
+                )),
+                DropColumn { table, column } => stmts.push(format!(
+                    "{} DROP COLUMN {};",
+                    alter(table),
+                    quote_ident(column)
+                )),
+                _ => {}
+            }
+        }
+
+        // -- Pass 4: Local constraints --
+        for change in changes {
+            match change {
+                AddPrimaryKey { table, primary_key } => stmts.push(format!(
+                    "{} ADD CONSTRAINT {} PRIMARY KEY ({});",
+                    alter(table),
+                    quote_ident(&primary_key.effective_name(table)),
+                    cols(&primary_key.columns)
+                )),
+                AddUnique { table, unique } => {
+                    stmts.push(render_unique(unique, table, schema_name))
+                }
+                AddCheck { table, name, check } => {
+                    stmts.push(render_check(check, name, table, schema_name))
+                }
+                _ => {}
+            }
+        }
+
+        // -- Pass 5: Drop tables and types nothing refers to anymore --
+        for change in changes {
+            if let DropTable(t) = change {
+                stmts.push(format!(
+                    "DROP TABLE {};",
+                    quote_qualified(Some(schema_name), &t.name)
+                ));
+            }
+        }
+        for change in changes {
+            if let DropEnum(e) = change {
+                stmts.push(format!(
+                    "DROP TYPE {};",
+                    quote_type(e.schema.as_deref(), &e.name, schema_name)
+                ));
+            }
+        }
+
+        // -- Pass 6: Indexes --
+        for change in changes {
+            match change {
+                CreateTable(t) => {
+                    for idx in t.indexes.iter().filter(|idx| !idx.is_primary) {
+                        stmts.push(render_index(idx, &t.name, schema_name));
+                    }
+                }
+                CreateIndex { table, index } => stmts.push(render_index(index, table, schema_name)),
+                _ => {}
+            }
+        }
+
+        // -- Pass 7: Foreign Keys --
+        for change in changes {
+            match change {
+                CreateTable(t) => {
+                    for fk in &t.foreign_keys {
+                        stmts.push(render_foreign_key(fk, &t.name, schema_name));
+                    }
+                }
+                AddForeignKey { table, foreign_key } => {
+                    stmts.push(render_foreign_key(foreign_key, table, schema_name))
+                }
+                _ => {}
+            }
+        }
+
+        stmts.join("\n")
+    }
+}
+
+/// Structural equality through facet-diff, so the model types don't need `PartialEq`.
+pub(crate) fn same<'f, T: Facet<'f>>(a: &T, b: &T) -> bool {
+    a.diff(b).is_equal()
+}
+
+/// Whether `data_type` is the enum `e` or a (possibly nested) array of it.
+fn is_enum_or_array(data_type: &DataType, e: &EnumType) -> bool {
+    match data_type {
+        DataType::Enum { schema, name } => *schema == e.schema && *name == e.name,
+        DataType::Array(inner) => is_enum_or_array(inner, e),
+        _ => false,
+    }
+}
+
+/// Whether `data_type` includes the enum `e`, directly, through arrays or
+/// through the fields of one of `composites`.
+fn uses_enum(data_type: &DataType, e: &EnumType, composites: &[CompositeType]) -> bool {
+    match data_type {
+        DataType::Array(inner) => uses_enum(inner, e, composites),
+        DataType::Composite { schema, name } => composites
+            .iter()
+            .find(|c| c.schema == *schema && c.name == *name)
+            .is_some_and(|c| {
+                c.fields
+                    .iter()
+                    .any(|f| uses_enum(&f.data_type, e, composites))
+            }),
+        other => is_enum_or_array(other, e),
+    }
+}
+
+/// The text type with the same array shape as `data_type`, e.g. `text[]` for
+/// `mood[]`, to cast enum values through by label.
+fn text_form(data_type: &DataType) -> String {
+    match data_type {
+        DataType::Array(inner) => format!("{}[]", text_form(inner)),
+        _ => "text".into(),
+    }
+}
+
+fn diff_enums(old: &PartialSchema, new: &PartialSchema, changes: &mut Vec<SchemaChange>) {
+    let matches = |a: &EnumType, b: &EnumType| a.schema == b.schema && a.name == b.name;
+
+    for e in &new.enums {
+        let Some(o) = old.enums.iter().find(|o| matches(o, e)) else {
+            changes.push(SchemaChange::CreateEnum(e.clone()));
+            continue;
+        };
+
+        let dropped: Vec<String> = o
+            .variants
+            .iter()
+            .filter(|v| !e.variants.contains(v))
+            .cloned()
+            .collect();
+        let kept_old: Vec<&String> = o
+            .variants
+            .iter()
+            .filter(|v| e.variants.contains(v))
+            .collect();
+        let kept_new: Vec<&String> = e
+            .variants
+            .iter()
+            .filter(|v| o.variants.contains(v))
+            .collect();
+
+        if !dropped.is_empty() || kept_old != kept_new {
+            let composites = old
+                .composite_types
+                .iter()
+                .filter(|c| c.fields.iter().any(|f| is_enum_or_array(&f.data_type, e)))
+                .cloned()
+                .collect();
+            let columns = old
+                .tables
+                .iter()
+                .flat_map(|t| t.columns.iter().map(move |c| (t, c)))
+                .filter(|(_, c)| uses_enum(&c.data_type, e, &old.composite_types))
+                .map(|(t, c)| (t.name.clone(), c.clone()))
+                .collect();
+            changes.push(SchemaChange::DropEnumValues {
+                enum_type: e.clone(),
+                dropped,
+                composites,
+                columns,
+            });
+            continue;
+        }
+
+        // Values ahead of the first existing one go BEFORE it; everything else
+        // goes AFTER its predecessor, which by then exists.
+        let first_kept = kept_new.first().map(|v| (*v).clone());
+        let mut seen_kept = false;
+        for (i, v) in e.variants.iter().enumerate() {
+            if o.variants.contains(v) {
+                seen_kept = true;
+                continue;
+            }
+            let (before, after) = if seen_kept {
+                (None, Some(e.variants[i - 1].clone()))
+            } else {
+                (first_kept.clone(), None)
+            };
+            changes.push(SchemaChange::AddEnumValue {
+                schema: e.schema.clone(),
+                name: e.name.clone(),
+                value: v.clone(),
+                before,
+                after,
+            });
+        }
+    }
+
+    for o in &old.enums {
+        if !new.enums.iter().any(|e| matches(o, e)) {
+            changes.push(SchemaChange::DropEnum(o.clone()));
+        }
+    }
+}
+
+fn diff_table(old: &Table, new: &Table, changes: &mut Vec<SchemaChange>) {
+    let table = &new.name;
+
+    if old.comment != new.comment {
+        changes.push(SchemaChange::AlterTableComment {
+            table: table.clone(),
+            comment: new.comment.clone(),
+        });
+    }
+
+    // Columns
+    for c in &new.columns {
+        let Some(o) = old.columns.iter().find(|o| o.name == c.name) else {
+            changes.push(SchemaChange::AddColumn {
+                table: table.clone(),
+                column: c.clone(),
+            });
+            continue;
+        };
+        if !same(&o.data_type, &c.data_type) {
+            changes.push(SchemaChange::AlterColumnType {
+                table: table.clone(),
+                column: c.name.clone(),
+                from: o.data_type.clone(),
+                to: c.data_type.clone(),
+            });
+        }
+        if o.nullable != c.nullable {
+            changes.push(SchemaChange::AlterColumnNullability {
+                table: table.clone(),
+                column: c.name.clone(),
+                nullable: c.nullable,
+            });
+        }
+        if o.default != c.default {
+            changes.push(SchemaChange::AlterColumnDefault {
+                table: table.clone(),
+                column: c.name.clone(),
+                default: c.default.clone(),
+            });
+        }
+        if o.comment != c.comment {
+            changes.push(SchemaChange::AlterColumnComment {
+                table: table.clone(),
+                column: c.name.clone(),
+                comment: c.comment.clone(),
+            });
+        }
+    }
+    for o in &old.columns {
+        if !new.columns.iter().any(|c| c.name == o.name) {
+            changes.push(SchemaChange::DropColumn {
+                table: table.clone(),
+                column: o.name.clone(),
+            });
+        }
+    }
+
+    // Primary key
+    match (&old.primary_key, &new.primary_key) {
+        (Some(o), Some(n)) if same(o, n) => {}
+        (o, n) => {
+            if let Some(o) = o {
+                changes.push(SchemaChange::DropPrimaryKey {
+                    table: table.clone(),
+                    primary_key: o.clone(),
+                });
+            }
+            if let Some(n) = n {
+                changes.push(SchemaChange::AddPrimaryKey {
+                    table: table.clone(),
+                    primary_key: n.clone(),
+                });
+            }
+        }
+    }
+
+    // Uniques
+    for (o, n) in pair_by_name(&old.uniques, &new.uniques, |u| u.effective_name(table)) {
+        match (o, n) {
+            (Some(o), Some(n)) if same(o, n) => {}
+            (o, n) => {
+                if let Some(o) = o {
+                    changes.push(SchemaChange::DropUnique {
+                        table: table.clone(),
+                        unique: o.clone(),
+                    });
+                }
+                if let Some(n) = n {
+                    changes.push(SchemaChange::AddUnique {
+                        table: table.clone(),
+                        unique: n.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    // Checks
+    let old_checks: Vec<_> = old.checks.iter().zip(old.check_names()).collect();
+    let new_checks: Vec<_> = new.checks.iter().zip(new.check_names()).collect();
+    for (o, n) in pair_by_name(&old_checks, &new_checks, |(_, name)| name.clone()) {
+        match (o, n) {
+            (Some((o, _)), Some((n, _))) if same(*o, *n) => {}
+            (o, n) => {
+                if let Some((_, name)) = o {
+                    changes.push(SchemaChange::DropCheck {
+                        table: table.clone(),
+                        name: name.clone(),
+                    });
+                }
+                if let Some((check, name)) = n {
+                    changes.push(SchemaChange::AddCheck {
+                        table: table.clone(),
+                        name: name.clone(),
+                        check: (*check).clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    // Indexes
+    let old_indexes: Vec<Index> = old
+        .indexes
+        .iter()
+        .filter(|i| !i.is_primary)
+        .cloned()
+        .collect();
+    let new_indexes: Vec<Index> = new
+        .indexes
+        .iter()
+        .filter(|i| !i.is_primary)
+        .cloned()
+        .collect();
+    for (o, n) in pair_by_name(&old_indexes, &new_indexes, |i| i.effective_name(table)) {
+        match (o, n) {
+            (Some(o), Some(n)) if same(o, n) => {}
+            (o, n) => {
+                if let Some(o) = o {
+                    changes.push(SchemaChange::DropIndex {
+                        table: table.clone(),
+                        index: o.clone(),
+                    });
+                }
+                if let Some(n) = n {
+                    changes.push(SchemaChange::CreateIndex {
+                        table: table.clone(),
+                        index: n.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    // Foreign keys
+    for (o, n) in pair_by_name(&old.foreign_keys, &new.foreign_keys, |fk| {
+        fk.effective_name(table)
+    }) {
+        match (o, n) {
+            (Some(o), Some(n)) if same(o, n) => {}
+            (o, n) => {
+                if let Some(o) = o {
+                    changes.push(SchemaChange::DropForeignKey {
+                        table: table.clone(),
+                        foreign_key: o.clone(),
+                    });
+                }
+                if let Some(n) = n {
+                    changes.push(SchemaChange::AddForeignKey {
+                        table: table.clone(),
+                        foreign_key: n.clone(),
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Pair up items of `old` and `new` by name: new items in order (with their old
+/// counterpart if any), followed by old items that no longer exist.
+fn pair_by_name<'a, T>(
+    old: &'a [T],
+    new: &'a [T],
+    name: impl Fn(&T) -> String,
+) -> Vec<(Option<&'a T>, Option<&'a T>)> {
+    let mut pairs: Vec<_> = new
+        .iter()
+        .map(|n| (old.iter().find(|o| name(o) == name(n)), Some(n)))
+        .collect();
+    pairs.extend(
+        old.iter()
+            .filter(|o| !new.iter().any(|n| name(n) == name(o)))
+            .map(|o| (Some(o), None)),
+    );
+    pairs
+}
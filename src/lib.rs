@@ -1,21 +1,169 @@
-use std::{borrow::Cow, collections::HashMap};
+use std::{collections::HashMap, fmt};
 
 use facet::Facet;
 
 mod conversion;
+mod decode;
+mod diff;
+mod ident;
+mod mapping;
+mod naming;
 pub mod relations;
-pub use conversion::ConversionError;
+mod row;
+mod statements;
+pub use conversion::{ConversionError, ConversionOptions, SchemaBuilder};
+pub use decode::{Row, RowDecoder};
+pub use diff::SchemaChange;
+pub use ident::quote_ident;
+use ident::{quote_qualified, quote_qualified_type};
+pub use mapping::{DecodeFn, EncodeFn, TypeMapping, TypeRegistry};
+pub use naming::{Affixed, Lowercase, NamingStrategy, Plural, SnakeCase};
+pub use row::{EnumInsert, RowEncoder, RowError};
+pub use statements::{SchemaStatements, StatementError};
+
+/// Comma-separated column list of a struct-level `psql::primary_key` or
+/// `psql::unique`, e.g. `"tenant_id, id"`.
+pub type Columns = &'static str;
+
+/// A `psql::index`: a column list, or
+/// `[name] [USING method] [(columns)] [INCLUDE (columns)] [WHERE predicate]`.
+pub type IndexDefinition = &'static str;
+
+/// Length of a `psql::varchar(n)` column.
+pub type Length = u32;
+
+/// Precision and scale of a `psql::numeric([p, s])` column.
+pub type Precision = [u32; 2];
 
 facet::define_attr_grammar! {
     ns "psql";
     crate_path ::facet_psql_schema;
 
     pub enum Attr {
-        PrimaryKey,
+        /// Make the field's column part of the primary key. On a struct, list
+        /// the key's columns instead: `psql::primary_key("tenant_id, id")`.
+        PrimaryKey(Option<Columns>),
+        /// Add a `UNIQUE` constraint on the field's column. On a struct, list
+        /// the constrained columns: `psql::unique("a, b")`.
+        Unique(Option<Columns>),
+        /// Index the field's column. On a struct, list the indexed columns,
+        /// each optionally followed by an operator class, `ASC`/`DESC` and
+        /// `NULLS FIRST`/`NULLS LAST`: `psql::index("tenant_id, created DESC")`.
+        /// Spell out the rest like `CREATE INDEX` does to configure it:
+        /// `psql::index("post_tags USING gin (tags) INCLUDE (title) WHERE published")`.
+        Index(Option<IndexDefinition>),
+        /// Add a `CHECK` with this expression for the field's column, e.g.
+        /// `psql::check("age >= 0")`. Repeat it for several. On a struct, the
+        /// check applies to the whole table. Name it by writing the constraint
+        /// out: `psql::check("CONSTRAINT adult CHECK (age >= 18)")`.
+        Check(&'static str),
+        /// `DEFAULT` expression of the field's column, as SQL, e.g.
+        /// `psql::default("now()")`.
+        Default(&'static str),
+        /// Make the column `NOT NULL` even if the field is an `Option`.
+        NotNull,
+        /// Leave the field out of the table. Fields facet skips when
+        /// serializing are left out too.
+        Skip,
+        /// SQL type of the field's column instead of the one derived from its
+        /// Rust type, e.g. `psql::type = "numeric(12,2)"`.
+        Type(&'static str),
+        /// Store a string field as `varchar`, e.g. `psql::varchar(255)`.
+        Varchar(Option<Length>),
+        /// Store a string field as `citext`, from the extension of that name.
+        Citext,
+        /// Store a number as `numeric`, with the given precision and scale:
+        /// `psql::numeric([12, 2])`. Bare, the column is an unconstrained `numeric`.
+        Numeric(Option<Precision>),
+        /// Accept a declared type that doesn't hold every value of the field,
+        /// such as `integer` for an `i64` or `char(n)` for a `String`.
+        Lossy,
+        /// Foreign key to the table generated for the given type, e.g.
+        /// `psql::references(Customer)`. Wrap the type in [`Ref`] to add
+        /// referential actions: `psql::references(psql::Ref<Customer, psql::on_delete::Cascade>)`.
+        References(shape_type),
+        /// Store a unit-only enum as an integer rather than a PostgreSQL
+        /// enum. Goes on the field or on the enum itself.
+        AsInteger,
+        /// Store a nested struct field as a composite type instead of JSONB.
+        Composite,
+        /// Store the field as JSONB, even if it could be a native array or
+        /// composite type.
+        Jsonb,
+        /// Store a byte sequence such as `Vec<u8>` as a native `smallint[]`
+        /// instead of `bytea`.
+        Array,
+        /// Add a `CHECK` that a fixed-size array column has the array's length.
+        CheckLength,
+        /// How a top-level enum is stored: `"table_per_variant"` (the
+        /// default), `"single_table"`, `"jsonb"`, or `"enum"` for unit-only enums.
+        Storage(&'static str),
+        /// What the `discriminant` column of an enum's table holds:
+        /// `"integer"` (the default), `"text"` with a lookup table of variant
+        /// labels, or `"enum"` for a PostgreSQL enum of them.
+        Discriminator(&'static str),
+        /// Name of the struct's or enum's table, instead of the one the
+        /// naming strategy derives.
+        Table(&'static str),
+        /// Name of the field's column, e.g. for the fields of tuple structs
+        /// and variants, which are otherwise named `value` or `value_<n>`.
+        Column(&'static str),
+        /// How a single-field tuple struct is stored when used as a field:
+        /// `"inner"` as its field's type, or `"domain"` as a domain over it.
+        /// `#[facet(transparent)]` newtypes default to `"inner"`.
+        Newtype(&'static str),
+        /// Store a newtype as a domain over its field's type with this
+        /// `CHECK`, e.g. `psql::domain("VALUE > 0")`. Repeat it for several.
+        Domain(&'static str),
     }
 }
 
-#[derive(Facet, Clone)]
+macro_rules! referential_actions {
+    ($($action:ident => $sql:literal,)*) => {
+        $(
+            #[doc = concat!("`", $sql, "`")]
+            #[derive(Facet)]
+            pub struct $action;
+        )*
+    };
+}
+
+/// Target of a `psql::references` together with its referential actions,
+/// taken from [`on_delete`] and [`on_update`] in either order, e.g.
+/// `psql::references(psql::Ref<Order, psql::on_delete::SetNull, psql::on_update::Cascade>)`.
+#[derive(Facet)]
+#[facet(type_tag = "psql::references")]
+pub struct Ref<T: 'static, A: 'static = (), B: 'static = ()>(std::marker::PhantomData<(T, A, B)>);
+
+/// `ON DELETE` actions for `psql::references`, e.g.
+/// `psql::references(psql::Ref<Customer, psql::on_delete::Cascade>)`.
+pub mod on_delete {
+    use facet::Facet;
+
+    referential_actions! {
+        NoAction => "ON DELETE NO ACTION",
+        Restrict => "ON DELETE RESTRICT",
+        Cascade => "ON DELETE CASCADE",
+        SetNull => "ON DELETE SET NULL",
+        SetDefault => "ON DELETE SET DEFAULT",
+    }
+}
+
+/// `ON UPDATE` actions for `psql::references`, e.g.
+/// `psql::references(psql::Ref<Customer, psql::on_update::Cascade>)`.
+pub mod on_update {
+    use facet::Facet;
+
+    referential_actions! {
+        NoAction => "ON UPDATE NO ACTION",
+        Restrict => "ON UPDATE RESTRICT",
+        Cascade => "ON UPDATE CASCADE",
+        SetNull => "ON UPDATE SET NULL",
+        SetDefault => "ON UPDATE SET DEFAULT",
+    }
+}
+
+#[derive(Facet, Clone, Default)]
 pub struct PartialSchema {
     pub tables: Vec<Table>,
     pub views: Vec<View>,
@@ -48,6 +196,9 @@ pub struct Table {
     pub comment: Option<String>,
     /// Owned sequences (name -> owned_by column)
     pub owned_sequences: Vec<String>,
+    /// Rows inserted right after the table is created, as SQL literals in
+    /// column order. Used for lookup tables.
+    pub rows: Vec<Vec<String>>,
 }
 
 #[derive(Facet, Clone)]
@@ -398,13 +549,82 @@ pub struct QualifiedObject {
     pub name: String,
 }
 
-/// Example: small helper trait (not required) for convenience
-impl QualifiedName {
-    pub fn to_string(&self) -> String {
+impl fmt::Display for QualifiedName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self.schema {
-            Some(s) => format!("{}.{}", s, self.name),
-            None => self.name.clone(),
+            Some(s) => write!(f, "{}.{}", s, self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+impl PrimaryKey {
+    /// The constraint name, falling back to PostgreSQL's `<table>_pkey` convention.
+    pub(crate) fn effective_name(&self, table: &str) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| format!("{}_pkey", table))
+    }
+}
+
+impl Table {
+    /// Names for `checks`, in order. Unnamed checks are numbered the way
+    /// PostgreSQL does it: `<table>_check`, `<table>_check1`, ...
+    pub(crate) fn check_names(&self) -> Vec<String> {
+        let mut unnamed = 0;
+        self.checks
+            .iter()
+            .map(|ck| match &ck.name {
+                Some(name) => name.clone(),
+                None => {
+                    let name = match unnamed {
+                        0 => format!("{}_check", self.name),
+                        n => format!("{}_check{}", self.name, n),
+                    };
+                    unnamed += 1;
+                    name
+                }
+            })
+            .collect()
+    }
+}
+
+impl UniqueConstraint {
+    /// The constraint name, falling back to PostgreSQL's `<table>_<cols>_key` convention.
+    pub(crate) fn effective_name(&self, table: &str) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| format!("{}_{}_key", table, self.columns.join("_")))
+    }
+}
+
+impl ForeignKey {
+    /// The constraint name, falling back to PostgreSQL's `<table>_<cols>_fkey` convention.
+    pub(crate) fn effective_name(&self, table: &str) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| format!("{}_{}_fkey", table, self.columns.join("_")))
+    }
+}
+
+impl Index {
+    /// The index name, generating `<table>_idx_<cols>` when none was given.
+    pub(crate) fn effective_name(&self, table: &str) -> String {
+        if !self.name.is_empty() {
+            return self.name.clone();
         }
+        format!(
+            "{}_idx_{}",
+            table,
+            self.columns
+                .iter()
+                .map(|c| match &c.expr {
+                    IndexExpr::Column(n) => n.as_str(),
+                    _ => "expr",
+                })
+                .collect::<Vec<_>>()
+                .join("_")
+        )
     }
 }
 
@@ -417,112 +637,27 @@ impl PartialSchema {
     /// - CREATE TABLE with columns and primary key (uniques/checks/fks added with ALTER TABLE)
     /// - views / materialized views
     ///
-    /// The output is deterministic (Vecs are iterated in order).
+    /// The output is deterministic (Vecs are iterated in order). Every
+    /// identifier goes through [`quote_ident`]. Enum, composite and domain
+    /// types without a schema are created in, and qualified with,
+    /// `schema_name`, as tables are.
     pub fn to_ddl(&self, schema_name: &str) -> String {
-        fn esc(s: &str) -> String {
-            s.replace('\'', "''")
-        }
-
-        fn render_data_type(dt: &DataType) -> String {
-            match dt {
-                DataType::Boolean => "boolean".into(),
-                DataType::SmallInt => "smallint".into(),
-                DataType::Integer => "integer".into(),
-                DataType::BigInt => "bigint".into(),
-                DataType::Real => "real".into(),
-                DataType::DoublePrecision => "double precision".into(),
-                DataType::Numeric { precision, scale } => match (precision, scale) {
-                    (Some(p), Some(s)) => format!("numeric({},{})", p, s),
-                    (Some(p), None) => format!("numeric({})", p),
-                    _ => "numeric".into(),
-                },
-                DataType::Serial => "serial".into(),
-                DataType::BigSerial => "bigserial".into(),
-                DataType::Text => "text".into(),
-                DataType::Varchar(opt) => match opt {
-                    Some(n) => format!("varchar({})", n),
-                    None => "varchar".into(),
-                },
-                DataType::Char(opt) => match opt {
-                    Some(n) => format!("char({})", n),
-                    None => "char".into(),
-                },
-                DataType::Bytea => "bytea".into(),
-                DataType::Timestamp { with_time_zone } => {
-                    if *with_time_zone {
-                        "timestamp with time zone".into()
-                    } else {
-                        "timestamp without time zone".into()
-                    }
-                }
-                DataType::Date => "date".into(),
-                DataType::Time { with_time_zone } => {
-                    if *with_time_zone {
-                        "time with time zone".into()
-                    } else {
-                        "time without time zone".into()
-                    }
-                }
-                DataType::Interval => "interval".into(),
-                DataType::Json => "json".into(),
-                DataType::Jsonb => "jsonb".into(),
-                DataType::Uuid => "uuid".into(),
-                DataType::Inet => "inet".into(),
-                DataType::MacAddr => "macaddr".into(),
-                DataType::TsVector => "tsvector".into(),
-                DataType::Array(inner) => format!("{}[]", render_data_type(inner)),
-                DataType::Enum { schema, name } => match schema {
-                    Some(s) => format!("{}.{}", s, name),
-                    None => name.clone(),
-                },
-                DataType::Composite { schema, name } => match schema {
-                    Some(s) => format!("{}.{}", s, name),
-                    None => name.clone(),
-                },
-                DataType::Domain { schema, name } => match schema {
-                    Some(s) => format!("{}.{}", s, name),
-                    None => name.clone(),
-                },
-                DataType::Custom { schema, name } => match schema {
-                    Some(s) => format!("{}.{}", s, name),
-                    None => name.clone(),
-                },
-                DataType::Any => "any".into(),
-                DataType::Unknown => "unknown".into(),
-            }
-        }
-
         let mut stmts: Vec<String> = Vec::new();
 
-        stmts.push(format!("CREATE SCHEMA IF NOT EXISTS {};", schema_name));
+        stmts.push(format!(
+            "CREATE SCHEMA IF NOT EXISTS {};",
+            quote_ident(schema_name)
+        ));
 
         // -- Pass 1: Types & Sequences --
         // Enums
         for e in &self.enums {
-            let vars = e
-                .variants
-                .iter()
-                .map(|v| format!("'{}'", esc(v)))
-                .collect::<Vec<_>>()
-                .join(", ");
-            let qname = if let Some(s) = &e.schema {
-                format!("{}.{}", s, e.name)
-            } else {
-                e.name.clone()
-            };
-            stmts.push(format!("CREATE TYPE {} AS ENUM ({});", qname, vars));
-            if let Some(c) = &e.comment {
-                stmts.push(format!("COMMENT ON TYPE {} IS '{}';", qname, esc(c)));
-            }
+            stmts.extend(render_enum(e, schema_name));
         }
 
         // Sequences
         for seq in &self.sequences {
-            let q = if let Some(s) = &seq.schema {
-                format!("{}.{}", s, seq.name)
-            } else {
-                seq.name.clone()
-            };
+            let q = quote_qualified(seq.schema.as_deref(), &seq.name);
             let mut parts: Vec<String> = vec![format!("CREATE SEQUENCE {}", q)];
             if let Some(start) = seq.start {
                 parts.push(format!("START WITH {}", start));
@@ -553,15 +688,17 @@ impl PartialSchema {
 
         // Composite types
         for ct in &self.composite_types {
-            let q = if let Some(s) = &ct.schema {
-                format!("{}.{}", s, ct.name)
-            } else {
-                ct.name.clone()
-            };
+            let q = quote_type(ct.schema.as_deref(), &ct.name, schema_name);
             let fields = ct
                 .fields
                 .iter()
-                .map(|f| format!("{} {}", f.name, render_data_type(&f.data_type)))
+                .map(|f| {
+                    format!(
+                        "{} {}",
+                        quote_ident(&f.name),
+                        render_data_type(&qualify_type(&f.data_type, schema_name))
+                    )
+                })
                 .collect::<Vec<_>>()
                 .join(", ");
             stmts.push(format!("CREATE TYPE {} AS ({});", q, fields));
@@ -572,15 +709,11 @@ impl PartialSchema {
 
         // Domains
         for dom in &self.domains {
-            let q = if let Some(s) = &dom.schema {
-                format!("{}.{}", s, dom.name)
-            } else {
-                dom.name.clone()
-            };
+            let q = quote_type(dom.schema.as_deref(), &dom.name, schema_name);
             let mut line = format!(
                 "CREATE DOMAIN {} AS {}",
                 q,
-                render_data_type(&dom.base_type)
+                render_data_type(&qualify_type(&dom.base_type, schema_name))
             );
             if dom.not_null {
                 line.push_str(" NOT NULL");
@@ -588,6 +721,12 @@ impl PartialSchema {
             if let Some(d) = &dom.default {
                 line.push_str(&format!(" DEFAULT {}", d));
             }
+            for ck in &dom.constraints {
+                if let Some(name) = &ck.name {
+                    line.push_str(&format!(" CONSTRAINT {}", quote_ident(name)));
+                }
+                line.push_str(&format!(" CHECK ({})", ck.expression));
+            }
             line.push(';');
             stmts.push(line);
             if let Some(c) = &dom.comment {
@@ -597,81 +736,12 @@ impl PartialSchema {
 
         // -- Pass 2: Base Tables (No Indicies, No FKs) --
         for t in &self.tables {
-            let q = format!("{}.{}", schema_name, t.name);
-            let cols = t
-                .columns
-                .iter()
-                .map(|c| {
-                    let mut col = format!("{} {}", c.name, render_data_type(&c.data_type));
-                    if let Some(coll) = &c.collation {
-                        col.push_str(&format!(" COLLATE {}", coll));
-                    }
-                    if c.is_identity {
-                        let r#gen = match c.identity_generation {
-                            Some(IdentityGeneration::Always) => "ALWAYS",
-                            Some(IdentityGeneration::ByDefault) => "BY DEFAULT",
-                            None => "BY DEFAULT",
-                        };
-                        col.push_str(&format!(" GENERATED {} AS IDENTITY", r#gen));
-                    } else if c.is_generated {
-                        if let Some(expr) = &c.generation_expression {
-                            col.push_str(&format!(" GENERATED ALWAYS AS ({}) STORED", expr));
-                        }
-                    } else if let Some(def) = &c.default {
-                        col.push_str(&format!(" DEFAULT {}", def));
-                    }
-                    if !c.nullable {
-                        col.push_str(" NOT NULL");
-                    }
-                    col
-                })
-                .collect::<Vec<_>>()
-                .join(", ");
-            let mut table_stmt = format!("CREATE TABLE {} ({})", q, cols);
-            if let Some(pk) = &t.primary_key {
-                let cols = pk.columns.join(", ");
-                table_stmt.push_str(&format!(", PRIMARY KEY ({})", cols));
-            }
-            table_stmt.push(';');
-            stmts.push(table_stmt);
-
-            // Unique constraints (Safe to add now as they usually refer to local cols)
-            if !t.uniques.is_empty() {
-                for u in &t.uniques {
-                    let name = u
-                        .name
-                        .as_deref()
-                        .map(|x| Cow::Borrowed(x))
-                        .unwrap_or_else(|| {
-                            format!("{}_{}_key", t.name, u.columns.join("_")).into()
-                        });
-                    stmts.push(format!(
-                        "ALTER TABLE {} ADD CONSTRAINT {} UNIQUE ({});",
-                        q,
-                        name,
-                        u.columns.join(", ")
-                    ));
-                }
-            }
-
-            // Check constraints (Safe to add now)
-            if !t.checks.is_empty() {
-                for ck in &t.checks {
-                    if let Some(nm) = &ck.name {
-                        stmts.push(format!(
-                            "ALTER TABLE {} ADD CONSTRAINT {} CHECK ({});",
-                            q, nm, ck.expression
-                        ));
-                    } else {
-                        stmts.push(format!("ALTER TABLE {} ADD CHECK ({});", q, ck.expression));
-                    }
-                }
-            }
+            stmts.extend(render_table(t, schema_name));
         }
 
         // -- Pass 3: Views --
         for v in &self.views {
-            let q = format!("{}.{}", schema_name, v.name);
+            let q = quote_qualified(Some(schema_name), &v.name);
             let stmt = if v.materialized {
                 format!("CREATE MATERIALIZED VIEW {} AS\n{};", q, v.definition)
             } else {
@@ -683,7 +753,7 @@ impl PartialSchema {
             }
         }
         for mv in &self.materialized_views {
-            let q = format!("{}.{}", schema_name, mv.name);
+            let q = quote_qualified(Some(schema_name), &mv.name);
             stmts.push(format!(
                 "CREATE MATERIALIZED VIEW {} AS\n{};",
                 q, mv.definition
@@ -699,153 +769,465 @@ impl PartialSchema {
 
         // -- Pass 4: Indexes --
         for t in &self.tables {
-            let qtable = format!("{}.{}", schema_name, t.name);
             for idx in &t.indexes {
-                let idx_name = if idx.name.is_empty() {
-                    // Generate a name if empty
-                    format!(
-                        "{}_idx_{}",
-                        t.name,
-                        idx.columns
-                            .iter()
-                            .map(|c| match &c.expr {
-                                IndexExpr::Column(n) => n.as_str(),
-                                _ => "expr",
-                            })
-                            .collect::<Vec<_>>()
-                            .join("_")
-                    )
-                } else {
-                    idx.name.clone()
-                };
-
                 // If it's a primary key index, we likely already handled it via PRIMARY KEY constraint.
                 // But if explicitly defined in indexes, maybe we want it explicit?
                 // Usually `is_primary` implies it backs the PK.
                 if idx.is_primary {
                     continue;
                 }
+                stmts.push(render_index(idx, &t.name, schema_name));
+            }
+        }
 
-                let method = idx.method.as_deref().unwrap_or("btree");
-                let unique = if idx.unique { "UNIQUE " } else { "" };
-                let concurrent = if idx.concurrently {
-                    "CONCURRENTLY "
-                } else {
-                    ""
-                };
-
-                let mut cols_str = Vec::new();
-                for col in &idx.columns {
-                    let expr = match &col.expr {
-                        IndexExpr::Column(c) => c.clone(),
-                        IndexExpr::Expression(e) => format!("({})", e),
-                    };
-                    let mut def = expr;
-                    if let Some(coll) = &col.collate {
-                        def.push_str(&format!(" COLLATE {}", coll));
-                    }
-                    if let Some(op) = &col.opclass {
-                        def.push_str(&format!(" {}", op));
-                    }
-                    if let Some(order) = &col.order {
-                        match order {
-                            SortOrder::Asc => def.push_str(" ASC"),
-                            SortOrder::Desc => def.push_str(" DESC"),
-                        }
-                    }
-                    if let Some(nulls) = &col.nulls_order {
-                        match nulls {
-                            NullsOrder::First => def.push_str(" NULLS FIRST"),
-                            NullsOrder::Last => def.push_str(" NULLS LAST"),
-                        }
-                    }
-                    cols_str.push(def);
-                }
+        // -- Pass 5: Foreign Keys --
+        for t in &self.tables {
+            for fk in &t.foreign_keys {
+                stmts.push(render_foreign_key(fk, &t.name, schema_name));
+            }
+        }
 
-                let mut stmt = format!(
-                    "CREATE {}INDEX {}{} ON {} USING {} ({})",
-                    unique,
-                    concurrent,
-                    idx_name,
-                    qtable,
-                    method,
-                    cols_str.join(", ")
-                );
-
-                if !idx.include.is_empty() {
-                    stmt.push_str(&format!(" INCLUDE ({})", idx.include.join(", ")));
-                }
+        stmts.join("\n")
+    }
+}
 
-                if let Some(pred) = &idx.predicate {
-                    stmt.push_str(&format!(" WHERE {}", pred));
-                }
+fn esc(s: &str) -> String {
+    s.replace('\'', "''")
+}
 
-                if let Some(ts) = &idx.tablespace {
-                    stmt.push_str(&format!(" TABLESPACE {}", ts));
-                }
+fn cols(names: &[String]) -> String {
+    names
+        .iter()
+        .map(|n| quote_ident(n))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
 
-                stmt.push(';');
-                stmts.push(stmt);
+fn render_data_type(dt: &DataType) -> String {
+    match dt {
+        DataType::Boolean => "boolean".into(),
+        DataType::SmallInt => "smallint".into(),
+        DataType::Integer => "integer".into(),
+        DataType::BigInt => "bigint".into(),
+        DataType::Real => "real".into(),
+        DataType::DoublePrecision => "double precision".into(),
+        DataType::Numeric { precision, scale } => match (precision, scale) {
+            (Some(p), Some(s)) => format!("numeric({},{})", p, s),
+            (Some(p), None) => format!("numeric({})", p),
+            _ => "numeric".into(),
+        },
+        DataType::Serial => "serial".into(),
+        DataType::BigSerial => "bigserial".into(),
+        DataType::Text => "text".into(),
+        DataType::Varchar(opt) => match opt {
+            Some(n) => format!("varchar({})", n),
+            None => "varchar".into(),
+        },
+        DataType::Char(opt) => match opt {
+            Some(n) => format!("char({})", n),
+            None => "char".into(),
+        },
+        DataType::Bytea => "bytea".into(),
+        DataType::Timestamp { with_time_zone } => {
+            if *with_time_zone {
+                "timestamp with time zone".into()
+            } else {
+                "timestamp without time zone".into()
             }
         }
+        DataType::Date => "date".into(),
+        DataType::Time { with_time_zone } => {
+            if *with_time_zone {
+                "time with time zone".into()
+            } else {
+                "time without time zone".into()
+            }
+        }
+        DataType::Interval => "interval".into(),
+        DataType::Json => "json".into(),
+        DataType::Jsonb => "jsonb".into(),
+        DataType::Uuid => "uuid".into(),
+        DataType::Inet => "inet".into(),
+        DataType::MacAddr => "macaddr".into(),
+        DataType::TsVector => "tsvector".into(),
+        DataType::Array(inner) => format!("{}[]", render_data_type(inner)),
+        DataType::Enum { schema, name }
+        | DataType::Composite { schema, name }
+        | DataType::Domain { schema, name } => quote_qualified_type(schema.as_deref(), name),
+        DataType::Custom { schema, name } => quote_qualified(schema.as_deref(), name),
+        DataType::Any => "any".into(),
+        DataType::Unknown => "unknown".into(),
+    }
+}
 
-        // -- Pass 5: Foreign Keys --
-        for t in &self.tables {
-            let q = format!("{}.{}", schema_name, t.name);
-            if !t.foreign_keys.is_empty() {
-                for fk in &t.foreign_keys {
-                    let name = fk
-                        .name
-                        .as_deref()
-                        .map(|x| Cow::Borrowed(x))
-                        .unwrap_or_else(|| {
-                            format!("{}_{}_fkey", t.name, fk.columns.join("_")).into()
-                        });
-                    let ref_t = fk.referenced_table.to_string();
-                    let cols = fk.columns.join(", ");
-                    let refcols = match &fk.referenced_columns {
-                        Some(v) => format!("({})", v.join(", ")),
-                        None => String::new(),
-                    };
-                    let mut stmt = format!(
-                        "ALTER TABLE {} ADD CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {}{}",
-                        q,
-                        name,
-                        cols,
-                        ref_t,
-                        if refcols.is_empty() {
-                            "".into()
-                        } else {
-                            format!(" {}", refcols)
-                        }
-                    );
-                    if let Some(action) = &fk.on_delete {
-                        let a = match action {
-                            ReferentialAction::NoAction => "NO ACTION",
-                            ReferentialAction::Restrict => "RESTRICT",
-                            ReferentialAction::Cascade => "CASCADE",
-                            ReferentialAction::SetNull => "SET NULL",
-                            ReferentialAction::SetDefault => "SET DEFAULT",
-                        };
-                        stmt.push_str(&format!(" ON DELETE {}", a));
-                    }
-                    if let Some(action) = &fk.on_update {
-                        let a = match action {
-                            ReferentialAction::NoAction => "NO ACTION",
-                            ReferentialAction::Restrict => "RESTRICT",
-                            ReferentialAction::Cascade => "CASCADE",
-                            ReferentialAction::SetNull => "SET NULL",
-                            ReferentialAction::SetDefault => "SET DEFAULT",
-                        };
-                        stmt.push_str(&format!(" ON UPDATE {}", a));
-                    }
-                    stmt.push(';');
-                    stmts.push(stmt);
-                }
+/// The type named by `text`, as written in DDL: the types `render_data_type`
+/// writes, their common aliases, and `[]` for arrays. Other names are custom
+/// types, optionally schema-qualified.
+pub(crate) fn parse_data_type(text: &str) -> Option<DataType> {
+    let text = text.trim().to_lowercase();
+    if let Some(element) = text.strip_suffix("[]") {
+        return Some(DataType::Array(Box::new(parse_data_type(element)?)));
+    }
+    let (name, args) = match text.split_once('(') {
+        Some((name, rest)) => (name.trim(), Some(rest.strip_suffix(')')?)),
+        None => (text.as_str(), None),
+    };
+    let args: Vec<u32> = match args {
+        Some(args) => args
+            .split(',')
+            .map(|n| n.trim().parse().ok())
+            .collect::<Option<_>>()?,
+        None => vec![],
+    };
+    let length = args.first().copied();
+    let words: Vec<&str> = name.split_whitespace().collect();
+    let data_type = match words.as_slice() {
+        ["boolean" | "bool"] => DataType::Boolean,
+        ["smallint" | "int2"] => DataType::SmallInt,
+        ["integer" | "int" | "int4"] => DataType::Integer,
+        ["bigint" | "int8"] => DataType::BigInt,
+        ["real" | "float4"] => DataType::Real,
+        ["double", "precision"] | ["float8"] => DataType::DoublePrecision,
+        ["numeric" | "decimal"] => DataType::Numeric {
+            precision: args.first().copied(),
+            scale: args.get(1).copied(),
+        },
+        ["serial"] => DataType::Serial,
+        ["bigserial"] => DataType::BigSerial,
+        ["text"] => DataType::Text,
+        ["varchar"] | ["character", "varying"] => DataType::Varchar(length),
+        ["char" | "character"] => DataType::Char(length),
+        ["bytea"] => DataType::Bytea,
+        ["timestamp"] | ["timestamp", "without", "time", "zone"] => DataType::Timestamp {
+            with_time_zone: false,
+        },
+        ["timestamptz"] | ["timestamp", "with", "time", "zone"] => DataType::Timestamp {
+            with_time_zone: true,
+        },
+        ["date"] => DataType::Date,
+        ["time"] | ["time", "without", "time", "zone"] => DataType::Time {
+            with_time_zone: false,
+        },
+        ["timetz"] | ["time", "with", "time", "zone"] => DataType::Time {
+            with_time_zone: true,
+        },
+        ["interval"] => DataType::Interval,
+        ["json"] => DataType::Json,
+        ["jsonb"] => DataType::Jsonb,
+        ["uuid"] => DataType::Uuid,
+        ["inet"] => DataType::Inet,
+        ["macaddr"] => DataType::MacAddr,
+        ["tsvector"] => DataType::TsVector,
+        [custom] => {
+            let valid = |part: &str| {
+                !part.is_empty() && part.chars().all(|c| c.is_alphanumeric() || c == '_')
+            };
+            match custom.split_once('.') {
+                Some((schema, name)) if valid(schema) && valid(name) => DataType::Custom {
+                    schema: Some(schema.to_string()),
+                    name: name.to_string(),
+                },
+                None if valid(custom) => DataType::Custom {
+                    schema: None,
+                    name: custom.to_string(),
+                },
+                _ => return None,
             }
         }
+        _ => return None,
+    };
+    let max_args = match data_type {
+        DataType::Numeric { .. } => 2,
+        DataType::Varchar(_) | DataType::Char(_) => 1,
+        _ => 0,
+    };
+    (args.len() <= max_args).then_some(data_type)
+}
 
-        stmts.join("\n")
+/// `data_type` with `schema_name` filled in for enum, composite and domain
+/// types that have no schema, as those are created in the target schema.
+pub(crate) fn qualify_type(data_type: &DataType, schema_name: &str) -> DataType {
+    match data_type {
+        DataType::Enum { schema: None, name } => DataType::Enum {
+            schema: Some(schema_name.into()),
+            name: name.clone(),
+        },
+        DataType::Composite { schema: None, name } => DataType::Composite {
+            schema: Some(schema_name.into()),
+            name: name.clone(),
+        },
+        DataType::Domain { schema: None, name } => DataType::Domain {
+            schema: Some(schema_name.into()),
+            name: name.clone(),
+        },
+        DataType::Array(inner) => DataType::Array(Box::new(qualify_type(inner, schema_name))),
+        other => other.clone(),
+    }
+}
+
+/// The quoted name of a type created in `schema`, or in `schema_name` if it
+/// has none.
+pub(crate) fn quote_type(schema: Option<&str>, name: &str, schema_name: &str) -> String {
+    quote_qualified_type(Some(schema.unwrap_or(schema_name)), name)
+}
+
+fn render_enum(e: &EnumType, schema_name: &str) -> Vec<String> {
+    let mut stmts = Vec::new();
+    let vars = e
+        .variants
+        .iter()
+        .map(|v| format!("'{}'", esc(v)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let qname = quote_type(e.schema.as_deref(), &e.name, schema_name);
+    stmts.push(format!("CREATE TYPE {} AS ENUM ({});", qname, vars));
+    if let Some(c) = &e.comment {
+        stmts.push(format!("COMMENT ON TYPE {} IS '{}';", qname, esc(c)));
+    }
+    stmts
+}
+
+fn render_column(c: &Column, schema_name: &str) -> String {
+    let mut col = format!(
+        "{} {}",
+        quote_ident(&c.name),
+        render_data_type(&qualify_type(&c.data_type, schema_name))
+    );
+    if let Some(coll) = &c.collation {
+        col.push_str(&format!(" COLLATE {}", quote_ident(coll)));
+    }
+    if c.is_identity {
+        let r#gen = match c.identity_generation {
+            Some(IdentityGeneration::Always) => "ALWAYS",
+            Some(IdentityGeneration::ByDefault) => "BY DEFAULT",
+            None => "BY DEFAULT",
+        };
+        col.push_str(&format!(" GENERATED {} AS IDENTITY", r#gen));
+    } else if c.is_generated {
+        if let Some(expr) = &c.generation_expression {
+            col.push_str(&format!(" GENERATED ALWAYS AS ({}) STORED", expr));
+        }
+    } else if let Some(def) = &c.default {
+        col.push_str(&format!(" DEFAULT {}", def));
+    }
+    if !c.nullable {
+        col.push_str(" NOT NULL");
+    }
+    col
+}
+
+/// CREATE TABLE plus the unique and check constraints that only touch local columns.
+fn render_table(t: &Table, schema_name: &str) -> Vec<String> {
+    let mut stmts = Vec::new();
+    let q = quote_qualified(Some(schema_name), &t.name);
+    let columns = t
+        .columns
+        .iter()
+        .map(|c| render_column(c, schema_name))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let primary_key = match &t.primary_key {
+        Some(pk) => match &pk.name {
+            Some(name) => format!(
+                ", CONSTRAINT {} PRIMARY KEY ({})",
+                quote_ident(name),
+                cols(&pk.columns)
+            ),
+            None => format!(", PRIMARY KEY ({})", cols(&pk.columns)),
+        },
+        None => String::new(),
+    };
+    stmts.push(format!("CREATE TABLE {} ({}{});", q, columns, primary_key));
+    stmts.extend(render_comments(t, schema_name));
+
+    for u in &t.uniques {
+        stmts.push(render_unique(u, &t.name, schema_name));
+    }
+    for (ck, name) in t.checks.iter().zip(t.check_names()) {
+        stmts.push(render_check(ck, &name, &t.name, schema_name));
+    }
+    if !t.rows.is_empty() {
+        let names: Vec<_> = t.columns.iter().map(|c| c.name.clone()).collect();
+        let rows = t
+            .rows
+            .iter()
+            .map(|row| format!("({})", row.join(", ")))
+            .collect::<Vec<_>>()
+            .join(", ");
+        stmts.push(format!(
+            "INSERT INTO {} ({}) VALUES {};",
+            q,
+            cols(&names),
+            rows
+        ));
+    }
+    stmts
+}
+
+/// `COMMENT ON` statements for `t` and those of its columns that have one.
+pub(crate) fn render_comments(t: &Table, schema_name: &str) -> Vec<String> {
+    let table_comment = t
+        .comment
+        .as_deref()
+        .map(|c| render_table_comment(&t.name, Some(c), schema_name));
+    let column_comments = t.columns.iter().filter_map(|c| {
+        c.comment
+            .as_deref()
+            .map(|comment| render_column_comment(&t.name, &c.name, Some(comment), schema_name))
+    });
+    table_comment.into_iter().chain(column_comments).collect()
+}
+
+/// `COMMENT ON TABLE`; `None` removes the comment.
+pub(crate) fn render_table_comment(
+    table: &str,
+    comment: Option<&str>,
+    schema_name: &str,
+) -> String {
+    format!(
+        "COMMENT ON TABLE {} IS {};",
+        quote_qualified(Some(schema_name), table),
+        comment_literal(comment)
+    )
+}
+
+/// `COMMENT ON COLUMN`; `None` removes the comment.
+pub(crate) fn render_column_comment(
+    table: &str,
+    column: &str,
+    comment: Option<&str>,
+    schema_name: &str,
+) -> String {
+    format!(
+        "COMMENT ON COLUMN {}.{} IS {};",
+        quote_qualified(Some(schema_name), table),
+        quote_ident(column),
+        comment_literal(comment)
+    )
+}
+
+fn comment_literal(comment: Option<&str>) -> String {
+    match comment {
+        Some(c) => format!("'{}'", esc(c)),
+        None => "NULL".into(),
+    }
+}
+
+fn render_unique(u: &UniqueConstraint, table: &str, schema_name: &str) -> String {
+    format!(
+        "ALTER TABLE {} ADD CONSTRAINT {} UNIQUE ({});",
+        quote_qualified(Some(schema_name), table),
+        quote_ident(&u.effective_name(table)),
+        cols(&u.columns)
+    )
+}
+
+fn render_check(ck: &CheckConstraint, name: &str, table: &str, schema_name: &str) -> String {
+    format!(
+        "ALTER TABLE {} ADD CONSTRAINT {} CHECK ({});",
+        quote_qualified(Some(schema_name), table),
+        quote_ident(name),
+        ck.expression
+    )
+}
+
+fn render_index(idx: &Index, table: &str, schema_name: &str) -> String {
+    let method = idx.method.as_deref().unwrap_or("btree");
+    let unique = if idx.unique { "UNIQUE " } else { "" };
+    let concurrent = if idx.concurrently {
+        "CONCURRENTLY "
+    } else {
+        ""
+    };
+
+    let mut cols_str = Vec::new();
+    for col in &idx.columns {
+        let expr = match &col.expr {
+            IndexExpr::Column(c) => quote_ident(c).into_owned(),
+            IndexExpr::Expression(e) => format!("({})", e),
+        };
+        let mut def = expr;
+        if let Some(coll) = &col.collate {
+            def.push_str(&format!(" COLLATE {}", quote_ident(coll)));
+        }
+        if let Some(op) = &col.opclass {
+            def.push_str(&format!(" {}", op));
+        }
+        if let Some(order) = &col.order {
+            match order {
+                SortOrder::Asc => def.push_str(" ASC"),
+                SortOrder::Desc => def.push_str(" DESC"),
+            }
+        }
+        if let Some(nulls) = &col.nulls_order {
+            match nulls {
+                NullsOrder::First => def.push_str(" NULLS FIRST"),
+                NullsOrder::Last => def.push_str(" NULLS LAST"),
+            }
+        }
+        cols_str.push(def);
+    }
+
+    let mut stmt = format!(
+        "CREATE {}INDEX {}{} ON {} USING {} ({})",
+        unique,
+        concurrent,
+        quote_ident(&idx.effective_name(table)),
+        quote_qualified(Some(schema_name), table),
+        method,
+        cols_str.join(", ")
+    );
+
+    if !idx.include.is_empty() {
+        stmt.push_str(&format!(" INCLUDE ({})", cols(&idx.include)));
+    }
+
+    if let Some(pred) = &idx.predicate {
+        stmt.push_str(&format!(" WHERE {}", pred));
+    }
+
+    if let Some(ts) = &idx.tablespace {
+        stmt.push_str(&format!(" TABLESPACE {}", quote_ident(ts)));
+    }
+
+    stmt.push(';');
+    stmt
+}
+
+fn render_foreign_key(fk: &ForeignKey, table: &str, schema_name: &str) -> String {
+    let ref_t = quote_qualified(
+        Some(fk.referenced_table.schema.as_deref().unwrap_or(schema_name)),
+        &fk.referenced_table.name,
+    );
+    let refcols = match &fk.referenced_columns {
+        Some(v) => format!(" ({})", cols(v)),
+        None => String::new(),
+    };
+    let mut stmt = format!(
+        "ALTER TABLE {} ADD CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {}{}",
+        quote_qualified(Some(schema_name), table),
+        quote_ident(&fk.effective_name(table)),
+        cols(&fk.columns),
+        ref_t,
+        refcols
+    );
+    if let Some(action) = &fk.on_delete {
+        stmt.push_str(&format!(" ON DELETE {}", render_action(action)));
+    }
+    if let Some(action) = &fk.on_update {
+        stmt.push_str(&format!(" ON UPDATE {}", render_action(action)));
+    }
+    stmt.push(';');
+    stmt
+}
+
+fn render_action(action: &ReferentialAction) -> &'static str {
+    match action {
+        ReferentialAction::NoAction => "NO ACTION",
+        ReferentialAction::Restrict => "RESTRICT",
+        ReferentialAction::Cascade => "CASCADE",
+        ReferentialAction::SetNull => "SET NULL",
+        ReferentialAction::SetDefault => "SET DEFAULT",
     }
 }
 
@@ -922,6 +1304,7 @@ mod tests {
             },
             comment: Some("Application users".to_string()),
             owned_sequences: vec![],
+            rows: vec![],
         };
 
         schema.tables.push(table);
@@ -0,0 +1,387 @@
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use facet::{Facet, Opaque};
+use facet_reflect::{Partial, Peek};
+use sea_query::Value;
+
+use crate::{DataType, render_data_type};
+
+/// Writes a value of a mapped type as a column value.
+pub type EncodeFn = fn(Peek<'_, '_>) -> Result<Value, String>;
+
+/// Sets a value of a mapped type from a column value, which is never `NULL`.
+pub type DecodeFn = fn(Partial<'static, false>, Value) -> Result<Partial<'static, false>, String>;
+
+/// How values of a type the converter has no mapping for are stored: the
+/// column type, and how they are written to and read from rows.
+///
+/// ```ignore
+/// let options = ConversionOptions {
+///     types: TypeRegistry::default().register(TypeMapping::text::<Isbn>(DataType::Text)),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone)]
+pub struct TypeMapping {
+    type_id: TypeId,
+    type_name: String,
+    pub data_type: DataType,
+    pub encode: EncodeFn,
+    pub decode: DecodeFn,
+}
+
+impl TypeMapping {
+    /// Maps `T` to `data_type`, with its own encoding and decoding.
+    pub fn new<T: Facet<'static>>(data_type: DataType, encode: EncodeFn, decode: DecodeFn) -> Self {
+        TypeMapping {
+            type_id: T::SHAPE.id.get(),
+            type_name: T::SHAPE.to_string(),
+            data_type,
+            encode,
+            decode,
+        }
+    }
+
+    /// Maps `T` to `data_type`, writing values as their `Display` text and
+    /// parsing them back with `FromStr`. [`RowEncoder::insert`] casts the
+    /// text to the column type.
+    ///
+    /// [`RowEncoder::insert`]: crate::RowEncoder::insert
+    pub fn text<T>(data_type: DataType) -> Self
+    where
+        T: for<'f> Facet<'f> + Display + FromStr,
+        T::Err: Display,
+    {
+        TypeMapping::new::<T>(data_type, encode_text::<T>, decode_text::<T>)
+    }
+
+    /// Like [`TypeMapping::text`], for fields of a type without a `Facet`
+    /// implementation, marked `#[facet(opaque)]`.
+    pub fn opaque_text<T>(data_type: DataType) -> Self
+    where
+        T: Display + FromStr + 'static,
+        T::Err: Display,
+    {
+        TypeMapping::new::<Opaque<T>>(data_type, encode_opaque_text::<T>, decode_opaque_text::<T>)
+    }
+}
+
+impl fmt::Debug for TypeMapping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TypeMapping")
+            .field("type", &self.type_name)
+            .field("data_type", &render_data_type(&self.data_type))
+            .finish_non_exhaustive()
+    }
+}
+
+/// The [`TypeMapping`]s consulted for types the converter does not know,
+/// before reporting them as [`ConversionError::UnsupportedType`]. Set one
+/// with [`ConversionOptions::types`].
+///
+/// The default registry maps `#[facet(opaque)]` `serde_json::Value` fields
+/// to `jsonb`, and the types of the `net`, `uuid`, `chrono`, `time`,
+/// `rust_decimal` and `bytes` features.
+///
+/// [`ConversionError::UnsupportedType`]: crate::ConversionError::UnsupportedType
+/// [`ConversionOptions::types`]: crate::ConversionOptions::types
+#[derive(Clone)]
+pub struct TypeRegistry {
+    mappings: Arc<HashMap<TypeId, TypeMapping>>,
+}
+
+impl TypeRegistry {
+    /// A registry without any mappings, not even the built-in ones.
+    pub fn empty() -> Self {
+        TypeRegistry {
+            mappings: Arc::default(),
+        }
+    }
+
+    /// Add `mapping`, replacing any earlier one for the same type.
+    pub fn register(mut self, mapping: TypeMapping) -> Self {
+        Arc::make_mut(&mut self.mappings).insert(mapping.type_id, mapping);
+        self
+    }
+
+    pub(crate) fn get(&self, shape: &facet::Shape) -> Option<&TypeMapping> {
+        self.mappings.get(&shape.id.get())
+    }
+}
+
+impl Default for TypeRegistry {
+    fn default() -> Self {
+        builtin().into_iter().fold(Self::empty(), Self::register)
+    }
+}
+
+impl fmt::Debug for TypeRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.mappings.values()).finish()
+    }
+}
+
+fn builtin() -> Vec<TypeMapping> {
+    #[allow(unused_mut)]
+    let mut mappings = vec![TypeMapping::new::<Opaque<serde_json::Value>>(
+        DataType::Jsonb,
+        encode_json,
+        decode_json,
+    )];
+    #[cfg(feature = "net")]
+    mappings.extend([
+        TypeMapping::text::<std::net::IpAddr>(DataType::Inet),
+        TypeMapping::text::<std::net::Ipv4Addr>(DataType::Inet),
+        TypeMapping::text::<std::net::Ipv6Addr>(DataType::Inet),
+    ]);
+    #[cfg(feature = "uuid")]
+    mappings.push(TypeMapping::text::<uuid::Uuid>(DataType::Uuid));
+    #[cfg(feature = "chrono")]
+    mappings.extend(chrono_types::mappings());
+    #[cfg(feature = "time")]
+    mappings.extend(time_types::mappings());
+    #[cfg(feature = "bytes")]
+    mappings.extend(bytes_types::mappings());
+    #[cfg(feature = "rust_decimal")]
+    mappings.push(TypeMapping::opaque_text::<rust_decimal::Decimal>(
+        DataType::Numeric {
+            precision: None,
+            scale: None,
+        },
+    ));
+    mappings
+}
+
+fn encode_text<T: for<'f> Facet<'f> + Display>(peek: Peek<'_, '_>) -> Result<Value, String> {
+    let value = peek.get::<T>().map_err(|e| e.to_string())?;
+    Ok(Value::String(Some(Box::new(value.to_string()))))
+}
+
+fn decode_text<T>(
+    wip: Partial<'static, false>,
+    value: Value,
+) -> Result<Partial<'static, false>, String>
+where
+    T: Facet<'static> + FromStr,
+    T::Err: Display,
+{
+    let value = text(value)?.parse::<T>().map_err(|e| e.to_string())?;
+    wip.set(value).map_err(|e| e.to_string())
+}
+
+fn encode_opaque_text<T: Display + 'static>(peek: Peek<'_, '_>) -> Result<Value, String> {
+    let Opaque(value) = peek.get::<Opaque<T>>().map_err(|e| e.to_string())?;
+    Ok(Value::String(Some(Box::new(value.to_string()))))
+}
+
+fn decode_opaque_text<T>(
+    wip: Partial<'static, false>,
+    value: Value,
+) -> Result<Partial<'static, false>, String>
+where
+    T: FromStr + 'static,
+    T::Err: Display,
+{
+    let value = text(value)?.parse::<T>().map_err(|e| e.to_string())?;
+    wip.set(Opaque(value)).map_err(|e| e.to_string())
+}
+
+fn encode_json(peek: Peek<'_, '_>) -> Result<Value, String> {
+    let Opaque(json) = peek
+        .get::<Opaque<serde_json::Value>>()
+        .map_err(|e| e.to_string())?;
+    Ok(Value::Json(Some(Box::new(json.clone()))))
+}
+
+fn decode_json(
+    wip: Partial<'static, false>,
+    value: Value,
+) -> Result<Partial<'static, false>, String> {
+    let json = match value {
+        Value::Json(Some(json)) => *json,
+        // Drivers without a JSON type hand the document over as text
+        value => serde_json::from_str(&text(value)?).map_err(|e| e.to_string())?,
+    };
+    wip.set(Opaque(json)).map_err(|e| e.to_string())
+}
+
+/// The text of a value sent or returned as a string.
+fn text(value: Value) -> Result<String, String> {
+    match value {
+        Value::String(Some(text)) => Ok(*text),
+        other => Err(format!("expected text, found {:?}", other)),
+    }
+}
+
+/// An RFC 3339 timestamp from PostgreSQL's text form, e.g.
+/// `2024-05-01 12:00:00+02` to `2024-05-01T12:00:00+02:00`.
+#[cfg(any(feature = "chrono", feature = "time"))]
+fn rfc3339(text: &str) -> String {
+    let mut text = text.replacen(' ', "T", 1);
+    let offset = text.rfind(['+', '-']).filter(|&i| i > 10);
+    if offset.is_some_and(|i| text.len() - i == 3) {
+        text.push_str(":00");
+    }
+    text
+}
+
+#[cfg(feature = "chrono")]
+mod chrono_types {
+    use chrono::{
+        DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat, Utc,
+    };
+
+    use super::*;
+
+    pub(super) fn mappings() -> Vec<TypeMapping> {
+        let timestamptz = DataType::Timestamp {
+            with_time_zone: true,
+        };
+        vec![
+            TypeMapping::new::<DateTime<Utc>>(
+                timestamptz.clone(),
+                encode_datetime::<Utc>,
+                |wip, value| {
+                    wip.set(parse_datetime(value)?.to_utc())
+                        .map_err(|e| e.to_string())
+                },
+            ),
+            TypeMapping::new::<DateTime<FixedOffset>>(
+                timestamptz.clone(),
+                encode_datetime::<FixedOffset>,
+                |wip, value| wip.set(parse_datetime(value)?).map_err(|e| e.to_string()),
+            ),
+            TypeMapping::new::<DateTime<Local>>(
+                timestamptz,
+                encode_datetime::<Local>,
+                |wip, value| {
+                    wip.set(parse_datetime(value)?.with_timezone(&Local))
+                        .map_err(|e| e.to_string())
+                },
+            ),
+            TypeMapping::new::<NaiveDateTime>(
+                DataType::Timestamp {
+                    with_time_zone: false,
+                },
+                |peek| {
+                    let value = peek.get::<NaiveDateTime>().map_err(|e| e.to_string())?;
+                    let text = value.format("%Y-%m-%dT%H:%M:%S%.f").to_string();
+                    Ok(Value::String(Some(Box::new(text))))
+                },
+                |wip, value| {
+                    let text = text(value)?.replacen(' ', "T", 1);
+                    let value = NaiveDateTime::parse_from_str(&text, "%Y-%m-%dT%H:%M:%S%.f")
+                        .map_err(|e| e.to_string())?;
+                    wip.set(value).map_err(|e| e.to_string())
+                },
+            ),
+            TypeMapping::text::<NaiveDate>(DataType::Date),
+            TypeMapping::text::<NaiveTime>(DataType::Time {
+                with_time_zone: false,
+            }),
+        ]
+    }
+
+    fn encode_datetime<Tz>(peek: Peek<'_, '_>) -> Result<Value, String>
+    where
+        Tz: chrono::TimeZone,
+        Tz::Offset: Display,
+        DateTime<Tz>: for<'f> Facet<'f>,
+    {
+        let value = peek.get::<DateTime<Tz>>().map_err(|e| e.to_string())?;
+        let text = value.to_rfc3339_opts(SecondsFormat::AutoSi, false);
+        Ok(Value::String(Some(Box::new(text))))
+    }
+
+    fn parse_datetime(value: Value) -> Result<DateTime<FixedOffset>, String> {
+        DateTime::parse_from_rfc3339(&rfc3339(&text(value)?)).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(feature = "time")]
+mod time_types {
+    use time::format_description::well_known::Rfc3339;
+    use time::{OffsetDateTime, UtcDateTime};
+
+    use super::*;
+
+    pub(super) fn mappings() -> Vec<TypeMapping> {
+        let timestamptz = DataType::Timestamp {
+            with_time_zone: true,
+        };
+        vec![
+            TypeMapping::new::<OffsetDateTime>(
+                timestamptz.clone(),
+                |peek| {
+                    let value = peek.get::<OffsetDateTime>().map_err(|e| e.to_string())?;
+                    format(*value)
+                },
+                |wip, value| wip.set(parse(value)?).map_err(|e| e.to_string()),
+            ),
+            TypeMapping::new::<UtcDateTime>(
+                timestamptz,
+                |peek| {
+                    let value = peek.get::<UtcDateTime>().map_err(|e| e.to_string())?;
+                    format(value.to_offset(time::UtcOffset::UTC))
+                },
+                |wip, value| wip.set(parse(value)?.to_utc()).map_err(|e| e.to_string()),
+            ),
+        ]
+    }
+
+    fn format(value: OffsetDateTime) -> Result<Value, String> {
+        let text = value.format(&Rfc3339).map_err(|e| e.to_string())?;
+        Ok(Value::String(Some(Box::new(text))))
+    }
+
+    fn parse(value: Value) -> Result<OffsetDateTime, String> {
+        OffsetDateTime::parse(&rfc3339(&text(value)?), &Rfc3339).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(feature = "bytes")]
+mod bytes_types {
+    use bytes::{Bytes, BytesMut};
+
+    use super::*;
+    use crate::decode::bytea_from_text;
+
+    pub(super) fn mappings() -> Vec<TypeMapping> {
+        vec![
+            TypeMapping::new::<Bytes>(
+                DataType::Bytea,
+                |peek| encode(peek.get::<Bytes>().map_err(|e| e.to_string())?),
+                |wip, value| {
+                    wip.set(Bytes::from(decode(value)?))
+                        .map_err(|e| e.to_string())
+                },
+            ),
+            TypeMapping::new::<BytesMut>(
+                DataType::Bytea,
+                |peek| encode(peek.get::<BytesMut>().map_err(|e| e.to_string())?),
+                |wip, value| {
+                    let bytes = BytesMut::from(&decode(value)?[..]);
+                    wip.set(bytes).map_err(|e| e.to_string())
+                },
+            ),
+        ]
+    }
+
+    fn encode(bytes: &[u8]) -> Result<Value, String> {
+        Ok(Value::Bytes(Some(Box::new(bytes.to_vec()))))
+    }
+
+    fn decode(value: Value) -> Result<Vec<u8>, String> {
+        match value {
+            Value::Bytes(Some(bytes)) => Ok(*bytes),
+            value => {
+                let text = text(value)?;
+                bytea_from_text(&text).ok_or_else(|| format!("'{}' is not a bytea", text))
+            }
+        }
+    }
+}
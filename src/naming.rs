@@ -0,0 +1,165 @@
+use std::fmt;
+
+/// Derives SQL names from Rust names. Set one with
+/// [`ConversionOptions::naming`](crate::ConversionOptions::naming).
+///
+/// Names given explicitly (`psql::table`, `psql::column`, and facet's
+/// `rename` and `rename_all`) are used as they are. Constraint and index
+/// names are generated from the table and column names this produces.
+pub trait NamingStrategy: fmt::Debug + Send + Sync {
+    /// Table name for a struct or enum, from its type identifier.
+    fn table_name(&self, type_name: &str) -> String;
+
+    /// Column name for a named field.
+    fn column_name(&self, field_name: &str) -> String {
+        field_name.to_string()
+    }
+
+    /// Name of an enum, composite or domain type, from its type identifier.
+    fn type_name(&self, type_name: &str) -> String {
+        type_name.to_lowercase()
+    }
+
+    /// Name of an enum variant: its label in a PostgreSQL enum, and its part
+    /// of table and column names. Variant tables are `<table>_<variant>`,
+    /// and their keys `<variant>_id`.
+    fn variant_name(&self, variant_name: &str) -> String {
+        variant_name.to_lowercase()
+    }
+}
+
+/// Lowercases type identifiers (`UserProfile` → `userprofile`) and keeps
+/// field names as they are. The default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Lowercase;
+
+impl NamingStrategy for Lowercase {
+    fn table_name(&self, type_name: &str) -> String {
+        type_name.to_lowercase()
+    }
+}
+
+/// Converts type, field and variant names to snake_case
+/// (`UserProfile` → `user_profile`).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SnakeCase;
+
+impl NamingStrategy for SnakeCase {
+    fn table_name(&self, type_name: &str) -> String {
+        snake_case(type_name)
+    }
+
+    fn column_name(&self, field_name: &str) -> String {
+        snake_case(field_name)
+    }
+
+    fn type_name(&self, type_name: &str) -> String {
+        snake_case(type_name)
+    }
+
+    fn variant_name(&self, variant_name: &str) -> String {
+        snake_case(variant_name)
+    }
+}
+
+/// Pluralizes the table names of another strategy (`user_profile` →
+/// `user_profiles`).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Plural<S>(pub S);
+
+impl<S: NamingStrategy> NamingStrategy for Plural<S> {
+    fn table_name(&self, type_name: &str) -> String {
+        plural(&self.0.table_name(type_name))
+    }
+
+    fn column_name(&self, field_name: &str) -> String {
+        self.0.column_name(field_name)
+    }
+
+    fn type_name(&self, type_name: &str) -> String {
+        self.0.type_name(type_name)
+    }
+
+    fn variant_name(&self, variant_name: &str) -> String {
+        self.0.variant_name(variant_name)
+    }
+}
+
+/// Adds a prefix and a suffix to the table names of another strategy, e.g.
+/// `app_` for `app_user_profile`.
+#[derive(Clone, Debug, Default)]
+pub struct Affixed<S> {
+    pub inner: S,
+    pub prefix: String,
+    pub suffix: String,
+}
+
+impl<S: NamingStrategy> NamingStrategy for Affixed<S> {
+    fn table_name(&self, type_name: &str) -> String {
+        format!(
+            "{}{}{}",
+            self.prefix,
+            self.inner.table_name(type_name),
+            self.suffix
+        )
+    }
+
+    fn column_name(&self, field_name: &str) -> String {
+        self.inner.column_name(field_name)
+    }
+
+    fn type_name(&self, type_name: &str) -> String {
+        self.inner.type_name(type_name)
+    }
+
+    fn variant_name(&self, variant_name: &str) -> String {
+        self.inner.variant_name(variant_name)
+    }
+}
+
+/// `UserProfile` → `user_profile`, `HTTPServer` → `http_server`.
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::with_capacity(name.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            let prev = i.checked_sub(1).map(|p| chars[p]);
+            let next = chars.get(i + 1);
+            let starts_word = match prev {
+                None | Some('_') => false,
+                Some(p) => {
+                    p.is_lowercase()
+                        || p.is_ascii_digit()
+                        || (p.is_uppercase() && next.is_some_and(|n| n.is_lowercase()))
+                }
+            };
+            if starts_word {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// English plural of a snake_case name, changing only its last word.
+fn plural(name: &str) -> String {
+    let consonant_y = name.ends_with('y')
+        && !name
+            .chars()
+            .rev()
+            .nth(1)
+            .is_some_and(|c| "aeiou".contains(c));
+    if consonant_y {
+        format!("{}ies", &name[..name.len() - 1])
+    } else if ["s", "x", "z", "ch", "sh"]
+        .iter()
+        .any(|end| name.ends_with(end))
+    {
+        format!("{}es", name)
+    } else {
+        format!("{}s", name)
+    }
+}
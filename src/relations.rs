@@ -2,14 +2,14 @@ use facet::Facet;
 
 #[derive(Facet)]
 #[repr(C)]
-enum Identifier {
+pub enum Identifier {
     Stringish(String),
     Numberish(usize),
 }
 
 #[derive(Facet)]
 #[repr(C)]
-enum Many<T: 'static> {
+pub enum Many<T: 'static> {
     Lazy(Identifier),
     Eager(Vec<T>),
 }
@@ -0,0 +1,648 @@
+use std::error::Error;
+use std::fmt;
+
+use facet::Facet;
+use facet_reflect::{Peek, PeekEnum, PeekStruct};
+use sea_query::{Alias, ArrayType, Func, InsertStatement, Query, SimpleExpr, Value};
+
+use crate::conversion::{
+    Discriminant, EnumLayout, RowLayout, is_unit_only, newtype, stored_fields,
+};
+use crate::ident::quote_qualified;
+use crate::*;
+
+#[derive(Debug)]
+pub enum RowError {
+    /// The value's type does not convert to a table.
+    Conversion(ConversionError),
+    /// A field's value cannot be converted to or from its column.
+    Unsupported { field: String, message: String },
+    /// An integer does not fit the column derived for it.
+    OutOfRange {
+        field: String,
+        value: String,
+        data_type: String,
+    },
+    /// A column holds a value of the wrong kind for its field.
+    Mismatch {
+        field: String,
+        expected: String,
+        found: String,
+    },
+    /// The row has no value for a field.
+    Missing { field: String },
+}
+
+impl fmt::Display for RowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RowError::Conversion(e) => write!(f, "{}", e),
+            RowError::Unsupported { field, message } => {
+                write!(f, "Field '{}' cannot be converted: {}", field, message)
+            }
+            RowError::OutOfRange {
+                field,
+                value,
+                data_type,
+            } => write!(
+                f,
+                "Field '{}' holds {}, which does not fit {}",
+                field, value, data_type
+            ),
+            RowError::Mismatch {
+                field,
+                expected,
+                found,
+            } => write!(f, "Field '{}' expects {}, found {}", field, expected, found),
+            RowError::Missing { field } => write!(f, "Field '{}' has no value in the row", field),
+        }
+    }
+}
+
+impl Error for RowError {}
+
+impl From<ConversionError> for RowError {
+    fn from(e: ConversionError) -> Self {
+        RowError::Conversion(e)
+    }
+}
+
+/// Writes values as rows of the table derived from their type.
+///
+/// Columns, `NULL`s, JSONB and enum encodings follow the same mapping as
+/// `PartialSchema::try_from`, so a row always fits the generated table as
+/// long as both use the same [`ConversionOptions`].
+///
+/// ```ignore
+/// let insert = RowEncoder::new().insert(&user, "public")?;
+/// let (sql, values) = insert.build(PostgresQueryBuilder);
+/// ```
+#[derive(Default)]
+pub struct RowEncoder {
+    options: ConversionOptions,
+}
+
+impl RowEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use the options the schema was generated with.
+    pub fn options(mut self, options: ConversionOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Column name and value pairs for `value`, in table column order.
+    ///
+    /// PostgreSQL enum labels and composite records are plain strings here;
+    /// [`RowEncoder::insert`] adds the casts they need.
+    pub fn values<'f, T: Facet<'f>>(&self, value: &T) -> Result<Vec<(String, Value)>, RowError> {
+        let layout = RowLayout::of(T::SHAPE, &self.options)?;
+        let value = into_struct(Peek::new(value))?;
+        Ok(encode_row(&layout, |field| struct_field(value, field))?
+            .into_iter()
+            .map(|(column, value)| (column.name.clone(), value))
+            .collect())
+    }
+
+    /// An `INSERT` of `value` into its table in `schema_name`.
+    pub fn insert<'f, T: Facet<'f>>(
+        &self,
+        value: &T,
+        schema_name: &str,
+    ) -> Result<InsertStatement, RowError> {
+        let layout = RowLayout::of(T::SHAPE, &self.options)?;
+        let value = into_struct(Peek::new(value))?;
+        let row = encode_row(&layout, |field| struct_field(value, field))?;
+        Ok(insert_into(
+            schema_name,
+            &layout.table.name,
+            row_exprs(&row, schema_name),
+        ))
+    }
+
+    /// The `INSERT`s storing an enum value in the tables generated for it:
+    /// the active variant's row, then the main row with its discriminant and
+    /// a reference to that row, as the `variant_integrity` check requires.
+    pub fn enum_insert<'f, T: Facet<'f>>(
+        &self,
+        value: &T,
+        schema_name: &str,
+    ) -> Result<EnumInsert, RowError> {
+        let layout = EnumLayout::of(T::SHAPE, &self.options)?;
+        let value = Peek::new(value)
+            .into_enum()
+            .map_err(|e| RowError::Conversion(ConversionError::NotAStruct(e.to_string())))?;
+        let index = value
+            .variant_index()
+            .map_err(|e| unsupported(&layout.table.name, e))?;
+
+        let discriminant = &layout.discriminant.column;
+        let mut main = row_exprs(
+            &[(
+                discriminant,
+                discriminant_value(&layout.discriminant.values[index], &discriminant.data_type),
+            )],
+            schema_name,
+        );
+        let variant = match layout.variant(index) {
+            Some(variant) => {
+                let row = encode_row(&variant.row, |field| variant_field(value, field))?;
+                let mut insert = insert_into(
+                    schema_name,
+                    &variant.row.table.name,
+                    row_exprs(&row, schema_name),
+                );
+                insert.returning_col(Alias::new("id"));
+
+                // The id the variant row was just given
+                let sequence = Func::cust(Alias::new("pg_get_serial_sequence"))
+                    .arg(quote_qualified(Some(schema_name), &variant.row.table.name))
+                    .arg("id");
+                main.push((
+                    &variant.column,
+                    Func::cust(Alias::new("currval")).arg(sequence).into(),
+                ));
+                Some(insert)
+            }
+            None => None,
+        };
+
+        let mut main = insert_into(schema_name, &layout.table.name, main);
+        main.returning_col(Alias::new("id"));
+        Ok(EnumInsert { variant, main })
+    }
+}
+
+/// The statements storing one enum value, in execution order.
+///
+/// `main` reads the id of the variant row from its identity sequence, so run
+/// both in the same session, ideally in one transaction.
+pub struct EnumInsert {
+    /// Row of the active variant's table, `RETURNING id`. `None` for unit
+    /// variants, which have no table.
+    pub variant: Option<InsertStatement>,
+    /// Row of the enum's main table, `RETURNING id`.
+    pub main: InsertStatement,
+}
+
+impl EnumInsert {
+    /// The statements in the order they must run.
+    pub fn statements(&self) -> Vec<&InsertStatement> {
+        self.variant.iter().chain([&self.main]).collect()
+    }
+}
+
+fn into_struct<'mem, 'f>(peek: Peek<'mem, 'f>) -> Result<PeekStruct<'mem, 'f>, RowError> {
+    peek.into_struct()
+        .map_err(|e| RowError::Conversion(ConversionError::NotAStruct(e.to_string())))
+}
+
+fn struct_field<'mem, 'f>(
+    value: PeekStruct<'mem, 'f>,
+    field: &facet::Field,
+) -> Result<Peek<'mem, 'f>, RowError> {
+    value
+        .field_by_name(field.name)
+        .map_err(|e| unsupported(field.name, e))
+}
+
+fn variant_field<'mem, 'f>(
+    value: PeekEnum<'mem, 'f>,
+    field: &facet::Field,
+) -> Result<Peek<'mem, 'f>, RowError> {
+    match value.field_by_name(field.name) {
+        Ok(Some(peek)) => Ok(peek),
+        Ok(None) => Err(unsupported(field.name, "not a field of the active variant")),
+        Err(e) => Err(unsupported(field.name, e)),
+    }
+}
+
+/// An `INSERT` of one row into `table` in `schema_name`.
+fn insert_into<C: AsRef<str>>(
+    schema_name: &str,
+    table: &str,
+    row: Vec<(C, SimpleExpr)>,
+) -> InsertStatement {
+    let columns: Vec<_> = row.iter().map(|(c, _)| Alias::new(c.as_ref())).collect();
+    let mut insert = Query::insert();
+    insert
+        .into_table((Alias::new(schema_name), Alias::new(table)))
+        .columns(columns)
+        .values_panic(row.into_iter().map(|(_, expr)| expr));
+    insert
+}
+
+/// Encoded values as expressions, cast where PostgreSQL needs a type. Types
+/// without a schema are the ones created in `schema_name`.
+fn row_exprs<'l>(row: &[(&'l Column, Value)], schema_name: &str) -> Vec<(&'l str, SimpleExpr)> {
+    row.iter()
+        .map(|(column, value)| {
+            let expr = SimpleExpr::Value(value.clone());
+            let expr = if needs_cast(&column.data_type) {
+                let data_type = qualify_type(&column.data_type, schema_name);
+                expr.cast_as(Alias::new(render_data_type(&data_type)))
+            } else {
+                expr
+            };
+            (column.name.as_str(), expr)
+        })
+        .collect()
+}
+
+/// Each column of `layout` with its value, reading fields with `field_value`.
+fn encode_row<'l, 'mem, 'f>(
+    layout: &'l RowLayout,
+    field_value: impl Fn(&'static facet::Field) -> Result<Peek<'mem, 'f>, RowError>,
+) -> Result<Vec<(&'l Column, Value)>, RowError> {
+    let mut row = Vec::with_capacity(layout.table.columns.len());
+    for (column, path) in layout.columns() {
+        let encoder = ValueEncoder {
+            field: &path_name(path),
+            layout,
+        };
+        let mut peek = Some(field_value(path[0])?);
+        for field in &path[1..] {
+            // Every column of a flattened `None` is NULL
+            let Some(mut value) = peek else { break };
+            if let facet::Def::Option(_) = value.shape().def {
+                let option = value
+                    .into_option()
+                    .map_err(|e| unsupported(encoder.field, e))?;
+                match option.value() {
+                    Some(inner) => value = inner,
+                    None => {
+                        peek = None;
+                        break;
+                    }
+                }
+            }
+            peek = Some(struct_field(into_struct(value)?, field)?);
+        }
+        let encoded = match peek {
+            Some(value) => encoder.encode(value, &column.data_type)?,
+            None => null_of(&column.data_type),
+        };
+        row.push((column, encoded));
+    }
+    Ok(row)
+}
+
+/// The fields of a column's path joined with dots, e.g. `address.zip`.
+pub(crate) fn path_name(path: &[&facet::Field]) -> String {
+    path.iter()
+        .map(|field| field.name)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+pub(crate) fn unsupported(field: &str, message: impl fmt::Display) -> RowError {
+    RowError::Unsupported {
+        field: field.to_string(),
+        message: message.to_string(),
+    }
+}
+
+/// Encodes the value of one field according to its column type.
+struct ValueEncoder<'l> {
+    field: &'l str,
+    layout: &'l RowLayout,
+}
+
+impl ValueEncoder<'_> {
+    fn encode(&self, peek: Peek<'_, '_>, data_type: &DataType) -> Result<Value, RowError> {
+        // Pointers are stored as what they point at
+        if let facet::Def::Pointer(_) = peek.shape().def {
+            let pointer = peek
+                .into_pointer()
+                .map_err(|e| unsupported(self.field, e))?;
+            return match pointer.borrow_inner() {
+                Some(inner) => self.encode(inner, data_type),
+                None => Err(unsupported(self.field, "pointer cannot be borrowed")),
+            };
+        }
+
+        if let facet::Def::Option(_) = peek.shape().def {
+            let option = peek.into_option().map_err(|e| unsupported(self.field, e))?;
+            return match option.value() {
+                Some(inner) => self.encode(inner, data_type),
+                None => Ok(null_of(data_type)),
+            };
+        }
+
+        if let Some(mapping) = self.layout.registry.get(peek.shape()) {
+            return (mapping.encode)(peek).map_err(|e| unsupported(self.field, e));
+        }
+
+        if let DataType::Domain { name, .. } = data_type {
+            let Some(domain) = self.layout.domains.get(name) else {
+                return Err(unsupported(
+                    self.field,
+                    format!("unknown domain '{}'", name),
+                ));
+            };
+            return self.encode(peek, &domain.base_type);
+        }
+
+        // Newtypes are stored as their field
+        if newtype(peek.shape())?.is_some() {
+            let value = peek.into_struct().map_err(|e| unsupported(self.field, e))?;
+            let inner = value.field(0).map_err(|e| unsupported(self.field, e))?;
+            return self.encode(inner, data_type);
+        }
+
+        match data_type {
+            DataType::Boolean => Ok(Value::Bool(Some(*self.get::<bool>(peek)?))),
+            DataType::SmallInt => {
+                let v = self.integer(peek, data_type)?;
+                Ok(Value::SmallInt(Some(self.fit(v, data_type)?)))
+            }
+            DataType::Integer => {
+                let v = self.integer(peek, data_type)?;
+                Ok(Value::Int(Some(self.fit(v, data_type)?)))
+            }
+            DataType::BigInt => {
+                let v = self.integer(peek, data_type)?;
+                Ok(Value::BigInt(Some(self.fit(v, data_type)?)))
+            }
+            DataType::Real => Ok(Value::Float(Some(self.float(peek)? as f32))),
+            DataType::DoublePrecision => Ok(Value::Double(Some(self.float(peek)?))),
+            // Sent as text, which `insert` casts
+            DataType::Numeric { .. } => {
+                let number = match peek.scalar_type() {
+                    Some(facet::ScalarType::F32 | facet::ScalarType::F64) => {
+                        self.float(peek)?.to_string()
+                    }
+                    _ => self.integer(peek, data_type)?.to_string(),
+                };
+                Ok(Value::String(Some(Box::new(number))))
+            }
+            DataType::Char(_)
+            | DataType::Text
+            | DataType::Varchar(_)
+            | DataType::Uuid
+            | DataType::Inet
+            | DataType::MacAddr
+            | DataType::Custom { .. } => match (peek.as_str(), peek.get::<char>()) {
+                (Some(s), _) => Ok(Value::String(Some(Box::new(s.to_string())))),
+                (None, Ok(c)) => Ok(Value::String(Some(Box::new(c.to_string())))),
+                (None, Err(_)) => Err(unsupported(
+                    self.field,
+                    format!("{} is not a string", peek.shape()),
+                )),
+            },
+            DataType::Bytea => Ok(Value::Bytes(Some(Box::new(self.bytes(peek)?)))),
+            DataType::Json | DataType::Jsonb => {
+                let json = facet_json::peek_to_string(peek);
+                let json = serde_json::from_str(&json).map_err(|e| unsupported(self.field, e))?;
+                Ok(Value::Json(Some(Box::new(json))))
+            }
+            DataType::Array(element) => {
+                let items = self.elements(peek, element)?;
+                Ok(Value::Array(array_type(element), Some(Box::new(items))))
+            }
+            DataType::Enum { name, .. } => {
+                let Some(enum_type) = self.layout.enums.get(name) else {
+                    return Err(unsupported(
+                        self.field,
+                        format!("unknown enum type '{}'", name),
+                    ));
+                };
+                let value = peek.into_enum().map_err(|e| unsupported(self.field, e))?;
+                let index = value
+                    .variant_index()
+                    .map_err(|e| unsupported(self.field, e))?;
+                Ok(Value::String(Some(Box::new(
+                    enum_type.variants[index].clone(),
+                ))))
+            }
+            DataType::Composite { name, .. } => {
+                let Some(composite) = self.layout.composites.get(name) else {
+                    return Err(unsupported(
+                        self.field,
+                        format!("unknown composite '{}'", name),
+                    ));
+                };
+                let value = peek.into_struct().map_err(|e| unsupported(self.field, e))?;
+                let mut fields = Vec::with_capacity(composite.fields.len());
+                let stored = stored_fields(value.ty().fields);
+                for (&i, column) in stored.iter().zip(&composite.fields) {
+                    let field = value.field(i).map_err(|e| unsupported(self.field, e))?;
+                    fields.push(self.encode(field, &column.data_type)?);
+                }
+                Ok(Value::String(Some(Box::new(record_literal(&fields)))))
+            }
+            other => Err(unsupported(
+                self.field,
+                format!("no encoding for {}", render_data_type(other)),
+            )),
+        }
+    }
+
+    fn get<'mem, 'f, T: Facet<'f>>(&self, peek: Peek<'mem, 'f>) -> Result<&'mem T, RowError> {
+        peek.get::<T>().map_err(|_| {
+            unsupported(
+                self.field,
+                format!("expected {}, found {}", T::SHAPE, peek.shape()),
+            )
+        })
+    }
+
+    fn float(&self, peek: Peek<'_, '_>) -> Result<f64, RowError> {
+        match peek.get::<f32>() {
+            Ok(f) => Ok(*f as f64),
+            Err(_) => self.get::<f64>(peek).copied(),
+        }
+    }
+
+    /// Integer value of an integer primitive, or the discriminant of a
+    /// fieldless enum.
+    fn integer(&self, peek: Peek<'_, '_>, data_type: &DataType) -> Result<i128, RowError> {
+        use facet::ScalarType as S;
+
+        if let facet::Type::User(facet::UserType::Enum(enum_type)) = peek.shape().ty {
+            // The discriminant alone would drop the variant's fields
+            if !is_unit_only(&enum_type) {
+                return Err(unsupported(
+                    self.field,
+                    format!(
+                        "{} carries data, which an integer column cannot hold; store it with psql::storage = \"jsonb\"",
+                        peek.shape()
+                    ),
+                ));
+            }
+            let value = peek.into_enum().map_err(|e| unsupported(self.field, e))?;
+            return Ok(value.discriminant() as i128);
+        }
+
+        let value = match peek.scalar_type() {
+            Some(S::I8) => peek.get::<i8>().map(|v| *v as i128),
+            Some(S::I16) => peek.get::<i16>().map(|v| *v as i128),
+            Some(S::I32) => peek.get::<i32>().map(|v| *v as i128),
+            Some(S::I64) => peek.get::<i64>().map(|v| *v as i128),
+            Some(S::I128) => peek.get::<i128>().copied(),
+            Some(S::ISize) => peek.get::<isize>().map(|v| *v as i128),
+            Some(S::U8) => peek.get::<u8>().map(|v| *v as i128),
+            Some(S::U16) => peek.get::<u16>().map(|v| *v as i128),
+            Some(S::U32) => peek.get::<u32>().map(|v| *v as i128),
+            Some(S::U64) => peek.get::<u64>().map(|v| *v as i128),
+            Some(S::USize) => peek.get::<usize>().map(|v| *v as i128),
+            Some(S::U128) => match peek.get::<u128>() {
+                Ok(v) => {
+                    return i128::try_from(*v).map_err(|_| RowError::OutOfRange {
+                        field: self.field.to_string(),
+                        value: v.to_string(),
+                        data_type: render_data_type(data_type),
+                    });
+                }
+                Err(e) => Err(e),
+            },
+            _ => {
+                return Err(unsupported(
+                    self.field,
+                    format!("{} is not an integer", peek.shape()),
+                ));
+            }
+        };
+        value.map_err(|e| unsupported(self.field, e))
+    }
+
+    fn fit<T: TryFrom<i128>>(&self, value: i128, data_type: &DataType) -> Result<T, RowError> {
+        T::try_from(value).map_err(|_| RowError::OutOfRange {
+            field: self.field.to_string(),
+            value: value.to_string(),
+            data_type: render_data_type(data_type),
+        })
+    }
+
+    /// The bytes of a `Vec<u8>`, byte slice or other sequence of bytes.
+    fn bytes(&self, peek: Peek<'_, '_>) -> Result<Vec<u8>, RowError> {
+        if let Some(bytes) = peek.as_bytes() {
+            return Ok(bytes.to_vec());
+        }
+        let list = peek
+            .into_list_like()
+            .map_err(|e| unsupported(self.field, e))?;
+        list.iter()
+            .map(|item| self.get::<u8>(item).copied())
+            .collect()
+    }
+
+    fn elements(&self, peek: Peek<'_, '_>, element: &DataType) -> Result<Vec<Value>, RowError> {
+        if let facet::Def::Set(_) = peek.shape().def {
+            let set = peek.into_set().map_err(|e| unsupported(self.field, e))?;
+            return set.iter().map(|item| self.encode(item, element)).collect();
+        }
+        let list = peek
+            .into_list_like()
+            .map_err(|e| unsupported(self.field, e))?;
+        list.iter().map(|item| self.encode(item, element)).collect()
+    }
+}
+
+/// The value of a variant's discriminant for a column of `data_type`, which
+/// is wide enough for every discriminant of the enum.
+fn discriminant_value(discriminant: &Discriminant, data_type: &DataType) -> Value {
+    match (discriminant, data_type) {
+        (Discriminant::Integer(n), DataType::SmallInt) => Value::SmallInt(Some(*n as i16)),
+        (Discriminant::Integer(n), DataType::Integer) => Value::Int(Some(*n as i32)),
+        (Discriminant::Integer(n), _) => Value::BigInt(Some(*n)),
+        (Discriminant::Label(label), _) => Value::String(Some(Box::new(label.clone()))),
+    }
+}
+
+/// Whether a value for `data_type` is sent as text and needs a cast.
+fn needs_cast(data_type: &DataType) -> bool {
+    match data_type {
+        DataType::Enum { .. }
+        | DataType::Composite { .. }
+        | DataType::Numeric { .. }
+        | DataType::Timestamp { .. }
+        | DataType::Date
+        | DataType::Time { .. }
+        | DataType::Uuid
+        | DataType::Inet
+        | DataType::MacAddr
+        | DataType::Custom { .. } => true,
+        DataType::Array(element) => needs_cast(element),
+        _ => false,
+    }
+}
+
+/// The sea-query array type for elements of `data_type`; nested arrays use
+/// their innermost element type.
+pub(crate) fn array_type(data_type: &DataType) -> ArrayType {
+    match data_type {
+        DataType::Boolean => ArrayType::Bool,
+        DataType::SmallInt => ArrayType::SmallInt,
+        DataType::Integer => ArrayType::Int,
+        DataType::BigInt => ArrayType::BigInt,
+        DataType::Real => ArrayType::Float,
+        DataType::DoublePrecision => ArrayType::Double,
+        DataType::Jsonb => ArrayType::Json,
+        DataType::Array(element) => array_type(element),
+        _ => ArrayType::String,
+    }
+}
+
+/// A typed `NULL` for a column of `data_type`.
+pub(crate) fn null_of(data_type: &DataType) -> Value {
+    match data_type {
+        DataType::Boolean => Value::Bool(None),
+        DataType::SmallInt => Value::SmallInt(None),
+        DataType::Integer => Value::Int(None),
+        DataType::BigInt => Value::BigInt(None),
+        DataType::Real => Value::Float(None),
+        DataType::DoublePrecision => Value::Double(None),
+        DataType::Jsonb => Value::Json(None),
+        DataType::Bytea => Value::Bytes(None),
+        DataType::Array(element) => Value::Array(array_type(element), None),
+        _ => Value::String(None),
+    }
+}
+
+/// PostgreSQL text form of a composite value, e.g. `("a b","1",)`.
+fn record_literal(fields: &[Value]) -> String {
+    let fields: Vec<_> = fields
+        .iter()
+        .map(|v| text_of(v).map(|t| quote_element(&t)).unwrap_or_default())
+        .collect();
+    format!("({})", fields.join(","))
+}
+
+/// Text form of a value inside a record or array literal; `None` is `NULL`.
+fn text_of(value: &Value) -> Option<String> {
+    match value {
+        Value::Bool(v) => v.map(|b| if b { "t" } else { "f" }.to_string()),
+        Value::SmallInt(v) => v.map(|n| n.to_string()),
+        Value::Int(v) => v.map(|n| n.to_string()),
+        Value::BigInt(v) => v.map(|n| n.to_string()),
+        Value::Float(v) => v.map(|n| n.to_string()),
+        Value::Double(v) => v.map(|n| n.to_string()),
+        Value::String(v) => v.as_ref().map(|s| s.to_string()),
+        Value::Json(v) => v.as_ref().map(|j| j.to_string()),
+        Value::Bytes(v) => v.as_ref().map(|bytes| bytea_text(bytes)),
+        Value::Array(_, v) => v.as_ref().map(|items| {
+            let items: Vec<_> = items
+                .iter()
+                .map(|item| match text_of(item) {
+                    Some(t) => quote_element(&t),
+                    None => "NULL".to_string(),
+                })
+                .collect();
+            format!("{{{}}}", items.join(","))
+        }),
+        _ => None,
+    }
+}
+
+/// PostgreSQL's hex form of a `bytea`, e.g. `\x0aff`.
+fn bytea_text(bytes: &[u8]) -> String {
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("\\x{}", hex)
+}
+
+fn quote_element(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
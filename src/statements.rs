@@ -0,0 +1,465 @@
+use sea_query::{
+    Alias, ColumnDef, ColumnType, ConditionalStatement, Expr, ForeignKey as SeaForeignKey,
+    ForeignKeyAction, ForeignKeyCreateStatement, Index as SeaIndex, IndexCreateStatement,
+    IndexOrder, IndexType, InsertStatement, IntoIden, Query, StringLen, Table as SeaTable,
+    TableCreateStatement,
+    extension::postgres::{Type, TypeCreateStatement},
+};
+
+use crate::*;
+use std::error::Error;
+
+/// Raised when part of a schema has no sea-query equivalent.
+#[derive(Debug)]
+pub enum StatementError {
+    Unsupported(String),
+}
+
+impl fmt::Display for StatementError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatementError::Unsupported(msg) => {
+                write!(f, "Not expressible with sea-query: {}", msg)
+            }
+        }
+    }
+}
+
+impl Error for StatementError {}
+
+/// The sea-query statements making up a [`PartialSchema`], grouped in the
+/// order they have to be executed (the same order `to_ddl` emits them in).
+pub struct SchemaStatements {
+    /// `CREATE SCHEMA IF NOT EXISTS` for the target schema, as SQL, since
+    /// sea-query has no statement for it.
+    pub schema: String,
+    pub types: Vec<TypeCreateStatement>,
+    pub tables: Vec<TableCreateStatement>,
+    /// `COMMENT ON` the enum types, tables and columns that have a comment, as
+    /// SQL, since sea-query has no statement for them either.
+    pub comments: Vec<String>,
+    /// Rows of lookup tables, see [`Table::rows`].
+    pub rows: Vec<InsertStatement>,
+    pub indexes: Vec<IndexCreateStatement>,
+    pub foreign_keys: Vec<ForeignKeyCreateStatement>,
+}
+
+impl PartialSchema {
+    /// Convert this schema into sea-query statements targeting `schema_name`.
+    ///
+    /// Only tables, indexes, foreign keys and enum types have sea-query
+    /// counterparts; a schema containing views, domains, composite types or
+    /// sequences is rejected rather than silently truncated.
+    pub fn to_sea_query(&self, schema_name: &str) -> Result<SchemaStatements, StatementError> {
+        if !self.views.is_empty() || !self.materialized_views.is_empty() {
+            return Err(StatementError::Unsupported("views".into()));
+        }
+        if !self.domains.is_empty() {
+            return Err(StatementError::Unsupported("domain types".into()));
+        }
+        if !self.composite_types.is_empty() {
+            return Err(StatementError::Unsupported("composite types".into()));
+        }
+        if !self.sequences.is_empty() {
+            return Err(StatementError::Unsupported("sequences".into()));
+        }
+
+        let types = self
+            .enums
+            .iter()
+            .map(|e| {
+                let mut e = e.clone();
+                e.schema.get_or_insert_with(|| schema_name.into());
+                e.to_create_statement()
+            })
+            .collect();
+
+        let tables = self
+            .tables
+            .iter()
+            .map(|t| t.to_create_statement(schema_name))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let enum_comments = self.enums.iter().filter_map(|e| {
+            let comment = e.comment.as_ref()?;
+            Some(format!(
+                "COMMENT ON TYPE {} IS '{}';",
+                quote_type(e.schema.as_deref(), &e.name, schema_name),
+                esc(comment)
+            ))
+        });
+        let comments = enum_comments
+            .chain(
+                self.tables
+                    .iter()
+                    .flat_map(|t| render_comments(t, schema_name)),
+            )
+            .collect();
+
+        let rows = self
+            .tables
+            .iter()
+            .filter(|t| !t.rows.is_empty())
+            .map(|t| {
+                let mut insert = Query::insert();
+                insert
+                    .into_table((Alias::new(schema_name), Alias::new(&t.name)))
+                    .columns(t.columns.iter().map(|c| Alias::new(&c.name)));
+                for row in &t.rows {
+                    insert.values_panic(row.iter().map(Expr::cust));
+                }
+                insert
+            })
+            .collect();
+
+        let mut indexes = Vec::new();
+        for t in &self.tables {
+            for idx in t.indexes.iter().filter(|idx| !idx.is_primary) {
+                indexes.push(idx.to_create_statement(schema_name, &t.name)?);
+            }
+        }
+
+        let mut foreign_keys = Vec::new();
+        for t in &self.tables {
+            for fk in &t.foreign_keys {
+                let mut fk = fk.clone();
+                if fk.referenced_columns.is_none() {
+                    fk.referenced_columns = self.primary_key_of(&fk.referenced_table);
+                }
+                foreign_keys.push(fk.to_create_statement(schema_name, &t.name)?);
+            }
+        }
+
+        Ok(SchemaStatements {
+            schema: format!("CREATE SCHEMA IF NOT EXISTS {};", quote_ident(schema_name)),
+            types,
+            tables,
+            comments,
+            rows,
+            indexes,
+            foreign_keys,
+        })
+    }
+
+    fn primary_key_of(&self, table: &QualifiedName) -> Option<Vec<String>> {
+        self.tables
+            .iter()
+            .find(|t| t.name == table.name)
+            .and_then(|t| t.primary_key.as_ref())
+            .map(|pk| pk.columns.clone())
+    }
+}
+
+impl Table {
+    /// Build the `CREATE TABLE` statement, including the primary key, unique
+    /// and check constraints. Indexes and foreign keys are separate statements.
+    ///
+    /// Types without a schema are assumed to live in `schema_name`, the way
+    /// [`PartialSchema::to_sea_query`] creates them.
+    pub fn to_create_statement(
+        &self,
+        schema_name: &str,
+    ) -> Result<TableCreateStatement, StatementError> {
+        let opts = &self.options;
+        if !opts.inherits.is_empty()
+            || opts.unlogged
+            || opts.partitioned == Some(true)
+            || opts.tablespace.is_some()
+            || !opts.with_storage_params.is_empty()
+        {
+            return Err(StatementError::Unsupported(format!(
+                "table options on {}",
+                self.name
+            )));
+        }
+
+        let mut stmt = SeaTable::create();
+        stmt.table((Alias::new(schema_name), Alias::new(&self.name)));
+        if opts.temporary {
+            stmt.temporary();
+        }
+
+        // sea-query's own check constraints are anonymous, so named ones are
+        // appended as raw table constraints after the last column.
+        let named_checks = self
+            .checks
+            .iter()
+            .filter_map(|ck| {
+                let name = ck.name.as_ref()?;
+                Some(format!(
+                    ", CONSTRAINT {} CHECK ({})",
+                    quote_ident(name),
+                    ck.expression
+                ))
+            })
+            .collect::<String>();
+        if !named_checks.is_empty() && self.columns.is_empty() {
+            return Err(StatementError::Unsupported(format!(
+                "named check constraints on {} without columns",
+                self.name
+            )));
+        }
+
+        for (i, c) in self.columns.iter().enumerate() {
+            let mut def = column_def(c, schema_name);
+            if i + 1 == self.columns.len() && !named_checks.is_empty() {
+                def.extra(named_checks.clone());
+            }
+            stmt.col(def);
+        }
+
+        if let Some(pk) = &self.primary_key {
+            let mut idx = SeaIndex::create();
+            if let Some(name) = &pk.name {
+                idx.name(name);
+            }
+            for col in &pk.columns {
+                idx.col(Alias::new(col));
+            }
+            stmt.primary_key(idx.primary());
+        }
+
+        for u in &self.uniques {
+            let mut idx = SeaIndex::create();
+            idx.name(u.effective_name(&self.name));
+            for col in &u.columns {
+                idx.col(Alias::new(col));
+            }
+            stmt.index(idx.unique());
+        }
+
+        for ck in self.checks.iter().filter(|ck| ck.name.is_none()) {
+            stmt.check(Expr::cust(&ck.expression));
+        }
+
+        Ok(stmt)
+    }
+}
+
+fn column_def(c: &Column, schema_name: &str) -> ColumnDef {
+    let data_type = qualify_type(&c.data_type, schema_name);
+    let mut def = ColumnDef::new_with_type(Alias::new(&c.name), data_type.to_column_type());
+    if let Some(coll) = &c.collation {
+        def.extra(format!("COLLATE {}", quote_ident(coll)));
+    }
+    if c.is_identity {
+        let r#gen = match c.identity_generation {
+            Some(IdentityGeneration::Always) => "ALWAYS",
+            Some(IdentityGeneration::ByDefault) | None => "BY DEFAULT",
+        };
+        def.extra(format!("GENERATED {} AS IDENTITY", r#gen));
+    } else if c.is_generated {
+        if let Some(expr) = &c.generation_expression {
+            def.generated(Expr::cust(expr), true);
+        }
+    } else if let Some(d) = &c.default {
+        def.default(Expr::cust(d));
+    }
+    if !c.nullable {
+        def.not_null();
+    }
+    def
+}
+
+impl DataType {
+    /// The sea-query column type. Types sea-query has no variant for are
+    /// passed through as [`ColumnType::Custom`].
+    pub fn to_column_type(&self) -> ColumnType {
+        match self {
+            DataType::Boolean => ColumnType::Boolean,
+            DataType::SmallInt => ColumnType::SmallInteger,
+            DataType::Integer => ColumnType::Integer,
+            DataType::BigInt => ColumnType::BigInteger,
+            DataType::Real => ColumnType::Float,
+            DataType::DoublePrecision => ColumnType::Double,
+            DataType::Numeric { precision, scale } => match (precision, scale) {
+                (Some(p), Some(s)) => ColumnType::Decimal(Some((*p, *s))),
+                (Some(p), None) => ColumnType::custom(format!("numeric({})", p)),
+                _ => ColumnType::custom("numeric"),
+            },
+            DataType::Serial => ColumnType::custom("serial"),
+            DataType::BigSerial => ColumnType::custom("bigserial"),
+            DataType::Text => ColumnType::Text,
+            DataType::Varchar(len) => ColumnType::String(match len {
+                Some(n) => StringLen::N(*n),
+                None => StringLen::None,
+            }),
+            DataType::Char(len) => ColumnType::Char(*len),
+            DataType::Bytea => ColumnType::VarBinary(StringLen::None),
+            DataType::Timestamp { with_time_zone } => {
+                if *with_time_zone {
+                    ColumnType::TimestampWithTimeZone
+                } else {
+                    ColumnType::DateTime
+                }
+            }
+            DataType::Date => ColumnType::Date,
+            DataType::Time { with_time_zone } => {
+                if *with_time_zone {
+                    ColumnType::custom("time with time zone")
+                } else {
+                    ColumnType::Time
+                }
+            }
+            DataType::Interval => ColumnType::Interval(None, None),
+            DataType::Json => ColumnType::Json,
+            DataType::Jsonb => ColumnType::JsonBinary,
+            DataType::Uuid => ColumnType::Uuid,
+            DataType::Inet => ColumnType::Inet,
+            DataType::MacAddr => ColumnType::MacAddr,
+            DataType::TsVector => ColumnType::custom("tsvector"),
+            DataType::Array(inner) => ColumnType::Array(inner.to_column_type().into()),
+            DataType::Enum { schema, name }
+            | DataType::Composite { schema, name }
+            | DataType::Domain { schema, name } => {
+                ColumnType::custom(quote_qualified_type(schema.as_deref(), name))
+            }
+            DataType::Custom { schema, name } => {
+                ColumnType::custom(quote_qualified(schema.as_deref(), name))
+            }
+            DataType::Any => ColumnType::custom("any"),
+            DataType::Unknown => ColumnType::custom("unknown"),
+        }
+    }
+}
+
+impl Index {
+    /// Build the `CREATE INDEX` statement for this index on `table`.
+    pub fn to_create_statement(
+        &self,
+        schema_name: &str,
+        table: &str,
+    ) -> Result<IndexCreateStatement, StatementError> {
+        let name = self.effective_name(table);
+        if self.concurrently {
+            return Err(StatementError::Unsupported(format!(
+                "CONCURRENTLY on index {}",
+                name
+            )));
+        }
+        if self.tablespace.is_some() {
+            return Err(StatementError::Unsupported(format!(
+                "TABLESPACE on index {}",
+                name
+            )));
+        }
+
+        let mut stmt = SeaIndex::create();
+        stmt.name(&name)
+            .table((Alias::new(schema_name), Alias::new(table)));
+
+        for col in &self.columns {
+            if col.collate.is_some() || col.opclass.is_some() || col.nulls_order.is_some() {
+                return Err(StatementError::Unsupported(format!(
+                    "collation, operator class or NULLS order on index {}",
+                    name
+                )));
+            }
+            let order = col.order.as_ref().map(|o| match o {
+                SortOrder::Asc => IndexOrder::Asc,
+                SortOrder::Desc => IndexOrder::Desc,
+            });
+            match (&col.expr, order) {
+                (IndexExpr::Column(c), Some(o)) => stmt.col((Alias::new(c), o)),
+                (IndexExpr::Column(c), None) => stmt.col(Alias::new(c)),
+                (IndexExpr::Expression(e), Some(o)) => stmt.col((Expr::cust(e), o)),
+                (IndexExpr::Expression(e), None) => stmt.col(Expr::cust(e)),
+            };
+        }
+
+        if self.unique {
+            stmt.unique();
+        }
+        stmt.index_type(match self.method.as_deref().unwrap_or("btree") {
+            "btree" => IndexType::BTree,
+            "hash" => IndexType::Hash,
+            other => IndexType::Custom(Alias::new(other).into_iden()),
+        });
+        for col in &self.include {
+            stmt.include(Alias::new(col));
+        }
+        if let Some(pred) = &self.predicate {
+            stmt.and_where(Expr::cust(pred));
+        }
+
+        Ok(stmt)
+    }
+}
+
+impl ForeignKey {
+    /// Build the `ALTER TABLE ... ADD CONSTRAINT ... FOREIGN KEY` statement for
+    /// this key on `table`.
+    ///
+    /// A referenced table without a schema is assumed to live in `schema_name`.
+    /// `referenced_columns` must be set; [`PartialSchema::to_sea_query`] fills
+    /// it in from the referenced table's primary key.
+    pub fn to_create_statement(
+        &self,
+        schema_name: &str,
+        table: &str,
+    ) -> Result<ForeignKeyCreateStatement, StatementError> {
+        let name = self.effective_name(table);
+        if self.match_type.is_some() || self.deferrable.is_some() || self.initially.is_some() {
+            return Err(StatementError::Unsupported(format!(
+                "MATCH or DEFERRABLE on foreign key {}",
+                name
+            )));
+        }
+        let referenced_columns = self.referenced_columns.as_ref().ok_or_else(|| {
+            StatementError::Unsupported(format!("foreign key {} without referenced columns", name))
+        })?;
+
+        let ref_schema = self
+            .referenced_table
+            .schema
+            .as_deref()
+            .unwrap_or(schema_name);
+
+        let mut stmt = SeaForeignKey::create();
+        stmt.name(&name)
+            .from_tbl((Alias::new(schema_name), Alias::new(table)))
+            .to_tbl((
+                Alias::new(ref_schema),
+                Alias::new(&self.referenced_table.name),
+            ));
+        for col in &self.columns {
+            stmt.from_col(Alias::new(col));
+        }
+        for col in referenced_columns {
+            stmt.to_col(Alias::new(col));
+        }
+        if let Some(action) = &self.on_delete {
+            stmt.on_delete(action.into());
+        }
+        if let Some(action) = &self.on_update {
+            stmt.on_update(action.into());
+        }
+
+        Ok(stmt)
+    }
+}
+
+impl From<&ReferentialAction> for ForeignKeyAction {
+    fn from(action: &ReferentialAction) -> Self {
+        match action {
+            ReferentialAction::NoAction => ForeignKeyAction::NoAction,
+            ReferentialAction::Restrict => ForeignKeyAction::Restrict,
+            ReferentialAction::Cascade => ForeignKeyAction::Cascade,
+            ReferentialAction::SetNull => ForeignKeyAction::SetNull,
+            ReferentialAction::SetDefault => ForeignKeyAction::SetDefault,
+        }
+    }
+}
+
+impl EnumType {
+    /// Build the `CREATE TYPE ... AS ENUM` statement.
+    pub fn to_create_statement(&self) -> TypeCreateStatement {
+        let mut stmt = Type::create();
+        match &self.schema {
+            Some(s) => stmt.as_enum((Alias::new(s), Alias::new(&self.name))),
+            None => stmt.as_enum(Alias::new(&self.name)),
+        };
+        stmt.values(self.variants.iter().map(Alias::new));
+        stmt
+    }
+}
@@ -0,0 +1,116 @@
+use facet::Facet;
+use facet_psql_schema as psql;
+use facet_psql_schema::*;
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Sample {
+    #[facet(psql::primary_key)]
+    id: i64,
+    readings: Vec<f32>,
+    #[facet(psql::check_length)]
+    rgb: [i16; 3],
+    labels: Box<[String]>,
+    codes: &'static [i64],
+    grid: Vec<Vec<i32>>,
+    maybe_scores: Vec<Option<i32>>,
+    optional_tags: Option<Vec<String>>,
+    points: Vec<Point>,
+    #[facet(psql::jsonb)]
+    raw: Vec<i32>,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Matrix {
+    #[facet(psql::check_length)]
+    weights: Option<[f64; 4]>,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct BadLength {
+    #[facet(psql::check_length)]
+    values: Vec<i32>,
+}
+
+fn data_type<'a>(table: &'a Table, name: &str) -> &'a DataType {
+    &table
+        .columns
+        .iter()
+        .find(|c| c.name == name)
+        .unwrap()
+        .data_type
+}
+
+#[test]
+fn test_sequences_map_to_arrays() {
+    let schema = PartialSchema::try_from(Sample::SHAPE).expect("Failed to convert Sample");
+    let table = &schema.tables[0];
+    let sql = schema.to_ddl("public");
+    println!("{}", sql);
+
+    assert!(sql.contains("readings real[] NOT NULL"));
+    assert!(sql.contains("rgb smallint[] NOT NULL"));
+    assert!(sql.contains("labels text[] NOT NULL"));
+    assert!(sql.contains("codes bigint[] NOT NULL"));
+    assert!(sql.contains("grid integer[][] NOT NULL"));
+    assert!(sql.contains("maybe_scores integer[] NOT NULL"));
+    assert!(sql.contains("optional_tags text[],"));
+
+    assert!(matches!(
+        data_type(table, "grid"),
+        DataType::Array(inner) if matches!(**inner, DataType::Array(_))
+    ));
+}
+
+#[test]
+fn test_jsonb_is_kept_where_needed() {
+    let schema = PartialSchema::try_from(Sample::SHAPE).expect("Failed to convert Sample");
+    let table = &schema.tables[0];
+
+    // Struct elements have no native array form
+    assert!(matches!(data_type(table, "points"), DataType::Jsonb));
+    // Forced by the attribute
+    assert!(matches!(data_type(table, "raw"), DataType::Jsonb));
+
+    let options = ConversionOptions {
+        arrays_as_jsonb: true,
+        ..Default::default()
+    };
+    let err = SchemaBuilder::new()
+        .options(options)
+        .register(Matrix::SHAPE)
+        .build()
+        .err()
+        .expect("check_length needs a native array");
+    assert!(matches!(err, ConversionError::InvalidAttribute(_)));
+}
+
+#[test]
+fn test_fixed_size_array_length_check() {
+    let schema = PartialSchema::try_from(Sample::SHAPE).expect("Failed to convert Sample");
+    let check = &schema.tables[0].checks[0];
+    assert_eq!(check.name.as_deref(), Some("sample_rgb_check"));
+    assert_eq!(check.expression, "array_length(rgb, 1) = 3");
+
+    let schema = PartialSchema::try_from(Matrix::SHAPE).expect("Failed to convert Matrix");
+    let sql = schema.to_ddl("public");
+    println!("{}", sql);
+    assert!(sql.contains("weights double precision[]);"));
+    assert!(sql.contains(
+        "ALTER TABLE public.matrix ADD CONSTRAINT matrix_weights_check CHECK (array_length(weights, 1) = 4);"
+    ));
+
+    assert!(matches!(
+        PartialSchema::try_from(BadLength::SHAPE),
+        Err(ConversionError::InvalidAttribute(_))
+    ));
+}
@@ -0,0 +1,108 @@
+use facet::Facet;
+use facet_psql_schema as psql;
+use facet_psql_schema::*;
+use sea_query::Value;
+
+#[derive(Facet, Debug, PartialEq)]
+struct Attachment {
+    #[facet(psql::primary_key)]
+    id: i64,
+    data: Vec<u8>,
+    thumbnail: Option<Box<[u8]>>,
+    #[facet(psql::array)]
+    levels: Vec<u8>,
+    digest: [u8; 4],
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Borrowed {
+    magic: &'static [u8],
+    #[facet(psql::jsonb)]
+    raw: Vec<u8>,
+}
+
+fn attachment() -> Attachment {
+    Attachment {
+        id: 1,
+        data: vec![0, 1, 0xff],
+        thumbnail: Some(vec![0x0a].into_boxed_slice()),
+        levels: vec![3, 4],
+        digest: [1, 2, 3, 4],
+    }
+}
+
+#[test]
+fn test_byte_sequences_map_to_bytea() {
+    let schema = PartialSchema::try_from(Attachment::SHAPE).unwrap();
+    let sql = schema.to_ddl("public");
+    println!("{}", sql);
+    assert!(sql.contains("data bytea NOT NULL"));
+    assert!(sql.contains("thumbnail bytea,"));
+    assert!(sql.contains("levels smallint[] NOT NULL"));
+    // Fixed-size arrays keep their length, and stay arrays
+    assert!(sql.contains("digest smallint[] NOT NULL"));
+
+    let schema = PartialSchema::try_from(Borrowed::SHAPE).unwrap();
+    let sql = schema.to_ddl("public");
+    assert!(sql.contains("magic bytea NOT NULL"));
+    assert!(sql.contains("raw jsonb NOT NULL"));
+}
+
+#[test]
+fn test_bytea_rows_round_trip() {
+    let attachment = attachment();
+    let values = RowEncoder::new().values(&attachment).unwrap();
+    assert_eq!(values[1].1, Value::Bytes(Some(Box::new(vec![0, 1, 0xff]))));
+    assert_eq!(values[2].1, Value::Bytes(Some(Box::new(vec![0x0a]))));
+    assert!(matches!(values[3].1, Value::Array(_, Some(_))));
+
+    let decoded: Attachment = RowDecoder::new().decode(&values).unwrap();
+    assert_eq!(decoded, attachment);
+
+    let empty = Attachment {
+        thumbnail: None,
+        ..attachment
+    };
+    let values = RowEncoder::new().values(&empty).unwrap();
+    assert_eq!(values[2].1, Value::Bytes(None));
+    let decoded: Attachment = RowDecoder::new().decode(&values).unwrap();
+    assert_eq!(decoded, empty);
+}
+
+#[test]
+fn test_bytea_decodes_from_hex_text() {
+    let mut values = RowEncoder::new().values(&attachment()).unwrap();
+    values[1].1 = Value::String(Some(Box::new("\\x00ff10".into())));
+    let decoded: Attachment = RowDecoder::new().decode(&values).unwrap();
+    assert_eq!(decoded.data, vec![0, 0xff, 0x10]);
+
+    values[1].1 = Value::String(Some(Box::new("\\x0".into())));
+    let err = RowDecoder::new()
+        .decode::<Attachment, _>(&values)
+        .expect_err("odd number of hex digits");
+    assert!(matches!(err, RowError::Mismatch { .. }));
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn test_bytes_round_trip() {
+    #[derive(Facet, Debug, PartialEq)]
+    struct Blob {
+        #[facet(psql::primary_key)]
+        id: i64,
+        body: bytes::Bytes,
+    }
+
+    let schema = PartialSchema::try_from(Blob::SHAPE).unwrap();
+    assert!(schema.to_ddl("public").contains("body bytea NOT NULL"));
+
+    let blob = Blob {
+        id: 1,
+        body: bytes::Bytes::from_static(b"abc"),
+    };
+    let values = RowEncoder::new().values(&blob).unwrap();
+    assert_eq!(values[1].1, Value::Bytes(Some(Box::new(b"abc".to_vec()))));
+    let decoded: Blob = RowDecoder::new().decode(&values).unwrap();
+    assert_eq!(decoded, blob);
+}
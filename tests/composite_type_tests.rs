@@ -0,0 +1,121 @@
+use facet::Facet;
+use facet_psql_schema as psql;
+use facet_psql_schema::*;
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct GeoPoint {
+    lat: f64,
+    lon: f64,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Address {
+    street: String,
+    zip: Option<String>,
+    location: GeoPoint,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Customer {
+    #[facet(psql::primary_key)]
+    id: i64,
+    #[facet(psql::composite)]
+    home: Address,
+    #[facet(psql::composite)]
+    work: Option<Address>,
+    billing: Address,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Warehouse {
+    #[facet(psql::primary_key)]
+    id: i64,
+    location: GeoPoint,
+}
+
+fn column<'a>(table: &'a Table, name: &str) -> &'a Column {
+    table.columns.iter().find(|c| c.name == name).unwrap()
+}
+
+#[test]
+fn test_field_opt_in_creates_composite_types() {
+    let schema = PartialSchema::try_from(Customer::SHAPE).expect("Failed to convert Customer");
+
+    // Inner types come first, and each is declared once
+    let names: Vec<_> = schema
+        .composite_types
+        .iter()
+        .map(|c| c.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["geopoint", "address"]);
+
+    let address = &schema.composite_types[1];
+    let fields: Vec<_> = address.fields.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(fields, vec!["street", "zip", "location"]);
+    assert!(matches!(address.fields[0].data_type, DataType::Text));
+    assert!(address.fields[1].nullable);
+    assert!(
+        matches!(&address.fields[2].data_type, DataType::Composite { name, .. } if name == "geopoint")
+    );
+
+    let table = &schema.tables[0];
+    assert!(
+        matches!(&column(table, "home").data_type, DataType::Composite { name, .. } if name == "address")
+    );
+    let work = column(table, "work");
+    assert!(matches!(&work.data_type, DataType::Composite { name, .. } if name == "address"));
+    assert!(work.nullable);
+    // Fields without the attribute keep using JSONB
+    assert!(matches!(
+        column(table, "billing").data_type,
+        DataType::Jsonb
+    ));
+
+    let sql = schema.to_ddl("public");
+    println!("{}", sql);
+    let point = sql
+        .find("CREATE TYPE public.geopoint AS (lat double precision, lon double precision);")
+        .expect("Missing geopoint");
+    let address = sql
+        .find("CREATE TYPE public.address AS (street text, zip text, location public.geopoint);")
+        .expect("Missing address");
+    let table = sql.find("CREATE TABLE public.customer").unwrap();
+    assert!(point < address && address < table);
+}
+
+#[test]
+fn test_schema_wide_composites() {
+    let options = ConversionOptions {
+        composite_types: true,
+        ..Default::default()
+    };
+    let schema = SchemaBuilder::new()
+        .options(options)
+        .register(Customer::SHAPE)
+        .register(Warehouse::SHAPE)
+        .build()
+        .expect("Failed to build schema");
+
+    assert_eq!(schema.composite_types.len(), 2);
+    let customer = &schema.tables[0];
+    assert!(matches!(
+        column(customer, "billing").data_type,
+        DataType::Composite { .. }
+    ));
+    let warehouse = &schema.tables[1];
+    assert!(
+        matches!(&column(warehouse, "location").data_type, DataType::Composite { name, .. } if name == "geopoint")
+    );
+
+    // The default stays JSONB
+    let schema = PartialSchema::try_from(Warehouse::SHAPE).unwrap();
+    assert!(schema.composite_types.is_empty());
+    assert!(matches!(
+        column(&schema.tables[0], "location").data_type,
+        DataType::Jsonb
+    ));
+}
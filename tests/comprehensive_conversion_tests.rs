@@ -91,6 +91,7 @@ enum Status {
 }
 
 /// Enum with data
+#[allow(dead_code)]
 #[derive(Facet)]
 #[repr(C)]
 enum UserRole {
@@ -437,22 +438,28 @@ fn test_vector_fields_shape() {
         "VectorFields has tags, scores, weights, flags"
     );
 
-    // Vec fields are currently mapped to Jsonb
+    // Vec fields of scalars are mapped to native arrays
     let tags = table.columns.iter().find(|c| c.name == "tags").unwrap();
     assert!(!tags.nullable);
-    assert!(matches!(tags.data_type, DataType::Jsonb));
+    assert!(matches!(&tags.data_type, DataType::Array(inner) if matches!(**inner, DataType::Text)));
 
     let scores = table.columns.iter().find(|c| c.name == "scores").unwrap();
     assert!(!scores.nullable);
-    assert!(matches!(scores.data_type, DataType::Jsonb));
+    assert!(
+        matches!(&scores.data_type, DataType::Array(inner) if matches!(**inner, DataType::Integer))
+    );
 
     let weights = table.columns.iter().find(|c| c.name == "weights").unwrap();
     assert!(!weights.nullable);
-    assert!(matches!(weights.data_type, DataType::Jsonb));
+    assert!(
+        matches!(&weights.data_type, DataType::Array(inner) if matches!(**inner, DataType::DoublePrecision))
+    );
 
     let flags = table.columns.iter().find(|c| c.name == "flags").unwrap();
     assert!(!flags.nullable);
-    assert!(matches!(flags.data_type, DataType::Jsonb));
+    assert!(
+        matches!(&flags.data_type, DataType::Array(inner) if matches!(**inner, DataType::Boolean))
+    );
 }
 
 #[test]
@@ -638,6 +645,11 @@ fn test_user_with_status_shape() {
     println!("UserWithStatus shape: {:#?}", shape);
 
     let schema = PartialSchema::try_from(shape).expect("Failed to convert UserWithStatus");
+    assert_eq!(schema.enums.len(), 1);
+    assert_eq!(
+        schema.enums[0].variants,
+        vec!["active", "inactive", "pending"]
+    );
     let table = schema.tables.into_iter().next().unwrap();
 
     assert_eq!(table.name, "userwithstatus");
@@ -653,18 +665,18 @@ fn test_user_with_status_shape() {
     let username = table.columns.iter().find(|c| c.name == "username").unwrap();
     assert!(matches!(username.data_type, DataType::Text));
 
-    // Enum field should be Integer
+    // Unit-only enum field should be a PostgreSQL enum
     let status = table.columns.iter().find(|c| c.name == "status").unwrap();
     assert!(
-        matches!(status.data_type, DataType::Integer),
-        "Enum should map to Integer"
+        matches!(&status.data_type, DataType::Enum { name, .. } if name == "status"),
+        "Unit-only enum should map to Enum"
     );
     assert!(!status.nullable);
 
     let role = table.columns.iter().find(|c| c.name == "role").unwrap();
     assert!(
         matches!(role.data_type, DataType::Integer),
-        "Enum should map to Integer"
+        "Enum with data should map to Integer"
     );
     assert!(!role.nullable);
 }
@@ -692,7 +704,7 @@ fn test_metadata_container_shape() {
         table
             .primary_key
             .as_ref()
-            .map_or(false, |pk| pk.columns == vec!["id"]),
+            .is_some_and(|pk| pk.columns == vec!["id"]),
         "Expected 'id' to be the primary key"
     );
 }
@@ -714,7 +726,7 @@ fn test_product_shape() {
         table
             .primary_key
             .as_ref()
-            .map_or(false, |pk| pk.columns == vec!["id"]),
+            .is_some_and(|pk| pk.columns == vec!["id"]),
         "Expected 'id' to be the primary key"
     );
 
@@ -725,7 +737,7 @@ fn test_product_shape() {
         table
             .primary_key
             .as_ref()
-            .map_or(false, |pk| pk.columns == vec!["id"]),
+            .is_some_and(|pk| pk.columns == vec!["id"]),
         "Expected 'id' to be the primary key"
     );
 }
@@ -763,19 +775,19 @@ fn test_fixed_size_arrays_shape() {
             let table = schema.tables.into_iter().next().unwrap();
             assert_eq!(table.name, "fixedsizearrays");
             // Arrays should be present
-            assert!(table.columns.len() > 0);
+            assert!(!table.columns.is_empty());
             assert!(
                 table
                     .primary_key
                     .as_ref()
-                    .map_or(false, |pk| pk.columns == vec!["id"]),
+                    .is_some_and(|pk| pk.columns == vec!["id"]),
                 "Expected 'id' to be the primary key"
             );
             assert!(
                 table
                     .primary_key
                     .as_ref()
-                    .map_or(false, |pk| pk.columns == vec!["id"]),
+                    .is_some_and(|pk| pk.columns == vec!["id"]),
                 "Expected 'id' to be the primary key"
             );
         }
@@ -797,7 +809,7 @@ fn test_blog_post_shape() {
 
     assert_eq!(table.name, "blogpost");
     // BlogPost should have title, content, tags, etc.
-    assert!(table.columns.len() > 0);
+    assert!(!table.columns.is_empty());
     assert!(
         table
             .columns
@@ -809,7 +821,7 @@ fn test_blog_post_shape() {
         table
             .primary_key
             .as_ref()
-            .map_or(false, |pk| pk.columns == vec!["id"]),
+            .is_some_and(|pk| pk.columns == vec!["id"]),
         "Expected 'id' to be the primary key"
     );
 }
@@ -832,19 +844,19 @@ fn test_borrowed_data_shape() {
             let table = schema.tables.into_iter().next().unwrap();
             assert_eq!(table.name.to_lowercase(), "borroweddata");
             // Should have fields that are references
-            assert!(table.columns.len() > 0);
+            assert!(!table.columns.is_empty());
             assert!(
                 table
                     .primary_key
                     .as_ref()
-                    .map_or(false, |pk| pk.columns == vec!["id"]),
+                    .is_some_and(|pk| pk.columns == vec!["id"]),
                 "Expected 'id' to be the primary key"
             );
             assert!(
                 table
                     .primary_key
                     .as_ref()
-                    .map_or(false, |pk| pk.columns == vec!["id"]),
+                    .is_some_and(|pk| pk.columns == vec!["id"]),
                 "Expected 'id' to be the primary key"
             );
         }
@@ -947,7 +959,7 @@ fn test_organization_shape() {
         table
             .primary_key
             .as_ref()
-            .map_or(false, |pk| pk.columns == vec!["id"]),
+            .is_some_and(|pk| pk.columns == vec!["id"]),
         "Expected 'id' to be the primary key"
     );
 }
@@ -972,7 +984,7 @@ fn test_department_shape() {
         table
             .primary_key
             .as_ref()
-            .map_or(false, |pk| pk.columns == vec!["id"]),
+            .is_some_and(|pk| pk.columns == vec!["id"]),
         "Expected 'id' to be the primary key"
     );
 }
@@ -989,7 +1001,7 @@ fn test_employee_shape() {
 
     assert_eq!(table.name, "employee");
     // Employee has multiple fields
-    assert!(table.columns.len() > 0, "Employee should have fields");
+    assert!(!table.columns.is_empty(), "Employee should have fields");
 
     // Verify key fields exist
     assert!(table.columns.iter().any(|c| c.name == "id"));
@@ -0,0 +1,84 @@
+use facet::Facet;
+use facet_psql_schema as psql;
+use facet_psql_schema::*;
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Member {
+    #[facet(psql::primary_key)]
+    id: i64,
+    #[facet(psql::unique)]
+    email: String,
+    #[facet(psql::check("age >= 0"), psql::check("age < 200"))]
+    age: i32,
+    #[facet(psql::default("now()"))]
+    joined: String,
+    #[facet(psql::not_null, psql::default("'guest'"))]
+    role: Option<String>,
+    #[facet(psql::check_length, psql::check("scores[1] > 0"))]
+    scores: [i32; 3],
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[repr(u8)]
+enum Login {
+    Password {
+        #[facet(psql::unique)]
+        user: String,
+        #[facet(psql::check("length(hash) = 64"))]
+        hash: String,
+    },
+    Anonymous,
+}
+
+#[test]
+fn test_field_constraints() {
+    let schema = PartialSchema::try_from(Member::SHAPE).unwrap();
+    let table = &schema.tables[0];
+
+    assert_eq!(table.uniques.len(), 1);
+    assert_eq!(table.uniques[0].columns, vec!["email"]);
+
+    let checks: Vec<_> = table
+        .checks
+        .iter()
+        .map(|c| (c.name.as_deref().unwrap(), c.expression.as_str()))
+        .collect();
+    assert_eq!(
+        checks,
+        vec![
+            ("member_age_check", "age >= 0"),
+            ("member_age_check1", "age < 200"),
+            ("member_scores_check", "array_length(scores, 1) = 3"),
+            ("member_scores_check1", "scores[1] > 0"),
+        ]
+    );
+
+    let column = |name: &str| table.columns.iter().find(|c| c.name == name).unwrap();
+    assert_eq!(column("joined").default.as_deref(), Some("now()"));
+    assert!(!column("role").nullable);
+    assert_eq!(column("role").default.as_deref(), Some("'guest'"));
+
+    let sql = schema.to_ddl("public");
+    println!("{}", sql);
+    assert!(sql.contains("joined text DEFAULT now() NOT NULL"));
+    assert!(sql.contains("role text DEFAULT 'guest' NOT NULL"));
+    assert!(sql.contains("ADD CONSTRAINT member_email_key UNIQUE (email);"));
+    assert!(sql.contains("ADD CONSTRAINT member_age_check1 CHECK (age < 200);"));
+}
+
+#[test]
+fn test_variant_field_constraints() {
+    let schema = PartialSchema::try_from(Login::SHAPE).unwrap();
+    let table = schema
+        .tables
+        .iter()
+        .find(|t| t.name == "login_password")
+        .unwrap();
+    assert_eq!(table.uniques[0].columns, vec!["user"]);
+    assert_eq!(
+        table.checks[0].name.as_deref(),
+        Some("login_password_hash_check")
+    );
+}
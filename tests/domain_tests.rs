@@ -0,0 +1,81 @@
+use facet::Facet;
+use facet_psql_schema as psql;
+use facet_psql_schema::*;
+
+#[derive(Facet, Debug, PartialEq)]
+#[facet(psql::domain("VALUE ~ '^.+@.+$'"))]
+struct Email(String);
+
+#[derive(Facet, Debug, PartialEq)]
+#[facet(psql::domain("VALUE > 0"), psql::domain("VALUE <= 100"))]
+struct Percent(i16);
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[facet(transparent, psql::domain("VALUE > 0"), psql::newtype = "inner")]
+struct Conflicting(i32);
+
+#[derive(Facet, Debug, PartialEq)]
+struct Survey {
+    #[facet(psql::primary_key)]
+    id: i64,
+    contact: Email,
+    score: Option<Percent>,
+}
+
+#[test]
+fn test_domain_checks() {
+    let schema = PartialSchema::try_from(Survey::SHAPE).unwrap();
+    let table = &schema.tables[0];
+    assert!(matches!(
+        &table.columns[1].data_type,
+        DataType::Domain { name, .. } if name == "email"
+    ));
+    assert!(table.columns[2].nullable);
+
+    assert_eq!(schema.domains.len(), 2);
+    let email = &schema.domains[0];
+    assert!(matches!(email.base_type, DataType::Text));
+    assert_eq!(email.constraints.len(), 1);
+    assert_eq!(email.constraints[0].expression, "VALUE ~ '^.+@.+$'");
+
+    let sql = schema.to_ddl("public");
+    println!("{}", sql);
+    assert!(sql.contains("CREATE DOMAIN public.email AS text CHECK (VALUE ~ '^.+@.+$');"));
+    assert!(sql.contains(
+        "CREATE DOMAIN public.percent AS smallint CHECK (VALUE > 0) CHECK (VALUE <= 100);"
+    ));
+    // Domains come before the tables using them
+    assert!(sql.find("CREATE DOMAIN public.email").unwrap() < sql.find("CREATE TABLE").unwrap());
+}
+
+#[test]
+fn test_named_domain_constraints_are_rendered() {
+    let mut schema = PartialSchema::try_from(Email::SHAPE).unwrap();
+    schema.domains[0].constraints[0].name = Some("email_format".to_string());
+    schema.domains[0].not_null = true;
+    assert!(schema.to_ddl("public").contains(
+        "CREATE DOMAIN public.email AS text NOT NULL CONSTRAINT email_format CHECK (VALUE ~ '^.+@.+$');"
+    ));
+}
+
+#[test]
+fn test_domain_values_round_trip() {
+    let survey = Survey {
+        id: 1,
+        contact: Email("a@b.c".into()),
+        score: Some(Percent(40)),
+    };
+    let values = RowEncoder::new().values(&survey).unwrap();
+    assert_eq!(values[2].1, sea_query::Value::SmallInt(Some(40)));
+    let decoded: Survey = RowDecoder::new().decode(&values).unwrap();
+    assert_eq!(decoded, survey);
+}
+
+#[test]
+fn test_domain_requires_domain_storage() {
+    let err = PartialSchema::try_from(Conflicting::SHAPE)
+        .err()
+        .expect("psql::domain conflicts with newtype = \"inner\"");
+    assert!(matches!(err, ConversionError::InvalidAttribute(_)));
+}
@@ -1,6 +1,8 @@
 use facet::Facet;
-use facet_psql_schema::{DataType, PartialSchema};
+use facet_psql_schema as psql;
+use facet_psql_schema::{ConversionError, DataType, PartialSchema, RowEncoder};
 
+#[allow(dead_code)]
 #[repr(u8)]
 #[derive(Facet)]
 enum Thingy {
@@ -84,3 +86,147 @@ fn test_enum_to_schema_thingy() {
     assert!(table_b.columns.iter().any(|c| c.name == "id"));
     assert!(table_b.columns.iter().any(|c| c.name == "b"));
 }
+
+#[allow(dead_code)]
+#[repr(u8)]
+#[derive(Facet)]
+enum Delivery {
+    Courier { name: String },
+    Pickup = 3,
+    Lost,
+}
+
+#[allow(dead_code)]
+#[repr(u8)]
+#[derive(Facet)]
+enum Color {
+    Red = 1,
+    Green = 2,
+}
+
+#[allow(dead_code)]
+#[repr(u8)]
+#[derive(Facet)]
+#[facet(psql::discriminator = "enum")]
+enum Size {
+    Small,
+    Large,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Paint {
+    #[facet(psql::primary_key)]
+    id: i64,
+    #[facet(psql::references(Color))]
+    color: i16,
+}
+
+#[test]
+fn test_unit_variants_are_valid_discriminants() {
+    let schema = PartialSchema::try_from(Delivery::SHAPE).unwrap();
+    // Unit variants get no table
+    assert_eq!(schema.tables.len(), 2);
+    let main = schema.tables.iter().find(|t| t.name == "delivery").unwrap();
+    assert_eq!(
+        main.checks[0].expression,
+        "(discriminant IN (0, 3, 4)) AND (CASE WHEN discriminant = 0 THEN courier_id IS NOT NULL ELSE courier_id IS NULL END)"
+    );
+    let discriminant = main
+        .columns
+        .iter()
+        .find(|c| c.name == "discriminant")
+        .unwrap();
+    assert_eq!(
+        discriminant.comment.as_deref(),
+        Some("Discriminant for enum variant. Unit variants: Pickup = 3, Lost = 4")
+    );
+}
+
+#[test]
+fn test_unit_variants_are_documented_in_the_database() {
+    let schema = PartialSchema::try_from(Delivery::SHAPE).unwrap();
+    let comment = "COMMENT ON COLUMN public.delivery.discriminant IS \
+                   'Discriminant for enum variant. Unit variants: Pickup = 3, Lost = 4';";
+
+    let ddl = schema.to_ddl("public");
+    println!("{}", ddl);
+    assert!(ddl.contains(comment));
+
+    let statements = schema.to_sea_query("public").unwrap();
+    assert!(statements.comments.iter().any(|c| c == comment));
+
+    let changes = PartialSchema::diff(&PartialSchema::default(), &schema);
+    assert!(PartialSchema::migration_ddl(&changes, "public").contains(comment));
+
+    // A changed comment is migrated on its own
+    let mut old = schema.clone();
+    let main = old
+        .tables
+        .iter_mut()
+        .find(|t| t.name == "delivery")
+        .unwrap();
+    main.comment = Some("Deliveries".into());
+    main.columns
+        .iter_mut()
+        .find(|c| c.name == "discriminant")
+        .unwrap()
+        .comment = None;
+    let sql = PartialSchema::migration_ddl(&PartialSchema::diff(&old, &schema), "public");
+    assert_eq!(
+        sql,
+        format!("COMMENT ON TABLE public.delivery IS NULL;\n{}", comment)
+    );
+}
+
+#[test]
+fn test_unit_only_enum_is_a_table_of_variants() {
+    let schema = PartialSchema::try_from(Color::SHAPE).unwrap();
+    assert_eq!(schema.tables.len(), 1);
+    let table = &schema.tables[0];
+    assert_eq!(table.name, "color");
+    let columns: Vec<_> = table.columns.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(columns, vec!["discriminant", "name"]);
+    assert!(matches!(table.columns[0].data_type, DataType::SmallInt));
+    assert_eq!(
+        table.primary_key.as_ref().unwrap().columns,
+        vec!["discriminant"]
+    );
+    assert!(schema.to_ddl("public").contains(
+        "INSERT INTO public.color (discriminant, name) VALUES (1, 'red'), (2, 'green');"
+    ));
+
+    // Referenced by its discriminant
+    let schema = PartialSchema::try_from(Paint::SHAPE).unwrap();
+    let paint = schema.tables.iter().find(|t| t.name == "paint").unwrap();
+    assert_eq!(paint.foreign_keys[0].referenced_table.name, "color");
+    assert_eq!(
+        paint.foreign_keys[0].referenced_columns,
+        Some(vec!["discriminant".to_string()])
+    );
+
+    assert!(
+        RowEncoder::new()
+            .enum_insert(&Color::Red, "public")
+            .is_err()
+    );
+}
+
+#[test]
+fn test_unit_only_enum_with_enum_discriminator_is_a_type() {
+    let schema = PartialSchema::try_from(Size::SHAPE).unwrap();
+    assert!(schema.tables.is_empty());
+    assert_eq!(schema.enums[0].name, "size");
+    assert_eq!(schema.enums[0].variants, vec!["small", "large"]);
+
+    #[allow(dead_code)]
+    #[derive(Facet)]
+    struct Shirt {
+        #[facet(psql::references(Size))]
+        size: i64,
+    }
+    let err = PartialSchema::try_from(Shirt::SHAPE)
+        .err()
+        .expect("Size has no table");
+    assert!(matches!(err, ConversionError::InvalidReference(_)));
+}
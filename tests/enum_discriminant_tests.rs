@@ -0,0 +1,147 @@
+use facet::Facet;
+use facet_psql_schema as psql;
+use facet_psql_schema::*;
+use sea_query::{PostgresQueryBuilder, Value};
+
+#[derive(Facet, Debug, PartialEq)]
+#[repr(u16)]
+enum Order {
+    Placed { total: i64 } = 5,
+    Shipped(String) = 10,
+    Cancelled = 20,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[repr(i64)]
+enum Wide {
+    Small(i32),
+    Big(i64),
+}
+
+#[derive(Facet, Debug, PartialEq)]
+#[facet(psql::discriminator = "text")]
+#[repr(u8)]
+enum Shipment {
+    Parcel { weight: i32 },
+    Letter,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[facet(psql::discriminator = "enum", psql::storage = "single_table")]
+#[repr(u8)]
+enum Alert {
+    Email { to: String },
+    Sms { number: String },
+}
+
+fn discriminant(table: &Table) -> &Column {
+    table
+        .columns
+        .iter()
+        .find(|c| c.name == "discriminant")
+        .expect("discriminant column")
+}
+
+#[test]
+fn test_declared_discriminants_are_stored() {
+    let schema = PartialSchema::try_from(Order::SHAPE).unwrap();
+    let main = schema.tables.iter().find(|t| t.name == "order").unwrap();
+    assert!(matches!(discriminant(main).data_type, DataType::Integer));
+    assert_eq!(
+        main.checks[0].expression,
+        "(discriminant IN (5, 10, 20)) AND (CASE WHEN discriminant = 5 THEN placed_id IS NOT NULL ELSE placed_id IS NULL END) AND (CASE WHEN discriminant = 10 THEN shipped_id IS NOT NULL ELSE shipped_id IS NULL END)"
+    );
+
+    let insert = RowEncoder::new()
+        .enum_insert(&Order::Cancelled, "public")
+        .unwrap();
+    assert_eq!(
+        insert.main.to_string(PostgresQueryBuilder),
+        r#"INSERT INTO "public"."order" ("discriminant") VALUES (20) RETURNING "id""#
+    );
+
+    let row = vec![
+        ("id".to_string(), Value::BigInt(Some(1))),
+        ("discriminant".to_string(), Value::Int(Some(10))),
+        ("order_placed__total".to_string(), Value::BigInt(None)),
+        (
+            "order_shipped__value".to_string(),
+            Value::String(Some(Box::new("DHL".into()))),
+        ),
+    ];
+    let order: Order = RowDecoder::new().decode(&row).unwrap();
+    assert_eq!(order, Order::Shipped("DHL".into()));
+}
+
+#[test]
+fn test_column_follows_repr_width() {
+    let schema = PartialSchema::try_from(Wide::SHAPE).unwrap();
+    let main = schema.tables.iter().find(|t| t.name == "wide").unwrap();
+    assert!(matches!(discriminant(main).data_type, DataType::BigInt));
+}
+
+#[test]
+fn test_text_discriminator_references_lookup_table() {
+    let schema = PartialSchema::try_from(Shipment::SHAPE).unwrap();
+    let lookup = schema
+        .tables
+        .iter()
+        .find(|t| t.name == "shipment_variant")
+        .expect("lookup table");
+    assert_eq!(lookup.rows, vec![vec!["'parcel'"], vec!["'letter'"]]);
+
+    let sql = schema.to_ddl("public");
+    println!("{}", sql);
+    assert!(
+        sql.contains("INSERT INTO public.shipment_variant (name) VALUES ('parcel'), ('letter');")
+    );
+    assert!(sql.contains("discriminant text NOT NULL"));
+    assert!(sql.contains(
+        "FOREIGN KEY (discriminant) REFERENCES public.shipment_variant (name) ON DELETE RESTRICT"
+    ));
+    assert!(sql.contains("(CASE WHEN discriminant = 'parcel' THEN parcel_id IS NOT NULL"));
+
+    let statements = schema.to_sea_query("public").unwrap();
+    assert_eq!(
+        statements.rows[0].to_string(PostgresQueryBuilder),
+        r#"INSERT INTO "public"."shipment_variant" ("name") VALUES ('parcel'), ('letter')"#
+    );
+
+    let insert = RowEncoder::new()
+        .enum_insert(&Shipment::Letter, "public")
+        .unwrap();
+    assert_eq!(
+        insert.main.to_string(PostgresQueryBuilder),
+        r#"INSERT INTO "public"."shipment" ("discriminant") VALUES ('letter') RETURNING "id""#
+    );
+    let row = vec![
+        (
+            "discriminant".to_string(),
+            Value::String(Some(Box::new("letter".into()))),
+        ),
+        ("shipment_parcel__weight".to_string(), Value::Int(None)),
+    ];
+    let shipment: Shipment = RowDecoder::new().decode(&row).unwrap();
+    assert_eq!(shipment, Shipment::Letter);
+}
+
+#[test]
+fn test_enum_discriminator_declares_type() {
+    let schema = PartialSchema::try_from(Alert::SHAPE).unwrap();
+    assert_eq!(schema.enums.len(), 1);
+    assert_eq!(schema.enums[0].name, "alert_variant");
+    assert_eq!(schema.enums[0].variants, vec!["email", "sms"]);
+
+    let table = &schema.tables[0];
+    assert!(matches!(
+        &discriminant(table).data_type,
+        DataType::Enum { name, .. } if name == "alert_variant"
+    ));
+    assert!(
+        table.checks[1]
+            .expression
+            .starts_with("CASE WHEN discriminant = 'email' THEN")
+    );
+}
@@ -0,0 +1,111 @@
+use facet::Facet;
+use facet_psql_schema::*;
+use sea_query::{PostgresQueryBuilder, Value};
+
+#[derive(Facet, Debug, PartialEq)]
+#[repr(u8)]
+enum Payment {
+    Card { number: String, cvc: Option<u16> },
+    Transfer(i64),
+    Cash,
+}
+
+#[test]
+fn test_identity_columns_are_not_serial() {
+    let schema = PartialSchema::try_from(Payment::SHAPE).unwrap();
+    let sql = schema.to_ddl("public");
+    println!("{}", sql);
+    assert!(!sql.contains("bigserial"));
+    assert!(sql.contains("id bigint GENERATED ALWAYS AS IDENTITY"));
+    assert!(sql.contains(
+        "(CASE WHEN discriminant = 1 THEN transfer_id IS NOT NULL ELSE transfer_id IS NULL END)"
+    ));
+}
+
+#[test]
+fn test_variant_row_is_inserted_first() {
+    let insert = RowEncoder::new()
+        .enum_insert(
+            &Payment::Card {
+                number: "4242".into(),
+                cvc: None,
+            },
+            "public",
+        )
+        .expect("Failed to build inserts");
+
+    let sql: Vec<_> = insert
+        .statements()
+        .iter()
+        .map(|s| s.to_string(PostgresQueryBuilder))
+        .collect();
+    println!("{:#?}", sql);
+    assert_eq!(
+        sql,
+        vec![
+            r#"INSERT INTO "public"."payment_card" ("number", "cvc") VALUES ('4242', NULL) RETURNING "id""#,
+            r#"INSERT INTO "public"."payment" ("discriminant", "card_id") VALUES (0, currval(pg_get_serial_sequence('public.payment_card', 'id'))) RETURNING "id""#,
+        ]
+    );
+}
+
+#[test]
+fn test_unit_variants_only_insert_the_main_row() {
+    let insert = RowEncoder::new()
+        .enum_insert(&Payment::Cash, "public")
+        .expect("Failed to build inserts");
+    assert!(insert.variant.is_none());
+    assert_eq!(
+        insert.main.to_string(PostgresQueryBuilder),
+        r#"INSERT INTO "public"."payment" ("discriminant") VALUES (2) RETURNING "id""#
+    );
+}
+
+#[test]
+fn test_select_joins_variant_tables() {
+    let select = RowDecoder::new()
+        .enum_select::<Payment>("public")
+        .expect("Failed to build select");
+    assert_eq!(
+        select.to_string(PostgresQueryBuilder),
+        r#"SELECT "payment"."id", "payment"."discriminant", "payment_card"."number" AS "payment_card__number", "payment_card"."cvc" AS "payment_card__cvc", "payment_transfer"."value" AS "payment_transfer__value" FROM "public"."payment" LEFT JOIN "public"."payment_card" ON "payment_card"."id" = "payment"."card_id" LEFT JOIN "public"."payment_transfer" ON "payment_transfer"."id" = "payment"."transfer_id""#
+    );
+}
+
+#[test]
+fn test_selected_rows_decode_to_the_variant() {
+    let row = |discriminant: i32, number: Option<&str>, transfer: Option<i64>| {
+        vec![
+            ("id".to_string(), Value::BigInt(Some(1))),
+            ("discriminant".to_string(), Value::Int(Some(discriminant))),
+            (
+                "payment_card__number".to_string(),
+                Value::String(number.map(|n| Box::new(n.to_string()))),
+            ),
+            ("payment_card__cvc".to_string(), Value::SmallInt(Some(123))),
+            (
+                "payment_transfer__value".to_string(),
+                Value::BigInt(transfer),
+            ),
+        ]
+    };
+    let decoder = RowDecoder::new();
+
+    let card: Payment = decoder.decode(&row(0, Some("4242"), None)).unwrap();
+    assert_eq!(
+        card,
+        Payment::Card {
+            number: "4242".into(),
+            cvc: Some(123)
+        }
+    );
+    let transfer: Payment = decoder.decode(&row(1, None, Some(99))).unwrap();
+    assert_eq!(transfer, Payment::Transfer(99));
+    let cash: Payment = decoder.decode(&row(2, None, None)).unwrap();
+    assert_eq!(cash, Payment::Cash);
+
+    let err = decoder
+        .decode::<Payment, _>(&row(7, None, None))
+        .expect_err("no variant 7");
+    assert!(matches!(&err, RowError::Mismatch { field, .. } if field == "discriminant"));
+}
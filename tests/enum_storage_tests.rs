@@ -0,0 +1,131 @@
+use facet::Facet;
+use facet_psql_schema as psql;
+use facet_psql_schema::*;
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[facet(psql::storage = "single_table")]
+#[repr(u8)]
+enum Event {
+    Click {
+        x: i32,
+        y: i32,
+        label: Option<String>,
+    },
+    Scroll(f64),
+    Idle,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[facet(psql::storage = "jsonb")]
+#[repr(u8)]
+enum Payload {
+    Text { body: String },
+    Empty,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[facet(psql::storage = "enum")]
+#[repr(u8)]
+enum Level {
+    Low,
+    High,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[facet(psql::storage = "enum")]
+#[repr(u8)]
+enum NotUnit {
+    Some(i32),
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Message {
+    #[facet(psql::primary_key)]
+    id: i64,
+    payload: Payload,
+    #[facet(psql::references(Event))]
+    event_id: i64,
+}
+
+#[test]
+fn test_single_table_inheritance() {
+    let schema = PartialSchema::try_from(Event::SHAPE).expect("Failed to convert Event");
+    assert_eq!(schema.tables.len(), 1);
+    let table = &schema.tables[0];
+
+    let columns: Vec<_> = table
+        .columns
+        .iter()
+        .map(|c| (c.name.as_str(), c.nullable))
+        .collect();
+    assert_eq!(
+        columns,
+        vec![
+            ("id", false),
+            ("discriminant", false),
+            ("click_x", true),
+            ("click_y", true),
+            ("click_label", true),
+            ("scroll_value", true),
+        ]
+    );
+
+    let sql = schema.to_ddl("public");
+    println!("{}", sql);
+    assert!(sql.contains(
+        "CONSTRAINT event_click_check CHECK (CASE WHEN discriminant = 0 THEN click_x IS NOT NULL AND click_y IS NOT NULL ELSE click_x IS NULL AND click_y IS NULL AND click_label IS NULL END)"
+    ));
+    assert!(sql.contains(
+        "CONSTRAINT event_scroll_check CHECK (CASE WHEN discriminant = 1 THEN scroll_value IS NOT NULL ELSE scroll_value IS NULL END)"
+    ));
+}
+
+#[test]
+fn test_tagged_jsonb_storage() {
+    let schema = PartialSchema::try_from(Payload::SHAPE).expect("Failed to convert Payload");
+    let table = &schema.tables[0];
+    assert_eq!(table.columns.len(), 2);
+    assert!(matches!(table.columns[1].data_type, DataType::Jsonb));
+    assert_eq!(
+        table.checks[0].expression,
+        "CASE jsonb_typeof(value) WHEN 'string' THEN value #>> '{}' IN ('Empty') WHEN 'object' THEN value <> '{}' AND (value - 'Text' = '{}') ELSE false END"
+    );
+
+    // Fields of the enum's type hold the same document
+    let schema = PartialSchema::try_from(Message::SHAPE).expect("Failed to convert Message");
+    let message = schema.tables.iter().find(|t| t.name == "message").unwrap();
+    assert!(matches!(message.columns[1].data_type, DataType::Jsonb));
+    assert!(schema.tables.iter().any(|t| t.name == "event"));
+
+    let values = RowEncoder::new()
+        .values(&Message {
+            id: 1,
+            payload: Payload::Text { body: "hi".into() },
+            event_id: 2,
+        })
+        .unwrap();
+    match &values[1].1 {
+        sea_query::Value::Json(Some(json)) => {
+            assert_eq!(json.to_string(), r#"{"Text":{"body":"hi"}}"#)
+        }
+        other => panic!("Expected JSON, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_native_enum_storage() {
+    let schema = PartialSchema::try_from(Level::SHAPE).expect("Failed to convert Level");
+    assert!(schema.tables.is_empty());
+    assert_eq!(schema.enums.len(), 1);
+    assert_eq!(schema.enums[0].variants, vec!["low", "high"]);
+
+    let err = PartialSchema::try_from(NotUnit::SHAPE)
+        .err()
+        .expect("Enums with data cannot be native");
+    assert!(matches!(err, ConversionError::InvalidAttribute(_)));
+}
@@ -0,0 +1,127 @@
+use facet::Facet;
+use facet_psql_schema as psql;
+use facet_psql_schema::*;
+use sea_query::Value;
+
+#[derive(Facet, Debug, PartialEq)]
+struct Address {
+    street: String,
+    zip: Option<String>,
+}
+
+#[derive(Facet, Debug, PartialEq)]
+struct Audit {
+    created_by: String,
+    #[facet(psql::check("audit_version > 0"))]
+    version: i32,
+}
+
+#[derive(Facet, Debug, PartialEq)]
+struct Customer {
+    #[facet(psql::primary_key)]
+    id: i64,
+    #[facet(flatten)]
+    address: Address,
+    #[facet(flatten)]
+    audit: Option<Audit>,
+    #[facet(skip)]
+    cache: Vec<String>,
+    #[facet(psql::skip)]
+    session: Option<String>,
+    #[facet(skip_serializing)]
+    secret: String,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct NotAStruct {
+    #[facet(flatten)]
+    value: i32,
+}
+
+fn columns(table: &Table) -> Vec<(&str, bool)> {
+    table
+        .columns
+        .iter()
+        .map(|c| (c.name.as_str(), c.nullable))
+        .collect()
+}
+
+#[test]
+fn test_skipped_and_flattened_columns() {
+    let schema = PartialSchema::try_from(Customer::SHAPE).unwrap();
+    let table = &schema.tables[0];
+    assert_eq!(
+        columns(table),
+        vec![
+            ("id", false),
+            ("address_street", false),
+            ("address_zip", true),
+            // Optional, so NULL for a `None`
+            ("audit_created_by", true),
+            ("audit_version", true),
+        ]
+    );
+    assert_eq!(
+        table.checks[0].name.as_deref(),
+        Some("customer_audit_version_check")
+    );
+
+    let err = PartialSchema::try_from(NotAStruct::SHAPE)
+        .err()
+        .expect("only structs can be flattened");
+    assert!(matches!(err, ConversionError::InvalidAttribute(_)));
+}
+
+#[test]
+fn test_flattened_rows_round_trip() {
+    let customer = Customer {
+        id: 1,
+        address: Address {
+            street: "Main St".into(),
+            zip: None,
+        },
+        audit: None,
+        cache: vec!["x".into()],
+        session: Some("abc".into()),
+        secret: "hunter2".into(),
+    };
+    let values = RowEncoder::new().values(&customer).unwrap();
+    let names: Vec<_> = values.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "id",
+            "address_street",
+            "address_zip",
+            "audit_created_by",
+            "audit_version"
+        ]
+    );
+    assert_eq!(values[3].1, Value::String(None));
+    assert_eq!(values[4].1, Value::Int(None));
+
+    // Skipped fields come back as their default
+    let decoded: Customer = RowDecoder::new().decode(&values).unwrap();
+    assert_eq!(
+        decoded,
+        Customer {
+            cache: vec![],
+            session: None,
+            secret: String::new(),
+            ..customer
+        }
+    );
+
+    let customer = Customer {
+        audit: Some(Audit {
+            created_by: "ada".into(),
+            version: 2,
+        }),
+        ..decoded
+    };
+    let values = RowEncoder::new().values(&customer).unwrap();
+    assert_eq!(values[4].1, Value::Int(Some(2)));
+    let decoded: Customer = RowDecoder::new().decode(&values).unwrap();
+    assert_eq!(decoded, customer);
+}
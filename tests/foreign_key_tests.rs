@@ -0,0 +1,184 @@
+use facet::Facet;
+use facet_psql_schema as psql;
+use facet_psql_schema::*;
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Customer {
+    #[facet(psql::primary_key)]
+    id: i64,
+    name: String,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Order {
+    #[facet(psql::primary_key)]
+    id: i64,
+    #[facet(psql::references(psql::Ref<Customer, psql::on_delete::Cascade>))]
+    customer_id: i64,
+    #[facet(psql::references(psql::Ref<
+        Customer,
+        psql::on_delete::SetNull,
+        psql::on_update::Restrict,
+    >))]
+    referrer_id: Option<i64>,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[repr(C)]
+enum Payment {
+    Card { number: String },
+    Cash,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Receipt {
+    #[facet(psql::primary_key)]
+    id: i64,
+    #[facet(psql::references(Payment))]
+    payment_id: i64,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct MismatchedOrder {
+    #[facet(psql::references(Customer))]
+    customer_id: String,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Note {
+    text: String,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct NoteLink {
+    #[facet(psql::references(Note))]
+    note_id: i64,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct BadAction {
+    #[facet(psql::references(psql::Ref<Customer, String>))]
+    customer_id: i64,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct TwoDeleteActions {
+    #[facet(psql::references(psql::Ref<
+        Customer,
+        psql::on_delete::Cascade,
+        psql::on_delete::Restrict,
+    >))]
+    customer_id: i64,
+}
+
+fn table<'a>(schema: &'a PartialSchema, name: &str) -> &'a Table {
+    schema
+        .tables
+        .iter()
+        .find(|t| t.name == name)
+        .unwrap_or_else(|| panic!("Missing table {}", name))
+}
+
+#[test]
+fn test_references_create_foreign_keys() {
+    let schema = PartialSchema::try_from(Order::SHAPE).expect("Failed to convert Order");
+
+    // The referenced table is pulled into the schema
+    let names: Vec<_> = schema.tables.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, vec!["order", "customer"]);
+
+    let order = table(&schema, "order");
+    assert_eq!(order.foreign_keys.len(), 2);
+
+    let customer_fk = &order.foreign_keys[0];
+    assert_eq!(customer_fk.columns, vec!["customer_id".to_string()]);
+    assert_eq!(customer_fk.referenced_table.name, "customer");
+    assert_eq!(customer_fk.referenced_columns, Some(vec!["id".to_string()]));
+    assert!(matches!(
+        customer_fk.on_delete,
+        Some(ReferentialAction::Cascade)
+    ));
+    assert!(customer_fk.on_update.is_none());
+
+    let referrer_fk = &order.foreign_keys[1];
+    assert!(matches!(
+        referrer_fk.on_delete,
+        Some(ReferentialAction::SetNull)
+    ));
+    assert!(matches!(
+        referrer_fk.on_update,
+        Some(ReferentialAction::Restrict)
+    ));
+
+    let sql = schema.to_ddl("public");
+    println!("{}", sql);
+    assert!(sql.contains(
+        "ALTER TABLE public.\"order\" ADD CONSTRAINT order_customer_id_fkey FOREIGN KEY (customer_id) REFERENCES public.customer (id) ON DELETE CASCADE;"
+    ));
+    assert!(sql.contains(
+        "ALTER TABLE public.\"order\" ADD CONSTRAINT order_referrer_id_fkey FOREIGN KEY (referrer_id) REFERENCES public.customer (id) ON DELETE SET NULL ON UPDATE RESTRICT;"
+    ));
+}
+
+#[test]
+fn test_reference_to_enum_table() {
+    let schema = PartialSchema::try_from(Receipt::SHAPE).expect("Failed to convert Receipt");
+    assert!(schema.tables.iter().any(|t| t.name == "payment"));
+    assert!(schema.tables.iter().any(|t| t.name == "payment_card"));
+
+    let fk = &table(&schema, "receipt").foreign_keys[0];
+    assert_eq!(fk.referenced_table.name, "payment");
+    assert_eq!(fk.referenced_columns, Some(vec!["id".to_string()]));
+}
+
+#[test]
+fn test_shared_reference_is_converted_once() {
+    let schema = SchemaBuilder::new()
+        .register(Customer::SHAPE)
+        .register(Order::SHAPE)
+        .build()
+        .expect("Failed to build schema");
+    let names: Vec<_> = schema.tables.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, vec!["customer", "order"]);
+}
+
+#[test]
+fn test_invalid_references_are_rejected() {
+    let err = PartialSchema::try_from(MismatchedOrder::SHAPE)
+        .err()
+        .unwrap();
+    assert!(matches!(err, ConversionError::InvalidReference(_)));
+    assert!(err.to_string().contains("mismatchedorder.customer_id"));
+
+    assert!(matches!(
+        PartialSchema::try_from(NoteLink::SHAPE),
+        Err(ConversionError::InvalidReference(_))
+    ));
+    assert!(matches!(
+        PartialSchema::try_from(BadAction::SHAPE),
+        Err(ConversionError::InvalidAttribute(_))
+    ));
+    assert!(matches!(
+        PartialSchema::try_from(TwoDeleteActions::SHAPE),
+        Err(ConversionError::InvalidAttribute(_))
+    ));
+}
+
+#[test]
+fn test_references_are_qualified_with_the_target_schema() {
+    let schema = PartialSchema::try_from(Order::SHAPE).expect("Failed to convert Order");
+    let sql = schema.to_ddl("app");
+    println!("{}", sql);
+    assert!(sql.contains(
+        "ALTER TABLE app.\"order\" ADD CONSTRAINT order_customer_id_fkey FOREIGN KEY (customer_id) REFERENCES app.customer (id) ON DELETE CASCADE;"
+    ));
+}
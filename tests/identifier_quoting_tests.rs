@@ -0,0 +1,288 @@
+use facet::Facet;
+use facet_psql_schema as psql;
+use facet_psql_schema::*;
+
+/// PostgreSQL's reserved keywords, including those only usable as function or type names.
+const RESERVED: &[&str] = &[
+    "all",
+    "analyse",
+    "analyze",
+    "and",
+    "any",
+    "array",
+    "as",
+    "asc",
+    "asymmetric",
+    "authorization",
+    "binary",
+    "both",
+    "case",
+    "cast",
+    "check",
+    "collate",
+    "collation",
+    "column",
+    "concurrently",
+    "constraint",
+    "create",
+    "cross",
+    "current_catalog",
+    "current_date",
+    "current_role",
+    "current_schema",
+    "current_time",
+    "current_timestamp",
+    "current_user",
+    "default",
+    "deferrable",
+    "desc",
+    "distinct",
+    "do",
+    "else",
+    "end",
+    "except",
+    "false",
+    "fetch",
+    "for",
+    "foreign",
+    "freeze",
+    "from",
+    "full",
+    "grant",
+    "group",
+    "having",
+    "ilike",
+    "in",
+    "initially",
+    "inner",
+    "intersect",
+    "into",
+    "is",
+    "isnull",
+    "join",
+    "lateral",
+    "leading",
+    "left",
+    "like",
+    "limit",
+    "localtime",
+    "localtimestamp",
+    "natural",
+    "not",
+    "notnull",
+    "null",
+    "offset",
+    "on",
+    "only",
+    "or",
+    "order",
+    "outer",
+    "overlaps",
+    "placing",
+    "primary",
+    "references",
+    "returning",
+    "right",
+    "select",
+    "session_user",
+    "similar",
+    "some",
+    "symmetric",
+    "system_user",
+    "table",
+    "tablesample",
+    "then",
+    "to",
+    "trailing",
+    "true",
+    "union",
+    "unique",
+    "user",
+    "using",
+    "variadic",
+    "verbose",
+    "when",
+    "where",
+    "window",
+    "with",
+];
+
+#[test]
+fn test_reserved_keywords_are_quoted() {
+    for kw in RESERVED {
+        assert_eq!(
+            quote_ident(kw),
+            format!("\"{}\"", kw),
+            "Reserved keyword {} should be quoted",
+            kw
+        );
+    }
+}
+
+#[test]
+fn test_plain_identifiers_are_left_alone() {
+    for name in [
+        "users", "user_id", "_private", "col1", "price$", "name", "type", "value",
+    ] {
+        assert_eq!(quote_ident(name), name);
+    }
+}
+
+#[test]
+fn test_irregular_identifiers_are_quoted_and_escaped() {
+    assert_eq!(quote_ident("UserName"), "\"UserName\"");
+    assert_eq!(quote_ident("1st"), "\"1st\"");
+    assert_eq!(quote_ident("with space"), "\"with space\"");
+    assert_eq!(quote_ident("say \"hi\""), "\"say \"\"hi\"\"\"");
+    assert_eq!(quote_ident(""), "\"\"");
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Order {
+    #[facet(psql::primary_key)]
+    id: i64,
+    user: String,
+    group: Option<i32>,
+}
+
+#[test]
+fn test_to_ddl_quotes_every_object_kind() {
+    let mut schema = PartialSchema::try_from(Order::SHAPE).expect("Failed to convert Order");
+    let table = &mut schema.tables[0];
+    table.name = "Order".into();
+    table.uniques.push(UniqueConstraint {
+        name: Some("Order_user_key".into()),
+        columns: vec!["user".into()],
+        deferrable: None,
+    });
+    table.checks.push(CheckConstraint {
+        name: Some("check".into()),
+        expression: "id > 0".into(),
+        no_inherit: false,
+    });
+    table.indexes.push(Index {
+        name: "Order_group_idx".into(),
+        columns: vec![IndexColumn {
+            expr: IndexExpr::Column("group".into()),
+            collate: None,
+            opclass: None,
+            order: None,
+            nulls_order: None,
+        }],
+        unique: false,
+        method: None,
+        predicate: None,
+        include: vec!["user".into()],
+        tablespace: None,
+        concurrently: false,
+        is_primary: false,
+        is_valid: true,
+    });
+    table.foreign_keys.push(ForeignKey {
+        name: None,
+        columns: vec!["group".into()],
+        referenced_table: QualifiedName {
+            schema: Some("Other".into()),
+            name: "table".into(),
+        },
+        referenced_columns: Some(vec!["select".into()]),
+        on_delete: None,
+        on_update: None,
+        match_type: None,
+        deferrable: None,
+        initially: None,
+    });
+    schema.enums.push(EnumType {
+        schema: None,
+        name: "Mood".into(),
+        variants: vec!["happy".into()],
+        comment: None,
+    });
+    schema.sequences.push(Sequence {
+        name: "Counter".into(),
+        schema: Some("all".into()),
+        owned_by: None,
+        start: None,
+        increment: None,
+        min_value: None,
+        max_value: None,
+        cache: None,
+        cycle: false,
+        comment: None,
+    });
+
+    let sql = schema.to_ddl("My Schema");
+    println!("{}", sql);
+
+    assert!(sql.contains("CREATE SCHEMA IF NOT EXISTS \"My Schema\";"));
+    assert!(sql.contains("CREATE TYPE \"My Schema\".\"Mood\" AS ENUM ('happy');"));
+    assert!(sql.contains("CREATE SEQUENCE \"all\".\"Counter\" NO CYCLE;"));
+    assert!(sql.contains(
+        "CREATE TABLE \"My Schema\".\"Order\" (id bigint NOT NULL, \"user\" text NOT NULL, \"group\" integer, PRIMARY KEY (id));"
+    ));
+    assert!(sql.contains(
+        "ALTER TABLE \"My Schema\".\"Order\" ADD CONSTRAINT \"Order_user_key\" UNIQUE (\"user\");"
+    ));
+    assert!(
+        sql.contains(
+            "ALTER TABLE \"My Schema\".\"Order\" ADD CONSTRAINT \"check\" CHECK (id > 0);"
+        )
+    );
+    assert!(sql.contains(
+        "CREATE INDEX \"Order_group_idx\" ON \"My Schema\".\"Order\" USING btree (\"group\") INCLUDE (\"user\");"
+    ));
+    assert!(sql.contains(
+        "ADD CONSTRAINT \"Order_group_fkey\" FOREIGN KEY (\"group\") REFERENCES \"Other\".\"table\" (\"select\");"
+    ));
+}
+
+/// The primary key used to be appended after the closing parenthesis of
+/// `CREATE TABLE`, which PostgreSQL rejects.
+#[test]
+fn test_primary_key_is_rendered_inside_create_table() {
+    let schema = PartialSchema::try_from(Order::SHAPE).expect("Failed to convert Order");
+    let sql = schema.to_ddl("public");
+    println!("{}", sql);
+
+    assert!(sql.contains(
+        "CREATE TABLE public.\"order\" (id bigint NOT NULL, \"user\" text NOT NULL, \"group\" integer, PRIMARY KEY (id));"
+    ));
+    assert!(!sql.contains("), PRIMARY KEY"));
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[repr(u8)]
+enum Interval {
+    Daily,
+    Weekly,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Schedule {
+    #[facet(psql::primary_key)]
+    id: i64,
+    every: Interval,
+}
+
+/// Bare, `interval` would be the built-in type rather than the enum.
+#[test]
+fn test_types_named_after_column_name_keywords_are_quoted() {
+    assert_eq!(quote_ident("interval"), "interval");
+
+    let schema = PartialSchema::try_from(Schedule::SHAPE).unwrap();
+    let sql = schema.to_ddl("public");
+    println!("{}", sql);
+    assert!(sql.contains("CREATE TYPE public.\"interval\" AS ENUM ('daily', 'weekly');"));
+    assert!(sql.contains("every public.\"interval\" NOT NULL"));
+
+    let statements = schema.to_sea_query("public").unwrap();
+    let table = statements.tables[0].to_string(sea_query::PostgresQueryBuilder);
+    assert!(
+        table.contains("\"every\" public.\"interval\" NOT NULL"),
+        "{}",
+        table
+    );
+}
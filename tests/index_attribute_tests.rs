@@ -0,0 +1,124 @@
+use facet::Facet;
+use facet_psql_schema as psql;
+use facet_psql_schema::*;
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[facet(
+    psql::index("tenant_id, created DESC NULLS LAST"),
+    psql::index("post_tags USING gin (tags)"),
+    psql::index("(title text_pattern_ops) INCLUDE (tenant_id, created) WHERE published")
+)]
+struct Post {
+    #[facet(psql::primary_key)]
+    id: i64,
+    #[facet(psql::index)]
+    tenant_id: i64,
+    #[facet(psql::index("post_slug USING hash"))]
+    slug: String,
+    #[facet(psql::column = "created_at")]
+    created: i64,
+    title: String,
+    tags: Vec<String>,
+    published: bool,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[facet(psql::index("USING gin"))]
+struct NoColumns {
+    id: i64,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[facet(psql::index("by id USING btree (id)"))]
+struct TwoNames {
+    id: i64,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[facet(psql::index("(id) INCLUDE id"))]
+struct BareInclude {
+    id: i64,
+}
+
+#[test]
+fn test_index_attributes() {
+    let schema = PartialSchema::try_from(Post::SHAPE).unwrap();
+    let table = &schema.tables[0];
+    let names: Vec<_> = table.indexes.iter().map(|i| i.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "post_idx_tenant_id",
+            "post_slug",
+            "post_idx_tenant_id_created_at",
+            "post_tags",
+            "post_idx_title",
+        ]
+    );
+
+    let sql = schema.to_ddl("public");
+    println!("{}", sql);
+    assert!(
+        sql.contains("CREATE INDEX post_idx_tenant_id ON public.post USING btree (tenant_id);")
+    );
+    assert!(sql.contains("CREATE INDEX post_slug ON public.post USING hash (slug);"));
+    assert!(sql.contains(
+        "CREATE INDEX post_idx_tenant_id_created_at ON public.post USING btree (tenant_id, created_at DESC NULLS LAST);"
+    ));
+    assert!(sql.contains("CREATE INDEX post_tags ON public.post USING gin (tags);"));
+    assert!(sql.contains(
+        "CREATE INDEX post_idx_title ON public.post USING btree (title text_pattern_ops) INCLUDE (tenant_id, created_at) WHERE published;"
+    ));
+}
+
+#[derive(Facet)]
+#[allow(dead_code)]
+#[facet(psql::index("a"), psql::index("USING hash (a)"))]
+struct Twice {
+    a: i32,
+}
+
+#[test]
+fn test_generated_names_do_not_clash() {
+    let schema = PartialSchema::try_from(Twice::SHAPE).unwrap();
+    let names: Vec<_> = schema.tables[0]
+        .indexes
+        .iter()
+        .map(|i| i.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["twice_idx_a", "twice_idx_a1"]);
+}
+
+#[test]
+fn test_malformed_index_definitions_are_rejected() {
+    for shape in [NoColumns::SHAPE, TwoNames::SHAPE, BareInclude::SHAPE] {
+        assert!(
+            matches!(
+                PartialSchema::try_from(shape),
+                Err(ConversionError::InvalidAttribute(_))
+            ),
+            "{} should be rejected",
+            shape
+        );
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct FieldColumns {
+    #[facet(psql::index("id, name"))]
+    id: i64,
+    name: String,
+}
+
+#[test]
+fn test_field_indexes_take_no_columns() {
+    assert!(matches!(
+        PartialSchema::try_from(FieldColumns::SHAPE),
+        Err(ConversionError::InvalidAttribute(_))
+    ));
+}
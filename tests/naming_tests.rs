@@ -0,0 +1,244 @@
+use std::sync::Arc;
+
+use facet::Facet;
+use facet_psql_schema as psql;
+use facet_psql_schema::*;
+
+#[derive(Facet, Debug, PartialEq)]
+struct UserProfile {
+    #[facet(psql::primary_key)]
+    profile_id: i64,
+    display_name: String,
+    #[facet(rename = "Bio")]
+    bio: Option<String>,
+    #[facet(psql::references(AccountHolder))]
+    holder_id: i64,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[facet(psql::table = "holders")]
+struct AccountHolder {
+    #[facet(psql::primary_key)]
+    id: i64,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[facet(rename = "Audit", rename_all = "camelCase")]
+struct AuditEntry {
+    entry_id: i64,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[repr(u8)]
+enum PaymentMethod {
+    BankTransfer { iban: String },
+    CreditCard(String),
+    Cash,
+}
+
+/// Plural snake_case tables and `c_`-prefixed columns.
+#[derive(Debug)]
+struct Prefixed;
+
+impl NamingStrategy for Prefixed {
+    fn table_name(&self, type_name: &str) -> String {
+        Plural(SnakeCase).table_name(type_name)
+    }
+
+    fn column_name(&self, field_name: &str) -> String {
+        format!("c_{}", field_name)
+    }
+}
+
+fn prefixed() -> ConversionOptions {
+    ConversionOptions {
+        naming: Arc::new(Prefixed),
+        ..Default::default()
+    }
+}
+
+fn names(table: &Table) -> Vec<&str> {
+    table.columns.iter().map(|c| c.name.as_str()).collect()
+}
+
+#[test]
+fn test_default_naming_lowercases() {
+    let schema = PartialSchema::try_from(UserProfile::SHAPE).unwrap();
+    let table = schema.tables.iter().find(|t| t.name == "userprofile");
+    assert_eq!(
+        names(table.unwrap()),
+        vec!["profile_id", "display_name", "Bio", "holder_id"]
+    );
+    assert!(schema.tables.iter().any(|t| t.name == "holders"));
+}
+
+#[test]
+fn test_strategy_names_tables_and_columns() {
+    let schema = SchemaBuilder::new()
+        .register(UserProfile::SHAPE)
+        .options(prefixed())
+        .build()
+        .unwrap();
+    let table = schema
+        .tables
+        .iter()
+        .find(|t| t.name == "user_profiles")
+        .unwrap();
+    // facet's rename is kept as written
+    assert_eq!(
+        names(table),
+        vec!["c_profile_id", "c_display_name", "Bio", "c_holder_id"]
+    );
+    assert_eq!(
+        table.primary_key.as_ref().unwrap().columns,
+        vec!["c_profile_id"]
+    );
+    assert_eq!(table.foreign_keys[0].referenced_table.name, "holders");
+
+    let sql = schema.to_ddl("public");
+    println!("{}", sql);
+    assert!(
+        sql.contains("ADD CONSTRAINT user_profiles_c_holder_id_fkey FOREIGN KEY (c_holder_id)")
+    );
+
+    // Rows use the same names
+    let profile = UserProfile {
+        profile_id: 1,
+        display_name: "Ada".into(),
+        bio: None,
+        holder_id: 2,
+    };
+    let values = RowEncoder::new()
+        .options(prefixed())
+        .values(&profile)
+        .unwrap();
+    assert_eq!(values[1].0, "c_display_name");
+    let decoded: UserProfile = RowDecoder::new()
+        .options(prefixed())
+        .decode(&values)
+        .unwrap();
+    assert_eq!(decoded, profile);
+}
+
+#[test]
+fn test_facet_renames_are_kept() {
+    let schema = SchemaBuilder::new()
+        .register(AuditEntry::SHAPE)
+        .options(prefixed())
+        .build()
+        .unwrap();
+    assert_eq!(schema.tables[0].name, "Audit");
+    assert_eq!(names(&schema.tables[0]), vec!["entryId"]);
+}
+
+#[test]
+fn test_strategy_names_variant_tables() {
+    let options = ConversionOptions {
+        naming: Arc::new(Affixed {
+            inner: SnakeCase,
+            prefix: "app_".to_string(),
+            suffix: String::new(),
+        }),
+        ..Default::default()
+    };
+    let schema = SchemaBuilder::new()
+        .register(PaymentMethod::SHAPE)
+        .options(options)
+        .build()
+        .unwrap();
+    let mut tables: Vec<_> = schema.tables.iter().map(|t| t.name.as_str()).collect();
+    tables.sort();
+    assert_eq!(
+        tables,
+        vec![
+            "app_payment_method",
+            "app_payment_method_bank_transfer",
+            "app_payment_method_credit_card",
+        ]
+    );
+    let main = schema
+        .tables
+        .iter()
+        .find(|t| t.name == "app_payment_method")
+        .unwrap();
+    assert_eq!(
+        names(main),
+        vec!["id", "discriminant", "bank_transfer_id", "credit_card_id"]
+    );
+}
+
+#[derive(Facet, Debug, PartialEq)]
+#[repr(u8)]
+enum OrderStatus {
+    InProgress,
+    OnHold,
+}
+
+#[derive(Facet, Debug, PartialEq)]
+#[facet(rename = "carrier_kind", rename_all = "kebab-case")]
+#[repr(u8)]
+enum CarrierKind {
+    ParcelService,
+    Courier,
+}
+
+#[derive(Facet, Debug, PartialEq)]
+struct ShippingAddress {
+    street: String,
+}
+
+#[derive(Facet, Debug, PartialEq)]
+struct Shipment {
+    #[facet(psql::primary_key)]
+    id: i64,
+    status: OrderStatus,
+    carrier: CarrierKind,
+    #[facet(psql::composite)]
+    address: ShippingAddress,
+}
+
+fn snake_case() -> ConversionOptions {
+    ConversionOptions {
+        naming: Arc::new(SnakeCase),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_strategy_names_types_and_labels() {
+    let schema = SchemaBuilder::new()
+        .register(Shipment::SHAPE)
+        .options(snake_case())
+        .build()
+        .unwrap();
+    let sql = schema.to_ddl("public");
+    println!("{}", sql);
+    assert!(sql.contains("CREATE TYPE public.order_status AS ENUM ('in_progress', 'on_hold');"));
+    // facet's rename and rename_all are kept as written
+    assert!(sql.contains("CREATE TYPE public.carrier_kind AS ENUM ('parcel-service', 'courier');"));
+    assert!(sql.contains("CREATE TYPE public.shipping_address AS (street text);"));
+
+    // Rows use the same labels
+    let shipment = Shipment {
+        id: 1,
+        status: OrderStatus::OnHold,
+        carrier: CarrierKind::ParcelService,
+        address: ShippingAddress {
+            street: "Main St".into(),
+        },
+    };
+    let values = RowEncoder::new()
+        .options(snake_case())
+        .values(&shipment)
+        .unwrap();
+    assert_eq!(values[1].1, sea_query::Value::from("on_hold"));
+    assert_eq!(values[2].1, sea_query::Value::from("parcel-service"));
+    let decoded: Shipment = RowDecoder::new()
+        .options(snake_case())
+        .decode(&values)
+        .unwrap();
+    assert_eq!(decoded, shipment);
+}
@@ -0,0 +1,173 @@
+use facet::Facet;
+use facet_psql_schema as psql;
+use facet_psql_schema::*;
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[repr(u8)]
+enum Mood {
+    Happy,
+    Sad,
+    SoSo,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[repr(u8)]
+#[facet(psql::as_integer)]
+enum Priority {
+    Low,
+    High,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Entry {
+    #[facet(psql::primary_key)]
+    id: i64,
+    mood: Mood,
+    previous_mood: Option<Mood>,
+    #[facet(psql::as_integer)]
+    legacy_mood: Mood,
+    priority: Priority,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Journal {
+    #[facet(psql::primary_key)]
+    id: i64,
+    mood: Mood,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[repr(u8)]
+enum Stage {
+    #[facet(rename = "in-progress")]
+    InProgress,
+    Done,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Task {
+    #[facet(psql::primary_key)]
+    id: i64,
+    stage: Stage,
+}
+
+mod other {
+    use super::*;
+
+    #[allow(dead_code)]
+    #[derive(Facet)]
+    #[repr(u8)]
+    pub enum Mood {
+        Calm,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Facet)]
+    pub struct Diary {
+        pub mood: Mood,
+    }
+}
+
+fn column<'a>(table: &'a Table, name: &str) -> &'a Column {
+    table.columns.iter().find(|c| c.name == name).unwrap()
+}
+
+#[test]
+fn test_unit_enum_fields_become_pg_enums() {
+    let schema = PartialSchema::try_from(Entry::SHAPE).expect("Failed to convert Entry");
+
+    assert_eq!(schema.enums.len(), 1, "Mood should be declared once");
+    assert_eq!(schema.enums[0].name, "mood");
+    assert_eq!(schema.enums[0].variants, vec!["happy", "sad", "soso"]);
+
+    let table = &schema.tables[0];
+    let mood = column(table, "mood");
+    assert!(matches!(&mood.data_type, DataType::Enum { name, .. } if name == "mood"));
+    assert!(!mood.nullable);
+
+    let previous = column(table, "previous_mood");
+    assert!(matches!(&previous.data_type, DataType::Enum { name, .. } if name == "mood"));
+    assert!(previous.nullable);
+
+    let sql = schema.to_ddl("public");
+    println!("{}", sql);
+    let create_type = sql
+        .find("CREATE TYPE public.mood AS ENUM ('happy', 'sad', 'soso');")
+        .expect("Missing CREATE TYPE");
+    let create_table = sql.find("CREATE TABLE public.entry").unwrap();
+    assert!(create_type < create_table);
+    assert!(sql.contains("mood public.mood NOT NULL, previous_mood public.mood,"));
+}
+
+#[test]
+fn test_integer_encoding_can_be_kept() {
+    let schema = PartialSchema::try_from(Entry::SHAPE).expect("Failed to convert Entry");
+    let table = &schema.tables[0];
+
+    // Opted out on the field
+    assert!(matches!(
+        column(table, "legacy_mood").data_type,
+        DataType::Integer
+    ));
+    // Opted out on the enum
+    assert!(matches!(
+        column(table, "priority").data_type,
+        DataType::Integer
+    ));
+    assert!(!schema.enums.iter().any(|e| e.name == "priority"));
+}
+
+#[test]
+fn test_enum_types_are_shared_and_checked() {
+    let schema = SchemaBuilder::new()
+        .register(Entry::SHAPE)
+        .register(Journal::SHAPE)
+        .build()
+        .expect("Failed to build schema");
+    assert_eq!(schema.enums.len(), 1);
+
+    let err = SchemaBuilder::new()
+        .register(Journal::SHAPE)
+        .register(other::Diary::SHAPE)
+        .build()
+        .err()
+        .unwrap();
+    assert!(matches!(err, ConversionError::NameCollision(_)));
+}
+
+#[test]
+fn test_renamed_variants_keep_their_facet_name() {
+    let schema = PartialSchema::try_from(Task::SHAPE).expect("Failed to convert Task");
+    assert_eq!(schema.enums[0].variants, vec!["in-progress", "done"]);
+
+    let sql = schema.to_ddl("public");
+    println!("{}", sql);
+    assert!(sql.contains("CREATE TYPE public.stage AS ENUM ('in-progress', 'done');"));
+}
+
+#[test]
+fn test_enum_types_live_in_the_target_schema() {
+    let schema = PartialSchema::try_from(Entry::SHAPE).unwrap();
+    let ddl = schema.to_ddl("app");
+    println!("{}", ddl);
+    assert!(ddl.contains("CREATE TYPE app.mood AS ENUM ('happy', 'sad', 'soso');"));
+    assert!(ddl.contains("mood app.mood NOT NULL, previous_mood app.mood,"));
+
+    let entry = Entry {
+        id: 1,
+        mood: Mood::Sad,
+        previous_mood: None,
+        legacy_mood: Mood::Happy,
+        priority: Priority::High,
+    };
+    let insert = RowEncoder::new().insert(&entry, "app").unwrap();
+    let sql = insert.to_string(sea_query::PostgresQueryBuilder);
+    println!("{}", sql);
+    assert!(sql.contains("CAST('sad' AS app.mood)"));
+}
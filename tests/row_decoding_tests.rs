@@ -0,0 +1,245 @@
+use std::collections::{BTreeSet, HashMap};
+
+use facet::Facet;
+use facet_psql_schema as psql;
+use facet_psql_schema::*;
+use sea_query::Value;
+
+#[derive(Facet, Debug, PartialEq)]
+#[repr(u8)]
+enum Mood {
+    Happy,
+    Sad,
+}
+
+#[derive(Facet, Debug, PartialEq)]
+#[repr(C)]
+#[allow(dead_code)]
+enum Shape {
+    Circle { radius: f64 },
+    Square { side: f64 },
+}
+
+#[derive(Facet, Debug, PartialEq)]
+#[repr(u8)]
+enum Status {
+    Draft = 1,
+    Published = 5,
+}
+
+#[derive(Facet, Debug, PartialEq)]
+struct Address {
+    street: String,
+    zip: Option<String>,
+}
+
+#[derive(Facet, Debug, PartialEq)]
+struct Profile {
+    #[facet(psql::primary_key)]
+    id: i64,
+    name: String,
+    nickname: Option<String>,
+    age: u16,
+    score: f32,
+    initial: char,
+    active: bool,
+    tags: Vec<String>,
+    maybe_scores: Vec<Option<i32>>,
+    rgb: [i16; 3],
+    set: BTreeSet<i32>,
+    mood: Mood,
+    #[facet(psql::as_integer)]
+    legacy_mood: Mood,
+    status: Status,
+    attributes: HashMap<String, i32>,
+    address: Address,
+    drawings: HashMap<String, Vec<Shape>>,
+    #[facet(psql::composite)]
+    home: Option<Address>,
+    boxed: Box<i64>,
+}
+
+#[derive(Facet, Debug, PartialEq)]
+struct Parcel {
+    label: String,
+    sizes: Vec<i32>,
+    notes: Vec<Option<String>>,
+    moods: Vec<Mood>,
+    #[facet(skip)]
+    cache: Vec<String>,
+    #[facet(psql::skip)]
+    weight: Option<f64>,
+}
+
+#[derive(Facet, Debug, PartialEq)]
+struct Shipment {
+    #[facet(psql::primary_key)]
+    id: i64,
+    #[facet(psql::composite)]
+    parcel: Parcel,
+}
+
+#[derive(Facet, Debug, PartialEq)]
+struct Counter {
+    value: u8,
+    label: String,
+}
+
+fn sample() -> Profile {
+    Profile {
+        id: 7,
+        name: "Ada".into(),
+        nickname: None,
+        age: 36,
+        score: 1.5,
+        initial: 'A',
+        active: true,
+        tags: vec!["a".into(), "b".into()],
+        maybe_scores: vec![Some(1), None],
+        rgb: [1, 2, 3],
+        set: BTreeSet::from([3, 1]),
+        mood: Mood::Sad,
+        legacy_mood: Mood::Sad,
+        status: Status::Published,
+        attributes: HashMap::from([("k".to_string(), 1)]),
+        address: Address {
+            street: "Main St".into(),
+            zip: None,
+        },
+        drawings: HashMap::from([(
+            "d".to_string(),
+            vec![Shape::Circle { radius: 1.0 }, Shape::Square { side: 2.0 }],
+        )]),
+        home: Some(Address {
+            street: "Elm \"St\", (2)".into(),
+            zip: Some("1234".into()),
+        }),
+        boxed: Box::new(5),
+    }
+}
+
+#[test]
+fn test_encoded_rows_decode_to_the_same_value() {
+    let values = RowEncoder::new()
+        .values(&sample())
+        .expect("Failed to encode");
+    let decoded: Profile = RowDecoder::new().decode(&values).expect("Failed to decode");
+    assert_eq!(decoded, sample());
+}
+
+#[test]
+fn test_decode_from_column_map() {
+    let mut row = HashMap::from([
+        ("value".to_string(), Value::SmallInt(Some(3))),
+        (
+            "label".to_string(),
+            Value::String(Some(Box::new("x".into()))),
+        ),
+    ]);
+    let counter: Counter = RowDecoder::new().decode(&row).expect("Failed to decode");
+    assert_eq!(
+        counter,
+        Counter {
+            value: 3,
+            label: "x".into()
+        }
+    );
+
+    row.insert("value".into(), Value::SmallInt(Some(300)));
+    let err = RowDecoder::new()
+        .decode::<Counter, _>(&row)
+        .expect_err("300 does not fit u8");
+    assert!(matches!(&err, RowError::OutOfRange { field, .. } if field == "value"));
+
+    row.insert("value".into(), Value::SmallInt(Some(3)));
+    row.remove("label");
+    let err = RowDecoder::new()
+        .decode::<Counter, _>(&row)
+        .expect_err("label is missing");
+    assert!(matches!(&err, RowError::Missing { field } if field == "label"));
+}
+
+#[test]
+fn test_mismatches_name_the_field() {
+    let mut values = RowEncoder::new().values(&sample()).unwrap();
+    let set = |values: &mut Vec<(String, Value)>, column: &str, value: Value| {
+        values.iter_mut().find(|(c, _)| c == column).unwrap().1 = value;
+    };
+
+    let mut wrong = values.clone();
+    set(&mut wrong, "name", Value::Int(Some(1)));
+    let err = RowDecoder::new()
+        .decode::<Profile, _>(&wrong)
+        .expect_err("integer for a string");
+    assert!(matches!(&err, RowError::Mismatch { field, found, .. }
+        if field == "name" && found == "integer"));
+
+    let mut wrong = values.clone();
+    set(&mut wrong, "age", Value::SmallInt(None));
+    let err = RowDecoder::new()
+        .decode::<Profile, _>(&wrong)
+        .expect_err("NULL for a required field");
+    assert!(matches!(&err, RowError::Mismatch { field, found, .. }
+        if field == "age" && found == "NULL"));
+
+    let mut wrong = values.clone();
+    set(
+        &mut wrong,
+        "address",
+        Value::Json(Some(Box::new(serde_json::json!({ "street": 1 })))),
+    );
+    let err = RowDecoder::new()
+        .decode::<Profile, _>(&wrong)
+        .expect_err("number for a nested string");
+    println!("{}", err);
+    assert!(matches!(&err, RowError::Mismatch { field, .. } if field == "address.street"));
+
+    set(
+        &mut values,
+        "mood",
+        Value::String(Some(Box::new("angry".into()))),
+    );
+    let err = RowDecoder::new()
+        .decode::<Profile, _>(&values)
+        .expect_err("unknown label");
+    assert!(matches!(&err, RowError::Mismatch { field, .. } if field == "mood"));
+}
+
+#[test]
+fn test_composites_with_arrays_round_trip() {
+    let shipment = Shipment {
+        id: 1,
+        parcel: Parcel {
+            label: "box, \"small\"".into(),
+            sizes: vec![1, 2],
+            notes: vec![Some("a {b}".into()), None, Some("NULL".into())],
+            moods: vec![Mood::Sad, Mood::Happy],
+            cache: vec![],
+            weight: None,
+        },
+    };
+
+    let schema = PartialSchema::try_from(Shipment::SHAPE).expect("Failed to convert");
+    let parcel = &schema.composite_types[0];
+    let fields: Vec<_> = parcel.fields.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(fields, ["label", "sizes", "notes", "moods"]);
+
+    let values = RowEncoder::new()
+        .values(&shipment)
+        .expect("Failed to encode");
+    let decoded: Shipment = RowDecoder::new().decode(&values).expect("Failed to decode");
+    assert_eq!(decoded, shipment);
+
+    // The text form PostgreSQL itself returns for the record
+    let row = vec![
+        ("id".to_string(), Value::BigInt(Some(1))),
+        (
+            "parcel".to_string(),
+            Value::String(Some(Box::new(
+                r#"("box, \"small\"","{1,2}","{""a {b}"",NULL,""NULL""}","{sad,happy}")"#.into(),
+            ))),
+        ),
+    ];
+    let decoded: Shipment = RowDecoder::new().decode(&row).expect("Failed to decode");
+    assert_eq!(decoded, shipment);
+}
@@ -0,0 +1,250 @@
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap};
+
+use facet::Facet;
+use facet_psql_schema as psql;
+use facet_psql_schema::*;
+use sea_query::{PostgresQueryBuilder, Value};
+
+#[derive(Facet)]
+#[repr(u8)]
+enum Mood {
+    Happy,
+    Sad,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[repr(C)]
+#[facet(psql::storage = "jsonb")]
+enum Shape {
+    Circle { radius: f64 },
+    Square { side: f64 },
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[repr(C)]
+enum Figure {
+    Dot,
+    Line { length: f64 },
+}
+
+#[derive(Facet)]
+struct Sketch {
+    figure: Figure,
+}
+
+#[derive(Facet)]
+struct Address {
+    street: String,
+    zip: Option<String>,
+}
+
+#[derive(Facet)]
+struct Profile {
+    #[facet(psql::primary_key)]
+    id: i64,
+    name: String,
+    nickname: Option<String>,
+    age: u16,
+    score: f32,
+    initial: char,
+    active: bool,
+    tags: Vec<String>,
+    maybe_scores: Vec<Option<i32>>,
+    mood: Mood,
+    #[facet(psql::as_integer)]
+    legacy_mood: Mood,
+    shape: Shape,
+    attributes: HashMap<String, i32>,
+    address: Address,
+    #[facet(psql::composite)]
+    home: Option<Address>,
+    boxed: Box<i64>,
+}
+
+#[derive(Facet)]
+struct Borrowed {
+    name: &'static str,
+    cow: Cow<'static, str>,
+    codes: &'static [i64],
+    labels: Box<[String]>,
+    rgb: [i16; 3],
+    grid: Vec<Vec<i32>>,
+    set: BTreeSet<i32>,
+}
+
+#[derive(Facet)]
+struct Counter {
+    value: u64,
+}
+
+fn sample() -> Profile {
+    Profile {
+        id: 7,
+        name: "Ada".into(),
+        nickname: None,
+        age: 36,
+        score: 1.5,
+        initial: 'A',
+        active: true,
+        tags: vec!["a".into(), "b".into()],
+        maybe_scores: vec![Some(1), None],
+        mood: Mood::Sad,
+        legacy_mood: Mood::Sad,
+        shape: Shape::Square { side: 2.0 },
+        attributes: HashMap::from([("k".to_string(), 1)]),
+        address: Address {
+            street: "Main St".into(),
+            zip: None,
+        },
+        home: Some(Address {
+            street: "Elm \"St\"".into(),
+            zip: Some("1234".into()),
+        }),
+        boxed: Box::new(5),
+    }
+}
+
+fn value<'a>(values: &'a [(String, Value)], column: &str) -> &'a Value {
+    &values.iter().find(|(c, _)| c == column).unwrap().1
+}
+
+#[test]
+fn test_values_match_derived_columns() {
+    let table = PartialSchema::try_from(Profile::SHAPE)
+        .unwrap()
+        .tables
+        .remove(0);
+    let values = RowEncoder::new()
+        .values(&sample())
+        .expect("Failed to encode");
+
+    let columns: Vec<_> = values.iter().map(|(c, _)| c.as_str()).collect();
+    let expected: Vec<_> = table.columns.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(columns, expected);
+
+    assert_eq!(value(&values, "id"), &Value::BigInt(Some(7)));
+    assert_eq!(
+        value(&values, "name"),
+        &Value::String(Some(Box::new("Ada".into())))
+    );
+    assert_eq!(value(&values, "nickname"), &Value::String(None));
+    assert_eq!(value(&values, "age"), &Value::SmallInt(Some(36)));
+    assert_eq!(value(&values, "score"), &Value::Float(Some(1.5)));
+    assert_eq!(
+        value(&values, "initial"),
+        &Value::String(Some(Box::new("A".into())))
+    );
+    assert_eq!(value(&values, "active"), &Value::Bool(Some(true)));
+    assert_eq!(value(&values, "boxed"), &Value::BigInt(Some(5)));
+}
+
+#[test]
+fn test_structured_values() {
+    let values = RowEncoder::new()
+        .values(&sample())
+        .expect("Failed to encode");
+
+    match value(&values, "tags") {
+        Value::Array(_, Some(items)) => assert_eq!(
+            **items,
+            vec![
+                Value::String(Some(Box::new("a".into()))),
+                Value::String(Some(Box::new("b".into())))
+            ]
+        ),
+        other => panic!("Expected array, got {:?}", other),
+    }
+    match value(&values, "maybe_scores") {
+        Value::Array(_, Some(items)) => {
+            assert_eq!(**items, vec![Value::Int(Some(1)), Value::Int(None)])
+        }
+        other => panic!("Expected array, got {:?}", other),
+    }
+
+    // Unit-only enums use their PostgreSQL label, or the discriminant when opted out
+    assert_eq!(
+        value(&values, "mood"),
+        &Value::String(Some(Box::new("sad".into())))
+    );
+    assert_eq!(value(&values, "legacy_mood"), &Value::Int(Some(1)));
+    // Enums with data keep their payload as JSONB
+    match value(&values, "shape") {
+        Value::Json(Some(json)) => assert_eq!(json.to_string(), r#"{"Square":{"side":2.0}}"#),
+        other => panic!("Expected JSON, got {:?}", other),
+    }
+
+    match value(&values, "attributes") {
+        Value::Json(Some(json)) => assert_eq!(json.to_string(), r#"{"k":1}"#),
+        other => panic!("Expected JSON, got {:?}", other),
+    }
+    match value(&values, "address") {
+        Value::Json(Some(json)) => {
+            assert_eq!(json.to_string(), r#"{"street":"Main St","zip":null}"#)
+        }
+        other => panic!("Expected JSON, got {:?}", other),
+    }
+    assert_eq!(
+        value(&values, "home"),
+        &Value::String(Some(Box::new(r#"("Elm \"St\"","1234")"#.into())))
+    );
+}
+
+#[test]
+fn test_insert_statement() {
+    let insert = RowEncoder::new()
+        .insert(&sample(), "public")
+        .expect("Failed to build insert");
+    let sql = insert.to_string(PostgresQueryBuilder);
+    println!("{}", sql);
+
+    assert!(sql.starts_with(r#"INSERT INTO "public"."profile" ("id", "name", "nickname","#));
+    assert!(sql.contains("CAST('sad' AS public.mood)"));
+    assert!(sql.contains(r#"CAST(E'(\"Elm \\\"St\\\"\",\"1234\")' AS public.address)"#));
+    assert!(sql.contains("ARRAY ['a','b']"));
+
+    let (_, values) = insert.build(PostgresQueryBuilder);
+    assert_eq!(values.0.len(), 16);
+}
+
+#[test]
+fn test_out_of_range_values_name_the_field() {
+    let err = RowEncoder::new()
+        .values(&Counter { value: u64::MAX })
+        .expect_err("u64::MAX does not fit bigint");
+    assert!(matches!(&err, RowError::OutOfRange { field, .. } if field == "value"));
+    assert!(RowEncoder::new().values(&Counter { value: 1 }).is_ok());
+}
+
+#[test]
+fn test_enums_with_data_are_not_reduced_to_their_discriminant() {
+    for figure in [Figure::Dot, Figure::Line { length: 2.0 }] {
+        let err = RowEncoder::new()
+            .values(&Sketch { figure })
+            .expect_err("An integer column cannot hold the variant's fields");
+        assert!(matches!(&err, RowError::Unsupported { field, .. } if field == "figure"));
+    }
+}
+
+#[test]
+fn test_borrowed_and_fixed_size_values() {
+    let row = Borrowed {
+        name: "n",
+        cow: Cow::Borrowed("c"),
+        codes: &[1, 2],
+        labels: vec!["x".to_string()].into_boxed_slice(),
+        rgb: [1, 2, 3],
+        grid: vec![vec![1], vec![2]],
+        set: BTreeSet::from([3, 1]),
+    };
+    let insert = RowEncoder::new()
+        .insert(&row, "public")
+        .expect("Failed to build insert");
+    let sql = insert.to_string(PostgresQueryBuilder);
+    println!("{}", sql);
+    assert!(sql.ends_with(
+        "VALUES ('n', 'c', ARRAY [1,2], ARRAY ['x'], ARRAY [1,2,3], ARRAY [ARRAY [1],ARRAY [2]], ARRAY [1,3])"
+    ));
+}
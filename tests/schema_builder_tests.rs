@@ -0,0 +1,90 @@
+use facet::Facet;
+use facet_psql_schema as psql;
+use facet_psql_schema::*;
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Customer {
+    #[facet(psql::primary_key)]
+    id: i64,
+    name: String,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Invoice {
+    #[facet(psql::primary_key)]
+    id: i64,
+    customer_id: i64,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[repr(u8)]
+enum Payment {
+    Card { last_four: String },
+    Transfer { iban: String },
+}
+
+mod other {
+    use super::*;
+
+    /// Maps to the same table name as the top-level `Customer`.
+    #[allow(dead_code)]
+    #[derive(Facet)]
+    pub struct Customer {
+        pub id: i64,
+    }
+}
+
+#[test]
+fn test_builder_merges_shapes() {
+    let schema = SchemaBuilder::new()
+        .register(Customer::SHAPE)
+        .register(Invoice::SHAPE)
+        .register(Payment::SHAPE)
+        .build()
+        .expect("Failed to build schema");
+
+    let names: Vec<&str> = schema.tables.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "customer",
+            "invoice",
+            "payment_card",
+            "payment_transfer",
+            "payment"
+        ]
+    );
+}
+
+#[test]
+fn test_builder_deduplicates_repeated_shapes() {
+    let schema = SchemaBuilder::new()
+        .register(Payment::SHAPE)
+        .register(Customer::SHAPE)
+        .register(Payment::SHAPE)
+        .register(Customer::SHAPE)
+        .build()
+        .expect("Registering a shape twice should not fail");
+
+    assert_eq!(schema.tables.len(), 4);
+}
+
+#[test]
+fn test_builder_detects_name_collisions() {
+    let result = SchemaBuilder::new()
+        .register(Customer::SHAPE)
+        .register(other::Customer::SHAPE)
+        .build();
+
+    match result {
+        Err(ConversionError::NameCollision(msg)) => {
+            println!("{}", msg);
+            assert!(msg.contains("'customer'"), "Message should name the table");
+        }
+        Err(e) => panic!("Expected NameCollision, got {}", e),
+        Ok(_) => panic!("Expected NameCollision, got a schema"),
+    }
+}
@@ -0,0 +1,327 @@
+use facet::Facet;
+use facet_psql_schema as psql;
+use facet_psql_schema::*;
+
+mod v1 {
+    use super::*;
+
+    #[allow(dead_code)]
+    #[derive(Facet)]
+    pub struct Account {
+        #[facet(psql::primary_key)]
+        pub id: i64,
+        pub email: String,
+        pub age: i32,
+        pub nickname: String,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Facet)]
+    pub struct Legacy {
+        pub id: i64,
+    }
+}
+
+mod v2 {
+    use super::*;
+
+    #[allow(dead_code)]
+    #[derive(Facet)]
+    pub struct Account {
+        #[facet(psql::primary_key)]
+        pub id: i64,
+        pub email: String,
+        pub age: i64,
+        pub nickname: Option<String>,
+        pub created_at: i64,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Facet)]
+    pub struct Session {
+        #[facet(psql::primary_key)]
+        pub id: i64,
+        pub account_id: i64,
+    }
+}
+
+mod moods {
+    use super::*;
+
+    #[allow(dead_code)]
+    #[derive(Facet)]
+    #[repr(u8)]
+    pub enum Mood {
+        Ok,
+        Sad,
+        Happy,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Facet)]
+    pub struct Checkin {
+        pub mood: Mood,
+        pub note: String,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Facet)]
+    pub struct Entry {
+        #[facet(psql::primary_key)]
+        pub id: i64,
+        pub mood: Mood,
+        pub moods: Vec<Mood>,
+        #[facet(psql::composite)]
+        pub checkin: Checkin,
+    }
+}
+
+fn schema_of(tables: Vec<Table>) -> PartialSchema {
+    PartialSchema {
+        tables,
+        views: vec![],
+        materialized_views: vec![],
+        enums: vec![],
+        domains: vec![],
+        composite_types: vec![],
+        sequences: vec![],
+        collations: vec![],
+        functions: vec![],
+    }
+}
+
+fn table_of(shape: &'static facet::Shape) -> Table {
+    PartialSchema::try_from(shape)
+        .expect("Failed to convert shape")
+        .tables
+        .remove(0)
+}
+
+fn mood(variants: &[&str]) -> EnumType {
+    EnumType {
+        schema: None,
+        name: "mood".into(),
+        variants: variants.iter().map(|v| v.to_string()).collect(),
+        comment: None,
+    }
+}
+
+#[test]
+fn test_identical_schemas_have_no_changes() {
+    let a = schema_of(vec![table_of(v1::Account::SHAPE)]);
+    let b = schema_of(vec![table_of(v1::Account::SHAPE)]);
+    assert!(PartialSchema::diff(&a, &b).is_empty());
+}
+
+#[test]
+fn test_table_and_column_changes() {
+    let old = schema_of(vec![
+        table_of(v1::Account::SHAPE),
+        table_of(v1::Legacy::SHAPE),
+    ]);
+    let new = schema_of(vec![
+        table_of(v2::Account::SHAPE),
+        table_of(v2::Session::SHAPE),
+    ]);
+
+    let changes = PartialSchema::diff(&old, &new);
+
+    assert!(changes.iter().any(|c| matches!(c,
+        SchemaChange::AlterColumnType { table, column, to: DataType::BigInt, .. }
+            if table == "account" && column == "age")));
+    assert!(changes.iter().any(|c| matches!(c,
+        SchemaChange::AlterColumnNullability { column, nullable: true, .. } if column == "nickname")));
+    assert!(changes.iter().any(|c| matches!(c,
+        SchemaChange::AddColumn { column, .. } if column.name == "created_at")));
+    assert!(changes.iter().any(|c| matches!(c,
+        SchemaChange::CreateTable(t) if t.name == "session")));
+    assert!(changes.iter().any(|c| matches!(c,
+        SchemaChange::DropTable(t) if t.name == "legacy")));
+    assert_eq!(changes.len(), 5, "Unchanged columns should not be reported");
+
+    let sql = PartialSchema::migration_ddl(&changes, "public");
+    println!("{}", sql);
+    assert!(
+        sql.contains("ALTER TABLE public.account ALTER COLUMN age TYPE bigint USING age::bigint;")
+    );
+    assert!(sql.contains("ALTER TABLE public.account ALTER COLUMN nickname DROP NOT NULL;"));
+    assert!(sql.contains("ALTER TABLE public.account ADD COLUMN created_at bigint NOT NULL;"));
+    assert!(sql.contains("CREATE TABLE public.session"));
+    assert!(sql.contains("DROP TABLE public.legacy;"));
+}
+
+#[test]
+fn test_constraint_and_index_changes_are_ordered() {
+    let mut old_account = table_of(v1::Account::SHAPE);
+    old_account.uniques.push(UniqueConstraint {
+        name: None,
+        columns: vec!["nickname".into()],
+        deferrable: None,
+    });
+    let old = schema_of(vec![old_account, table_of(v2::Session::SHAPE)]);
+
+    let mut new_account = table_of(v1::Account::SHAPE);
+    new_account.checks.push(CheckConstraint {
+        name: Some("age_positive".into()),
+        expression: "age > 0".into(),
+        no_inherit: false,
+    });
+    new_account.indexes.push(Index {
+        name: String::new(),
+        columns: vec![IndexColumn {
+            expr: IndexExpr::Column("email".into()),
+            collate: None,
+            opclass: None,
+            order: None,
+            nulls_order: None,
+        }],
+        unique: true,
+        method: None,
+        predicate: None,
+        include: vec![],
+        tablespace: None,
+        concurrently: false,
+        is_primary: false,
+        is_valid: true,
+    });
+    let mut session = table_of(v2::Session::SHAPE);
+    session.foreign_keys.push(ForeignKey {
+        name: None,
+        columns: vec!["account_id".into()],
+        referenced_table: QualifiedName {
+            schema: None,
+            name: "account".into(),
+        },
+        referenced_columns: Some(vec!["id".into()]),
+        on_delete: Some(ReferentialAction::Cascade),
+        on_update: None,
+        match_type: None,
+        deferrable: None,
+        initially: None,
+    });
+    let new = schema_of(vec![new_account, session]);
+
+    let changes = PartialSchema::diff(&old, &new);
+    let sql = PartialSchema::migration_ddl(&changes, "public");
+    println!("{}", sql);
+
+    let drop_unique = sql
+        .find("ALTER TABLE public.account DROP CONSTRAINT account_nickname_key;")
+        .expect("Missing unique drop");
+    let add_check = sql
+        .find("ALTER TABLE public.account ADD CONSTRAINT age_positive CHECK (age > 0);")
+        .expect("Missing check");
+    let create_index = sql
+        .find("CREATE UNIQUE INDEX account_idx_email ON public.account USING btree (email);")
+        .expect("Missing index");
+    let add_fk = sql
+        .find("ALTER TABLE public.session ADD CONSTRAINT session_account_id_fkey FOREIGN KEY (account_id) REFERENCES public.account (id) ON DELETE CASCADE;")
+        .expect("Missing FK");
+
+    assert!(drop_unique < add_check, "Drops should come first");
+    assert!(add_check < create_index, "Indexes follow constraints");
+    assert!(create_index < add_fk, "Foreign keys come last");
+
+    // And back again
+    let back = PartialSchema::migration_ddl(&PartialSchema::diff(&new, &old), "public");
+    println!("{}", back);
+    assert!(back.contains("ALTER TABLE public.session DROP CONSTRAINT session_account_id_fkey;"));
+    assert!(back.contains("DROP INDEX public.account_idx_email;"));
+    assert!(back.contains("ALTER TABLE public.account DROP CONSTRAINT age_positive;"));
+}
+
+#[test]
+fn test_enum_value_changes() {
+    let mut old = schema_of(vec![]);
+    old.enums.push(mood(&["ok", "sad"]));
+    let mut new = schema_of(vec![]);
+    new.enums
+        .push(mood(&["new_first", "ok", "meh", "sad", "happy"]));
+
+    let changes = PartialSchema::diff(&old, &new);
+    let sql = PartialSchema::migration_ddl(&changes, "public");
+    println!("{}", sql);
+    assert_eq!(
+        sql,
+        "ALTER TYPE public.mood ADD VALUE 'new_first' BEFORE 'ok';\n\
+         ALTER TYPE public.mood ADD VALUE 'meh' AFTER 'ok';\n\
+         ALTER TYPE public.mood ADD VALUE 'happy' AFTER 'sad';"
+    );
+}
+
+#[test]
+fn test_dropping_enum_values_rebuilds_the_type() {
+    let mut feeling = table_of(v2::Session::SHAPE);
+    feeling.columns[1].data_type = DataType::Enum {
+        schema: None,
+        name: "mood".into(),
+    };
+
+    let mut old = schema_of(vec![feeling.clone()]);
+    old.enums.push(mood(&["ok", "sad", "happy"]));
+    let mut new = schema_of(vec![feeling]);
+    new.enums.push(mood(&["ok", "happy"]));
+
+    let changes = PartialSchema::diff(&old, &new);
+    assert!(matches!(&changes[..],
+        [SchemaChange::DropEnumValues { dropped, columns, .. }]
+            if dropped == &vec!["sad".to_string()] && columns.len() == 1));
+
+    let sql = PartialSchema::migration_ddl(&changes, "public");
+    println!("{}", sql);
+    assert_eq!(
+        sql,
+        "ALTER TYPE public.mood RENAME TO mood__old;\n\
+         CREATE TYPE public.mood AS ENUM ('ok', 'happy');\n\
+         ALTER TABLE public.session ALTER COLUMN account_id TYPE public.mood USING account_id::text::public.mood;\n\
+         DROP TYPE public.mood__old;"
+    );
+}
+
+#[test]
+fn test_dropping_enum_values_casts_arrays_and_composites() {
+    let old = PartialSchema::try_from(moods::Entry::SHAPE).expect("Failed to convert shape");
+    let mut new = PartialSchema::try_from(moods::Entry::SHAPE).expect("Failed to convert shape");
+    new.enums = vec![mood(&["ok", "happy"])];
+
+    let changes = PartialSchema::diff(&old, &new);
+    assert!(matches!(&changes[..],
+        [SchemaChange::DropEnumValues { composites, columns, .. }]
+            if composites.len() == 1 && columns.len() == 3));
+
+    let sql = PartialSchema::migration_ddl(&changes, "public");
+    println!("{}", sql);
+    assert_eq!(
+        sql,
+        "ALTER TYPE public.mood RENAME TO mood__old;\n\
+         CREATE TYPE public.mood AS ENUM ('ok', 'happy');\n\
+         ALTER TABLE public.entry ALTER COLUMN checkin TYPE text USING checkin::text;\n\
+         ALTER TYPE public.checkin ALTER ATTRIBUTE mood TYPE public.mood;\n\
+         ALTER TABLE public.entry ALTER COLUMN mood TYPE public.mood USING mood::text::public.mood;\n\
+         ALTER TABLE public.entry ALTER COLUMN moods TYPE public.mood[] USING moods::text[]::public.mood[];\n\
+         ALTER TABLE public.entry ALTER COLUMN checkin TYPE public.checkin USING checkin::public.checkin;\n\
+         DROP TYPE public.mood__old;"
+    );
+}
+
+#[test]
+fn test_custom_primary_key_name_is_created_and_dropped() {
+    let mut old_account = table_of(v1::Account::SHAPE);
+    old_account.primary_key.as_mut().unwrap().name = Some("account_id_pk".into());
+    let old = schema_of(vec![old_account]);
+
+    let ddl = old.to_ddl("public");
+    println!("{}", ddl);
+    assert!(ddl.contains("CONSTRAINT account_id_pk PRIMARY KEY (id));"));
+
+    let mut new_account = table_of(v1::Account::SHAPE);
+    new_account.primary_key.as_mut().unwrap().columns = vec!["email".into()];
+    let new = schema_of(vec![new_account]);
+
+    let sql = PartialSchema::migration_ddl(&PartialSchema::diff(&old, &new), "public");
+    println!("{}", sql);
+    assert!(sql.contains("ALTER TABLE public.account DROP CONSTRAINT account_id_pk;"));
+    assert!(
+        sql.contains("ALTER TABLE public.account ADD CONSTRAINT account_pkey PRIMARY KEY (email);")
+    );
+}
@@ -0,0 +1,214 @@
+use facet::Facet;
+use facet_psql_schema as psql;
+use facet_psql_schema::*;
+use sea_query::PostgresQueryBuilder;
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Author {
+    #[facet(psql::primary_key)]
+    id: i64,
+    email: String,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Book {
+    #[facet(psql::primary_key)]
+    id: i64,
+    author_id: i64,
+    title: Option<String>,
+}
+
+fn empty_schema() -> PartialSchema {
+    PartialSchema {
+        tables: vec![],
+        views: vec![],
+        materialized_views: vec![],
+        enums: vec![],
+        domains: vec![],
+        composite_types: vec![],
+        sequences: vec![],
+        collations: vec![],
+        functions: vec![],
+    }
+}
+
+fn library_schema() -> PartialSchema {
+    let mut author = PartialSchema::try_from(Author::SHAPE)
+        .expect("Failed to convert Author")
+        .tables
+        .remove(0);
+    let mut book = PartialSchema::try_from(Book::SHAPE)
+        .expect("Failed to convert Book")
+        .tables
+        .remove(0);
+
+    author.uniques.push(UniqueConstraint {
+        name: None,
+        columns: vec!["email".into()],
+        deferrable: None,
+    });
+    author.checks.push(CheckConstraint {
+        name: None,
+        expression: "email <> ''".into(),
+        no_inherit: false,
+    });
+    book.foreign_keys.push(ForeignKey {
+        name: None,
+        columns: vec!["author_id".into()],
+        referenced_table: QualifiedName {
+            schema: None,
+            name: "author".into(),
+        },
+        // Resolved from the referenced table's primary key
+        referenced_columns: None,
+        on_delete: Some(ReferentialAction::Cascade),
+        on_update: None,
+        match_type: None,
+        deferrable: None,
+        initially: None,
+    });
+    book.indexes.push(Index {
+        name: String::new(),
+        columns: vec![IndexColumn {
+            expr: IndexExpr::Column("title".into()),
+            collate: None,
+            opclass: None,
+            order: Some(SortOrder::Desc),
+            nulls_order: None,
+        }],
+        unique: false,
+        method: None,
+        predicate: Some("title IS NOT NULL".into()),
+        include: vec![],
+        tablespace: None,
+        concurrently: false,
+        is_primary: false,
+        is_valid: true,
+    });
+
+    let mut schema = empty_schema();
+    schema.tables = vec![author, book];
+    schema.enums.push(EnumType {
+        schema: Some("public".into()),
+        name: "genre".into(),
+        variants: vec!["fiction".into(), "non_fiction".into()],
+        comment: None,
+    });
+    schema
+}
+
+#[test]
+fn test_table_create_statement() {
+    let schema = library_schema();
+    let sql = schema.tables[0]
+        .to_create_statement("public")
+        .expect("Failed to build table statement")
+        .to_string(PostgresQueryBuilder);
+    println!("{}", sql);
+
+    assert_eq!(
+        sql,
+        r#"CREATE TABLE "public"."author" ( "id" bigint NOT NULL, "email" text NOT NULL, PRIMARY KEY ("id"), CONSTRAINT "author_email_key" UNIQUE ("email"), CHECK (email <> '') )"#
+    );
+}
+
+#[test]
+fn test_schema_statements() {
+    let statements = library_schema()
+        .to_sea_query("public")
+        .expect("Failed to build statements");
+
+    assert_eq!(statements.types.len(), 1);
+    assert_eq!(statements.tables.len(), 2);
+    assert_eq!(statements.indexes.len(), 1);
+    assert_eq!(statements.foreign_keys.len(), 1);
+
+    let ty = statements.types[0].to_string(PostgresQueryBuilder);
+    println!("{}", ty);
+    assert_eq!(
+        ty,
+        r#"CREATE TYPE "public"."genre" AS ENUM ('fiction', 'non_fiction')"#
+    );
+
+    let idx = statements.indexes[0].to_string(PostgresQueryBuilder);
+    println!("{}", idx);
+    assert_eq!(
+        idx,
+        r#"CREATE INDEX "book_idx_title" ON "public"."book" USING BTREE ("title" DESC) WHERE title IS NOT NULL"#
+    );
+
+    let fk = statements.foreign_keys[0].to_string(PostgresQueryBuilder);
+    println!("{}", fk);
+    assert_eq!(
+        fk,
+        r#"ALTER TABLE "public"."book" ADD CONSTRAINT "book_author_id_fkey" FOREIGN KEY ("author_id") REFERENCES "public"."author" ("id") ON DELETE CASCADE"#
+    );
+}
+
+#[test]
+fn test_unsupported_features_are_rejected() {
+    let mut schema = library_schema();
+    schema.tables[1].indexes[0].columns[0].nulls_order = Some(NullsOrder::Last);
+    assert!(matches!(
+        schema.to_sea_query("public"),
+        Err(StatementError::Unsupported(_))
+    ));
+
+    let mut fk = schema.tables[1].foreign_keys[0].clone();
+    fk.referenced_columns = None;
+    assert!(
+        fk.to_create_statement("public", "book").is_err(),
+        "A standalone FK needs explicit referenced columns"
+    );
+}
+
+#[test]
+fn test_named_checks_and_types_are_kept() {
+    let mut schema = library_schema();
+    schema.enums[0].schema = None;
+    schema.tables[1].columns.push(Column {
+        name: "genre".into(),
+        data_type: DataType::Enum {
+            schema: None,
+            name: "genre".into(),
+        },
+        nullable: true,
+        default: None,
+        is_identity: false,
+        identity_generation: None,
+        is_generated: false,
+        generation_expression: None,
+        collation: None,
+        comment: None,
+        privileges: None,
+    });
+    schema.tables[1].checks.push(CheckConstraint {
+        name: Some("book_title_nonempty".into()),
+        expression: "title <> ''".into(),
+        no_inherit: false,
+    });
+
+    let statements = schema
+        .to_sea_query("app")
+        .expect("Failed to build statements");
+    assert_eq!(statements.schema, "CREATE SCHEMA IF NOT EXISTS app;");
+
+    let ty = statements.types[0].to_string(PostgresQueryBuilder);
+    assert_eq!(
+        ty,
+        r#"CREATE TYPE "app"."genre" AS ENUM ('fiction', 'non_fiction')"#
+    );
+
+    let sql = statements.tables[1].to_string(PostgresQueryBuilder);
+    println!("{}", sql);
+    assert!(sql.contains(
+        r#""genre" app.genre , CONSTRAINT book_title_nonempty CHECK (title <> ''), PRIMARY KEY ("id") )"#
+    ));
+
+    // to_ddl puts the type in the same place
+    let ddl = schema.to_ddl("app");
+    assert!(ddl.contains("CREATE TYPE app.genre AS ENUM ('fiction', 'non_fiction');"));
+    assert!(ddl.contains("genre app.genre"));
+}
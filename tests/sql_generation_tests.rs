@@ -14,47 +14,18 @@ struct TableB {
 struct TableA {
     #[facet(psql::primary_key)]
     id: i64,
+    #[facet(psql::references(psql::Ref<TableB, psql::on_delete::Cascade>))]
     b_id: Option<i64>,
 }
 
 #[test]
 fn test_sql_generation_foreign_key_ordering() {
-    // Generate schemas from Facet derived shapes
-    let schema_a = PartialSchema::try_from(TableA::SHAPE).expect("Failed to convert TableA");
-    let schema_b = PartialSchema::try_from(TableB::SHAPE).expect("Failed to convert TableB");
-
-    let mut table_a = schema_a.tables.into_iter().next().unwrap();
-    let table_b = schema_b.tables.into_iter().next().unwrap();
-
-    // Manually add Foreign Key to Table A (since not yet derivable)
-    table_a.foreign_keys.push(ForeignKey {
-        name: None,
-        columns: vec!["b_id".into()],
-        referenced_table: QualifiedName {
-            schema: None,
-            name: "tableb".into(), // snake_case of TableB
-        },
-        referenced_columns: Some(vec!["id".into()]),
-        on_delete: Some(ReferentialAction::Cascade),
-        on_update: None,
-        match_type: None,
-        deferrable: None,
-        initially: None,
-    });
-
-    // Combine into one schema
     // Put A before B to test ordering logic
-    let schema = PartialSchema {
-        tables: vec![table_a, table_b],
-        views: vec![],
-        materialized_views: vec![],
-        enums: vec![],
-        domains: vec![],
-        composite_types: vec![],
-        sequences: vec![],
-        collations: vec![],
-        functions: vec![],
-    };
+    let schema = SchemaBuilder::new()
+        .register(TableA::SHAPE)
+        .register(TableB::SHAPE)
+        .build()
+        .expect("Failed to build schema");
 
     let sql = schema.to_ddl("public");
     println!("{}", sql);
@@ -65,7 +36,7 @@ fn test_sql_generation_foreign_key_ordering() {
     let create_b_idx = sql
         .find("CREATE TABLE public.tableb")
         .expect("Missing CREATE TABLE tableb");
-    let alter_fk_idx = sql.find("ALTER TABLE public.tablea ADD CONSTRAINT tablea_b_id_fkey FOREIGN KEY (b_id) REFERENCES tableb (id)").expect("Missing FK constraint");
+    let alter_fk_idx = sql.find("ALTER TABLE public.tablea ADD CONSTRAINT tablea_b_id_fkey FOREIGN KEY (b_id) REFERENCES public.tableb (id) ON DELETE CASCADE").expect("Missing FK constraint");
 
     assert!(
         alter_fk_idx > create_a_idx,
@@ -0,0 +1,144 @@
+use facet::Facet;
+use facet_psql_schema as psql;
+use facet_psql_schema::*;
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Membership {
+    #[facet(psql::primary_key)]
+    group_id: i64,
+    #[facet(psql::primary_key)]
+    user_id: i64,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[facet(
+    psql::primary_key("tenant_id, id"),
+    psql::unique("tenant_id, slug"),
+    psql::unique("tenant"),
+    psql::check("starts <= ends")
+)]
+struct Project {
+    id: i64,
+    #[facet(psql::column = "tenant")]
+    tenant_id: i64,
+    slug: String,
+    starts: i32,
+    ends: i32,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[facet(psql::unique("id, missing"))]
+struct Typo {
+    id: i64,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[facet(psql::unique)]
+struct NoColumns {
+    id: i64,
+}
+
+#[test]
+fn test_primary_key_fields_form_a_composite_key() {
+    let schema = PartialSchema::try_from(Membership::SHAPE).unwrap();
+    let pk = schema.tables[0].primary_key.as_ref().unwrap();
+    assert_eq!(pk.columns, vec!["group_id", "user_id"]);
+    assert!(schema.to_ddl("public").contains(
+        "CREATE TABLE public.membership (group_id bigint NOT NULL, user_id bigint NOT NULL, PRIMARY KEY (group_id, user_id));"
+    ));
+}
+
+#[test]
+fn test_struct_level_constraints() {
+    let schema = PartialSchema::try_from(Project::SHAPE).unwrap();
+    let table = &schema.tables[0];
+    assert_eq!(
+        table.primary_key.as_ref().unwrap().columns,
+        vec!["tenant", "id"]
+    );
+    let uniques: Vec<_> = table.uniques.iter().map(|u| u.columns.clone()).collect();
+    assert_eq!(uniques, vec![vec!["tenant", "slug"], vec!["tenant"]]);
+    assert_eq!(table.checks[0].name, None);
+
+    let sql = schema.to_ddl("public");
+    println!("{}", sql);
+    assert!(sql.contains("PRIMARY KEY (tenant, id));"));
+    assert!(sql.contains("ADD CONSTRAINT project_tenant_slug_key UNIQUE (tenant, slug);"));
+    assert!(sql.contains("ADD CONSTRAINT project_check CHECK (starts <= ends);"));
+}
+
+#[test]
+fn test_unknown_columns_are_rejected() {
+    match PartialSchema::try_from(Typo::SHAPE) {
+        Err(ConversionError::InvalidAttribute(msg)) => assert!(msg.contains("'missing'")),
+        Err(e) => panic!("Expected InvalidAttribute, got: {:?}", e),
+        Ok(_) => panic!("Expected error, got Ok"),
+    }
+    assert!(matches!(
+        PartialSchema::try_from(NoColumns::SHAPE),
+        Err(ConversionError::InvalidAttribute(_))
+    ));
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[facet(
+    psql::check("CONSTRAINT booking_dates CHECK (starts <= ends)"),
+    psql::check("starts > 0")
+)]
+struct Booking {
+    #[facet(psql::primary_key)]
+    id: i64,
+    #[facet(psql::check("constraint positive_nights check (nights > 0)"))]
+    nights: i32,
+    starts: i32,
+    ends: i32,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[facet(psql::check("CONSTRAINT starts <= ends"))]
+struct Unclosed {
+    starts: i32,
+    ends: i32,
+}
+
+#[test]
+fn test_checks_can_be_named() {
+    let schema = PartialSchema::try_from(Booking::SHAPE).unwrap();
+    let table = &schema.tables[0];
+    let checks: Vec<_> = table
+        .checks
+        .iter()
+        .map(|ck| (ck.name.as_deref(), ck.expression.as_str()))
+        .collect();
+    assert_eq!(
+        checks,
+        vec![
+            (Some("positive_nights"), "nights > 0"),
+            (Some("booking_dates"), "starts <= ends"),
+            (None, "starts > 0"),
+        ]
+    );
+
+    let sql = schema.to_ddl("public");
+    println!("{}", sql);
+    assert!(sql.contains("ADD CONSTRAINT positive_nights CHECK (nights > 0);"));
+    assert!(sql.contains("ADD CONSTRAINT booking_dates CHECK (starts <= ends);"));
+    assert!(sql.contains("ADD CONSTRAINT booking_check CHECK (starts > 0);"));
+}
+
+#[test]
+fn test_malformed_named_check_is_rejected() {
+    match PartialSchema::try_from(Unclosed::SHAPE) {
+        Err(ConversionError::InvalidAttribute(msg)) => {
+            assert!(msg.contains("CONSTRAINT name CHECK (expr)"))
+        }
+        Err(e) => panic!("Expected InvalidAttribute, got: {:?}", e),
+        Ok(_) => panic!("Expected error, got Ok"),
+    }
+}
@@ -0,0 +1,103 @@
+use facet::Facet;
+use facet_psql_schema as psql;
+use facet_psql_schema::*;
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Point(i32, i32);
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Tagged(#[facet(psql::column = "tag")] String, i64);
+
+#[derive(Facet, Debug, PartialEq)]
+#[facet(transparent)]
+struct UserId(i64);
+
+#[derive(Facet, Debug, PartialEq)]
+#[facet(psql::newtype = "domain")]
+struct Email(String);
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[facet(psql::newtype = "domain")]
+struct NotNewtype {
+    a: i32,
+}
+
+#[derive(Facet, Debug, PartialEq)]
+struct Account {
+    #[facet(psql::primary_key)]
+    id: UserId,
+    email: Email,
+    backup: Option<Email>,
+    friends: Vec<UserId>,
+}
+
+fn column_names(table: &Table) -> Vec<&str> {
+    table.columns.iter().map(|c| c.name.as_str()).collect()
+}
+
+#[test]
+fn test_positional_fields_are_named() {
+    let schema = PartialSchema::try_from(Point::SHAPE).unwrap();
+    assert_eq!(column_names(&schema.tables[0]), vec!["value_0", "value_1"]);
+
+    let schema = PartialSchema::try_from(Tagged::SHAPE).unwrap();
+    assert_eq!(column_names(&schema.tables[0]), vec!["tag", "value_1"]);
+}
+
+#[test]
+fn test_newtypes_map_to_their_field() {
+    let schema = PartialSchema::try_from(Account::SHAPE).unwrap();
+    let table = &schema.tables[0];
+    assert!(matches!(table.columns[0].data_type, DataType::BigInt));
+    assert!(matches!(
+        &table.columns[1].data_type,
+        DataType::Domain { name, .. } if name == "email"
+    ));
+    assert!(table.columns[2].nullable);
+    assert!(matches!(
+        &table.columns[3].data_type,
+        DataType::Array(element) if matches!(**element, DataType::BigInt)
+    ));
+
+    assert_eq!(schema.domains.len(), 1);
+    assert!(matches!(schema.domains[0].base_type, DataType::Text));
+    assert!(
+        schema
+            .to_ddl("public")
+            .contains("CREATE DOMAIN public.email AS text;")
+    );
+
+    // On their own, newtypes produce their type and no table
+    let schema = PartialSchema::try_from(Email::SHAPE).unwrap();
+    assert!(schema.tables.is_empty());
+    assert_eq!(schema.domains.len(), 1);
+    let schema = PartialSchema::try_from(UserId::SHAPE).unwrap();
+    assert!(schema.tables.is_empty() && schema.domains.is_empty());
+
+    let err = PartialSchema::try_from(NotNewtype::SHAPE)
+        .err()
+        .expect("not a newtype");
+    assert!(matches!(err, ConversionError::InvalidAttribute(_)));
+}
+
+#[test]
+fn test_newtype_rows_round_trip() {
+    let account = Account {
+        id: UserId(1),
+        email: Email("a@example.com".into()),
+        backup: None,
+        friends: vec![UserId(2), UserId(3)],
+    };
+    let values = RowEncoder::new().values(&account).unwrap();
+    assert_eq!(values[0].1, sea_query::Value::BigInt(Some(1)));
+    assert_eq!(
+        values[1].1,
+        sea_query::Value::String(Some(Box::new("a@example.com".into())))
+    );
+
+    let decoded: Account = RowDecoder::new().decode(&values).unwrap();
+    assert_eq!(decoded, account);
+}
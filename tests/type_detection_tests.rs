@@ -0,0 +1,119 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
+
+use facet::Facet;
+use facet_psql_schema as psql;
+use facet_psql_schema::*;
+
+/// User types whose names contain `Option`, `Vec`, `HashMap` or `String`.
+#[allow(dead_code)]
+#[derive(Facet)]
+struct OptionSet {
+    #[facet(psql::primary_key)]
+    id: i64,
+    enabled: bool,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct VecClock {
+    ticks: u32,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct HashMapEntry {
+    key: String,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+#[repr(u8)]
+enum OptionKind {
+    On,
+    Off,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct StringBuilder {
+    #[facet(psql::primary_key)]
+    id: i64,
+    clock: VecClock,
+    entry: Option<HashMapEntry>,
+    kind: OptionKind,
+    clocks: Vec<VecClock>,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Kinds {
+    boxed: Box<i32>,
+    shared: Arc<String>,
+    cow: Cow<'static, str>,
+    borrowed: &'static str,
+    tags: HashSet<String>,
+    sorted: BTreeSet<i64>,
+    counts: HashMap<String, i32>,
+    ordered: BTreeMap<String, String>,
+    maybe_boxed: Option<Box<f64>>,
+}
+
+fn column<'a>(table: &'a Table, name: &str) -> &'a Column {
+    table.columns.iter().find(|c| c.name == name).unwrap()
+}
+
+#[test]
+fn test_user_types_with_std_like_names() {
+    // A root struct named like `Option` is still a plain table
+    let schema = PartialSchema::try_from(OptionSet::SHAPE).expect("Failed to convert OptionSet");
+    let table = &schema.tables[0];
+    assert_eq!(table.name, "optionset");
+    assert!(!column(table, "id").nullable);
+    assert!(matches!(column(table, "id").data_type, DataType::BigInt));
+    assert!(matches!(
+        column(table, "enabled").data_type,
+        DataType::Boolean
+    ));
+
+    let schema =
+        PartialSchema::try_from(StringBuilder::SHAPE).expect("Failed to convert StringBuilder");
+    let table = &schema.tables[0];
+    assert_eq!(table.name, "stringbuilder");
+
+    let clock = column(table, "clock");
+    assert!(matches!(clock.data_type, DataType::Jsonb));
+    assert!(!clock.nullable, "VecClock is not an Option");
+
+    let entry = column(table, "entry");
+    assert!(matches!(entry.data_type, DataType::Jsonb));
+    assert!(entry.nullable);
+
+    let kind = column(table, "kind");
+    assert!(matches!(&kind.data_type, DataType::Enum { name, .. } if name == "optionkind"));
+    assert!(!kind.nullable);
+
+    assert!(matches!(column(table, "clocks").data_type, DataType::Jsonb));
+}
+
+#[test]
+fn test_definition_kinds() {
+    let schema = PartialSchema::try_from(Kinds::SHAPE).expect("Failed to convert Kinds");
+    let table = &schema.tables[0];
+    let sql = schema.to_ddl("public");
+    println!("{}", sql);
+
+    assert!(sql.contains("boxed integer NOT NULL"));
+    assert!(sql.contains("shared text NOT NULL"));
+    assert!(sql.contains("cow text NOT NULL"));
+    assert!(sql.contains("borrowed text NOT NULL"));
+    assert!(sql.contains("tags text[] NOT NULL"));
+    assert!(sql.contains("sorted bigint[] NOT NULL"));
+    assert!(sql.contains("counts jsonb NOT NULL"));
+    assert!(sql.contains("ordered jsonb NOT NULL"));
+
+    let maybe_boxed = column(table, "maybe_boxed");
+    assert!(matches!(maybe_boxed.data_type, DataType::DoublePrecision));
+    assert!(maybe_boxed.nullable);
+}
@@ -0,0 +1,162 @@
+use std::fmt;
+use std::str::FromStr;
+
+use facet::Facet;
+use facet_psql_schema as psql;
+use facet_psql_schema::*;
+use sea_query::PostgresQueryBuilder;
+
+/// An amount of money, without a `Facet` implementation.
+#[derive(Debug, PartialEq)]
+struct Cents(i64);
+
+impl fmt::Display for Cents {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{:02}", self.0 / 100, self.0 % 100)
+    }
+}
+
+impl FromStr for Cents {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (units, cents) = s.split_once('.').ok_or("no decimal point")?;
+        let parse = |s: &str| s.parse::<i64>().map_err(|e| e.to_string());
+        Ok(Cents(parse(units)? * 100 + parse(cents)?))
+    }
+}
+
+#[derive(Facet, Debug, PartialEq)]
+struct Order {
+    #[facet(psql::primary_key)]
+    id: i64,
+    #[facet(opaque)]
+    total: Cents,
+    #[facet(opaque)]
+    details: serde_json::Value,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Unmapped {
+    #[facet(opaque)]
+    total: Cents,
+}
+
+fn options() -> ConversionOptions {
+    ConversionOptions {
+        types: TypeRegistry::default().register(TypeMapping::opaque_text::<Cents>(
+            DataType::Numeric {
+                precision: Some(12),
+                scale: Some(2),
+            },
+        )),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_registered_types_map_to_their_column_type() {
+    let schema = SchemaBuilder::new()
+        .options(options())
+        .register(Order::SHAPE)
+        .build()
+        .unwrap();
+    let sql = schema.to_ddl("public");
+    println!("{}", sql);
+    assert!(sql.contains("total numeric(12,2) NOT NULL"));
+    assert!(sql.contains("details jsonb NOT NULL"));
+
+    // Without a mapping the type is still unsupported
+    let err = PartialSchema::try_from(Unmapped::SHAPE)
+        .err()
+        .expect("Cents has no mapping");
+    assert!(matches!(err, ConversionError::UnsupportedType(_)));
+    let options = ConversionOptions {
+        types: TypeRegistry::empty(),
+        ..Default::default()
+    };
+    let err = SchemaBuilder::new()
+        .options(options)
+        .register(Order::SHAPE)
+        .build()
+        .err()
+        .expect("the empty registry maps nothing");
+    assert!(matches!(err, ConversionError::UnsupportedType(_)));
+}
+
+#[test]
+fn test_registered_types_round_trip() {
+    let order = Order {
+        id: 1,
+        total: Cents(1250),
+        details: serde_json::json!({ "items": [1, 2] }),
+    };
+    let encoder = RowEncoder::new().options(options());
+    let values = encoder.values(&order).unwrap();
+    assert_eq!(
+        values[1].1,
+        sea_query::Value::String(Some(Box::new("12.50".into())))
+    );
+    assert_eq!(
+        values[2].1,
+        sea_query::Value::Json(Some(Box::new(order.details.clone())))
+    );
+
+    let (sql, _) = encoder
+        .insert(&order, "public")
+        .unwrap()
+        .build(PostgresQueryBuilder);
+    assert!(sql.contains("CAST($2 AS numeric(12,2))"), "{}", sql);
+
+    let decoded: Order = RowDecoder::new()
+        .options(options())
+        .decode(&values)
+        .unwrap();
+    assert_eq!(decoded, order);
+}
+
+#[cfg(all(feature = "uuid", feature = "chrono", feature = "net"))]
+#[test]
+fn test_builtin_mappings() {
+    use chrono::{DateTime, Local, NaiveDate, Utc};
+    use std::net::IpAddr;
+
+    #[derive(Facet, Debug, PartialEq)]
+    struct Session {
+        #[facet(psql::primary_key)]
+        id: uuid::Uuid,
+        started: DateTime<Utc>,
+        seen: DateTime<Local>,
+        day: NaiveDate,
+        address: Option<IpAddr>,
+    }
+
+    let schema = PartialSchema::try_from(Session::SHAPE).unwrap();
+    let sql = schema.to_ddl("public");
+    println!("{}", sql);
+    assert!(sql.contains("id uuid NOT NULL"));
+    assert!(sql.contains("started timestamp with time zone NOT NULL"));
+    assert!(sql.contains("seen timestamp with time zone NOT NULL"));
+    assert!(sql.contains("day date NOT NULL"));
+    assert!(sql.contains("address inet,"));
+
+    let session = Session {
+        id: uuid::Uuid::from_u128(7),
+        started: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+        seen: DateTime::from_timestamp(1_700_000_100, 0)
+            .unwrap()
+            .with_timezone(&Local),
+        day: NaiveDate::from_ymd_opt(2024, 5, 1).unwrap(),
+        address: Some("10.0.0.1".parse().unwrap()),
+    };
+    let values = RowEncoder::new().values(&session).unwrap();
+    let decoded: Session = RowDecoder::new().decode(&values).unwrap();
+    assert_eq!(decoded, session);
+
+    // PostgreSQL's own text form of a timestamptz reads back too
+    let mut values = values;
+    values[1].1 = sea_query::Value::String(Some(Box::new("2023-11-14 22:13:20+00".into())));
+    let decoded: Session = RowDecoder::new().decode(&values).unwrap();
+    assert_eq!(decoded.started, session.started);
+}
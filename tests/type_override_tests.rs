@@ -0,0 +1,134 @@
+use facet::Facet;
+use facet_psql_schema as psql;
+use facet_psql_schema::*;
+use sea_query::{PostgresQueryBuilder, Value};
+
+#[derive(Facet, Debug, PartialEq)]
+struct Product {
+    #[facet(psql::primary_key, psql::type = "int", psql::lossy)]
+    id: i64,
+    #[facet(psql::varchar(80))]
+    name: String,
+    #[facet(psql::citext)]
+    slug: String,
+    #[facet(psql::numeric([12, 2]))]
+    price: f64,
+    #[facet(psql::type = "uuid")]
+    external_id: Option<String>,
+    #[facet(psql::type = "varchar(20)[]")]
+    tags: Vec<String>,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Incompatible {
+    #[facet(psql::type = "uuid")]
+    count: i32,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Narrowed {
+    #[facet(psql::type = "integer")]
+    count: i64,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Padded {
+    #[facet(psql::type = "char(2)")]
+    code: String,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct TooPrecise {
+    #[facet(psql::numeric([4, 6]))]
+    ratio: f64,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Opted {
+    #[facet(psql::type = "char(2)", psql::lossy)]
+    code: String,
+    #[facet(psql::type = "smallint", psql::lossy)]
+    count: i32,
+    #[facet(psql::numeric)]
+    amount: i64,
+    #[facet(psql::type = "bigint")]
+    small: i16,
+}
+
+#[allow(dead_code)]
+#[derive(Facet)]
+struct Unknown {
+    #[facet(psql::type = "varchar(1, 2)")]
+    name: String,
+}
+
+fn product() -> Product {
+    Product {
+        id: 7,
+        name: "Lamp".into(),
+        slug: "lamp".into(),
+        price: 12.5,
+        external_id: Some("67e55044-10b1-426f-9247-bb680e5fe0c8".into()),
+        tags: vec!["home".into()],
+    }
+}
+
+#[test]
+fn test_declared_types_are_used() {
+    let schema = PartialSchema::try_from(Product::SHAPE).unwrap();
+    let sql = schema.to_ddl("public");
+    println!("{}", sql);
+    assert!(sql.contains(
+        "(id integer NOT NULL, name varchar(80) NOT NULL, slug citext NOT NULL, price numeric(12,2) NOT NULL, external_id uuid, tags varchar(20)[] NOT NULL, PRIMARY KEY (id));"
+    ));
+}
+
+#[test]
+fn test_incompatible_or_unknown_types_fail() {
+    for shape in [Incompatible::SHAPE, Unknown::SHAPE, TooPrecise::SHAPE] {
+        let err = PartialSchema::try_from(shape)
+            .err()
+            .expect("declared type is rejected");
+        assert!(matches!(err, ConversionError::InvalidAttribute(_)));
+    }
+}
+
+#[test]
+fn test_declared_types_in_rows() {
+    let values = RowEncoder::new().values(&product()).unwrap();
+    assert_eq!(values[0].1, Value::Int(Some(7)));
+    assert_eq!(values[3].1, Value::String(Some(Box::new("12.5".into()))));
+
+    let insert = RowEncoder::new().insert(&product(), "public").unwrap();
+    let sql = insert.to_string(PostgresQueryBuilder);
+    assert!(sql.contains("CAST('lamp' AS citext)"));
+    assert!(sql.contains("CAST('12.5' AS numeric(12,2))"));
+    assert!(sql.contains("CAST('67e55044-10b1-426f-9247-bb680e5fe0c8' AS uuid)"));
+
+    let decoded: Product = RowDecoder::new().decode(&values).unwrap();
+    assert_eq!(decoded, product());
+}
+
+#[test]
+fn test_lossy_types_need_opting_in() {
+    for shape in [Narrowed::SHAPE, Padded::SHAPE] {
+        match PartialSchema::try_from(shape) {
+            Err(ConversionError::InvalidAttribute(msg)) => assert!(msg.contains("psql::lossy")),
+            Err(e) => panic!("Expected InvalidAttribute, got: {:?}", e),
+            Ok(_) => panic!("Expected {} to be rejected", shape),
+        }
+    }
+
+    let sql = PartialSchema::try_from(Opted::SHAPE)
+        .unwrap()
+        .to_ddl("public");
+    println!("{}", sql);
+    assert!(sql.contains(
+        "(code char(2) NOT NULL, count smallint NOT NULL, amount numeric NOT NULL, small bigint NOT NULL);"
+    ));
+}
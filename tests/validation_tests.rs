@@ -3,10 +3,10 @@ use facet_psql_schema as psql;
 use facet_psql_schema::{ConversionError, PartialSchema};
 
 #[derive(Facet)]
+#[facet(psql::primary_key("id1"))]
 struct DoublePk {
     #[facet(psql::primary_key)]
     id1: u64,
-    #[facet(psql::primary_key)]
     id2: u64,
 }
 